@@ -0,0 +1,27 @@
+//! Compares creating nodes one at a time with `Scene::new_node` against creating them all at once with
+//! `Scene::new_nodes`. Run with `cargo bench --bench node_creation`.
+
+use covalent::scene::Scene;
+use std::time::Instant;
+
+const NODES: usize = 100_000;
+
+fn main() {
+    let scene = Scene::new();
+    let start = Instant::now();
+    for _ in 0..NODES {
+        scene.write().unwrap().new_node();
+    }
+    let one_at_a_time = start.elapsed();
+    assert_eq!(NODES, scene.read().unwrap().node_count());
+
+    let scene = Scene::new();
+    let start = Instant::now();
+    scene.write().unwrap().new_nodes(NODES);
+    let batched = start.elapsed();
+    assert_eq!(NODES, scene.read().unwrap().node_count());
+
+    println!("Creating {} nodes:", NODES);
+    println!("  new_node:  {:?}", one_at_a_time);
+    println!("  new_nodes: {:?}", batched);
+}
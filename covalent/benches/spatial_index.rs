@@ -0,0 +1,54 @@
+//! Compares collider queries with and without a spatial index, on a grid of 8000 nodes like the one in
+//! `simple_example`. Run with `cargo bench --bench spatial_index`.
+
+use covalent::physics::{Aabb, ColliderComponent};
+use covalent::scene::Scene;
+use covalent::{pt3, vec3};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const QUERIES: usize = 1000;
+
+fn main() {
+    let scene = create_scene();
+    let scene = scene.read().unwrap();
+    let linear = time_queries(&scene);
+    scene.enable_spatial_index(0.2);
+    let indexed = time_queries(&scene);
+    println!("{} queries over 8000 colliders:", QUERIES);
+    println!("  linear:  {:?} ({:?} per query)", linear, linear / QUERIES as u32);
+    println!("  indexed: {:?} ({:?} per query)", indexed, indexed / QUERIES as u32);
+}
+
+/// Builds a 20x20x20 grid of nodes, each with a collider around a small quad, as in `create_scene_unoptimised`.
+fn create_scene() -> Arc<RwLock<Scene>> {
+    let scene = Scene::new();
+    for i in (-10..10).map(|x| x as f32) {
+        for j in (-10..10).map(|x| x as f32) {
+            for k in (-10..10).map(|x| x as f32) {
+                let node = scene.write().unwrap().new_node();
+                node.write().unwrap().set_pos(vec3(0.1 * i + 0.05, 0.1 * j + 0.05, 0.02 * k));
+                ColliderComponent::new(node, Aabb::from_centre(pt3(0.0, 0.0, 0.0), vec3(0.04, 0.04, 0.01)));
+            }
+        }
+    }
+    scene
+}
+
+/// Queries small boxes spread over the grid, alternating with rays fired across it.
+fn time_queries(scene: &Scene) -> Duration {
+    let start = Instant::now();
+    let mut hits = 0;
+    for q in 0..QUERIES {
+        let t = q as f32 / QUERIES as f32 * 2.0 - 1.0;
+        if q % 2 == 0 {
+            hits += scene.query_aabb(&Aabb::from_centre(pt3(t, -t * 0.5, 0.0), vec3(0.05, 0.05, 0.05))).len();
+        } else {
+            hits += scene.query_ray(pt3(-1.0, t, 0.0), vec3(1.0, 0.0, 0.0), 0.5).len();
+        }
+    }
+    let elapsed = start.elapsed();
+    // Make sure the queries aren't optimised away.
+    assert!(hits > 0);
+    elapsed
+}
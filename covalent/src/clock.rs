@@ -0,0 +1,166 @@
+/// Controls how time passes in a scene. Every scene contains a clock, which covalent reads at the start of each
+/// frame to work out the `delta` of the `TickEvent`, and how many `FixedTickEvent`s to fire.
+///
+/// The clock may be freely modified from inside event listeners; changes take effect from the next frame.
+pub struct Clock {
+    max_delta: Option<f64>,
+    fixed_timestep: f64,
+    max_fixed_steps: u32,
+    /// Real time that has passed but has not yet been simulated by a `FixedTickEvent`, in seconds.
+    accumulator: f64,
+    paused: bool,
+    time_scale: f64,
+}
+
+impl Clock {
+    /// Sets the largest `delta` (in seconds) that a single `TickEvent` may report.
+    ///
+    /// After a long stall (e.g. hitting a breakpoint, or dragging the window), the real time between two frames
+    /// can be very large. Reporting this directly would make objects teleport and skip through each other, so
+    /// instead the delta is clamped to this value. Set to `None` to disable the clamp.
+    ///
+    /// The clamp only applies to the variable-rate `TickEvent`. The amount of `FixedTickEvent`s per frame is limited
+    /// separately; see `set_max_fixed_steps`.
+    ///
+    /// # Panics
+    /// If `max_delta` is not strictly positive, this function will panic.
+    pub fn set_max_delta(&mut self, max_delta: Option<f64>) {
+        if let Some(max_delta) = max_delta {
+            assert!(max_delta > 0.0, "max delta must be positive, was {}", max_delta);
+        }
+        self.max_delta = max_delta;
+    }
+
+    /// Retrieves the largest `delta` (in seconds) that a single `TickEvent` may report, if any.
+    pub fn max_delta(&self) -> Option<f64> {
+        self.max_delta
+    }
+
+    /// Sets the `delta` (in seconds) of every `FixedTickEvent`. The default is a sixtieth of a second.
+    ///
+    /// # Panics
+    /// If `fixed_timestep` is not strictly positive, this function will panic.
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: f64) {
+        assert!(fixed_timestep > 0.0, "fixed timestep must be positive, was {}", fixed_timestep);
+        self.fixed_timestep = fixed_timestep;
+    }
+
+    /// Retrieves the `delta` (in seconds) of every `FixedTickEvent`.
+    pub fn fixed_timestep(&self) -> f64 {
+        self.fixed_timestep
+    }
+
+    /// Sets the largest amount of `FixedTickEvent`s that may be fired in a single frame. The default is five.
+    ///
+    /// If the game can't keep up with the fixed timestep, each frame would have to simulate more steps than the last
+    /// to catch up, which makes the game slower still. To avoid this, any time that would take more than this many
+    /// steps to simulate is dropped, so the simulation runs slower than real time instead.
+    pub fn set_max_fixed_steps(&mut self, max_fixed_steps: u32) {
+        self.max_fixed_steps = max_fixed_steps;
+    }
+
+    /// Retrieves the largest amount of `FixedTickEvent`s that may be fired in a single frame.
+    pub fn max_fixed_steps(&self) -> u32 {
+        self.max_fixed_steps
+    }
+
+    /// Pauses or resumes the passage of time in the scene. While paused, no `TickEvent`s, `FixedTickEvent`s or
+    /// `StaggeredTickEvent`s are fired, so gameplay stops, but the scene is still rendered and input events are still
+    /// handled, e.g. so that a pause menu can be used. Time spent paused is not caught up on when resuming.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns true if time is paused in the scene; see `set_paused`.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets how fast time passes in the scene, relative to real time. For example, `0.5` gives slow motion and `2.0`
+    /// fast-forwards. The default is `1.0`. Negative values are treated as zero, which freezes time much like
+    /// `set_paused`, except that tick events are still fired, with a `delta` of zero.
+    ///
+    /// The `delta` of every `TickEvent` is multiplied by the time scale, and `FixedTickEvent`s are fired more or less
+    /// often, so simulations that integrate over `delta` follow the time scale. Frame times reported by the `Context`,
+    /// such as `fps`, are measured in real time.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Retrieves how fast time passes in the scene, relative to real time; see `set_time_scale`.
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Adds the real time elapsed since the last frame, scaled by the time scale, to the time waiting to be simulated,
+    /// and returns how many `FixedTickEvent`s should be fired this frame.
+    pub(crate) fn fixed_steps(&mut self, delta: f64) -> u32 {
+        self.accumulator += delta * self.time_scale;
+        let steps = (self.accumulator / self.fixed_timestep).floor();
+        if steps > self.max_fixed_steps as f64 {
+            // Drop the time we can't catch up on, keeping only the partial step.
+            self.accumulator %= self.fixed_timestep;
+            self.max_fixed_steps
+        } else {
+            self.accumulator -= steps * self.fixed_timestep;
+            steps as u32
+        }
+    }
+
+    /// Converts the real time elapsed since the last frame into the `delta` to report to tick listeners.
+    /// The real time is clamped to the maximum delta before it is scaled by the time scale.
+    pub(crate) fn clamp_delta(&self, delta: f64) -> f64 {
+        let delta = match self.max_delta {
+            Some(max_delta) => delta.min(max_delta),
+            None => delta,
+        };
+        delta * self.time_scale
+    }
+}
+
+impl Default for Clock {
+    /// Creates a clock with a maximum delta of a tenth of a second, and sixty fixed steps per second.
+    fn default() -> Self {
+        Clock {
+            max_delta: Some(0.1),
+            fixed_timestep: 1.0 / 60.0,
+            max_fixed_steps: 5,
+            accumulator: 0.0,
+            paused: false,
+            time_scale: 1.0,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_steps_accumulate() {
+        let mut clock = Clock::default();
+        clock.set_fixed_timestep(0.25);
+        assert_eq!(0, clock.fixed_steps(0.125));
+        assert_eq!(1, clock.fixed_steps(0.25));
+        assert_eq!(2, clock.fixed_steps(0.5));
+        // A long stall only runs the maximum amount of steps, and the rest of the time is dropped.
+        assert_eq!(5, clock.fixed_steps(10.0));
+        assert_eq!(0, clock.fixed_steps(0.0625));
+        assert_eq!(1, clock.fixed_steps(0.0625));
+    }
+
+    #[test]
+    fn time_scale_slows_ticks() {
+        let mut clock = Clock::default();
+        clock.set_fixed_timestep(0.25);
+        clock.set_time_scale(0.5);
+        assert_eq!(0.025, clock.clamp_delta(0.05));
+        // The maximum delta limits real time, before it is scaled.
+        assert_eq!(0.05, clock.clamp_delta(1.0));
+        assert_eq!(0, clock.fixed_steps(0.25));
+        assert_eq!(1, clock.fixed_steps(0.25));
+
+        clock.set_time_scale(-1.0);
+        assert_eq!(0.0, clock.time_scale());
+        assert_eq!(0.0, clock.clamp_delta(0.05));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A single line written to the `DebugConsole`.
+pub struct DebugConsoleLine {
+    /// The text contained in this line.
+    pub text: String,
+    /// When this line was written to the console.
+    pub time: Instant,
+}
+
+/// An on-screen console for debugging messages. Only the last `capacity` lines are stored; writing more lines
+/// discards the oldest ones. Lines fade out once they are older than `lifetime`.
+///
+/// Each scene contains a debug console. Its visible lines are drawn as screen-space text by any `PipelinePhase::Text`
+/// that is given a `console_font`.
+/// Every line written is also sent to the `log` crate at the debug level.
+pub struct DebugConsole {
+    lines: VecDeque<DebugConsoleLine>,
+    capacity: usize,
+    lifetime: Duration,
+    fade_time: Duration,
+}
+
+impl DebugConsole {
+    /// Creates a debug console that stores at most `capacity` lines.
+    /// Lines stay fully visible for `lifetime`, then fade out over the next second.
+    pub fn new(capacity: usize, lifetime: Duration) -> DebugConsole {
+        DebugConsole {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+            lifetime,
+            fade_time: Duration::from_secs(1),
+        }
+    }
+
+    /// Writes a line of text to the console.
+    pub fn println(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        log::debug!("[console] {}", text);
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(DebugConsoleLine {
+            text,
+            time: Instant::now()
+        });
+    }
+
+    /// Removes all lines from the console.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Changes the maximum amount of lines stored in the console, discarding the oldest lines if required.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        while self.lines.len() > capacity {
+            self.lines.pop_front();
+        }
+        self.capacity = capacity;
+    }
+
+    /// Retrieves the maximum amount of lines stored in the console.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Sets how long lines remain fully visible before they start to fade out.
+    pub fn set_lifetime(&mut self, lifetime: Duration) {
+        self.lifetime = lifetime;
+    }
+
+    /// Iterates over the lines that are currently visible, oldest first, along with their opacity
+    /// (between zero and one). Lines that have completely faded out are not returned.
+    pub fn visible_lines(&self) -> impl Iterator<Item=(&DebugConsoleLine, f32)> {
+        let now = Instant::now();
+        let lifetime = self.lifetime;
+        let fade_time = self.fade_time;
+        // Lines are stored in chronological order, so skip the faded-out lines at the front.
+        let first_visible = self.lines.iter()
+            .position(|line| now.duration_since(line.time) < lifetime + fade_time)
+            .unwrap_or(self.lines.len());
+        self.lines.iter().skip(first_visible).map(move |line| {
+            let age = now.duration_since(line.time);
+            let alpha = if age <= lifetime {
+                1.0
+            } else {
+                1.0 - (age - lifetime).as_secs_f32() / fade_time.as_secs_f32()
+            };
+            (line, alpha)
+        })
+    }
+}
+
+impl Default for DebugConsole {
+    /// Creates a debug console storing 16 lines, each visible for five seconds.
+    fn default() -> Self {
+        DebugConsole::new(16, Duration::from_secs(5))
+    }
+}
@@ -6,6 +6,41 @@ pub struct DisplayHints {
     pub width: u32,
     /// The default height of the window, when this can be defined.
     pub height: u32,
+    /// If true, frames are presented in step with the display's refresh rate, which avoids tearing and stops the
+    /// application from rendering more frames than can be shown. Some platforms ignore this.
+    pub vsync: bool,
+    /// If this is `Some`, the backend waits between frames so that at most this many frames are rendered per second.
+    /// This is useful to save power, e.g. on laptops, when vsync is disabled or unavailable. A value of zero is
+    /// treated as `None`, i.e. no cap.
+    pub max_fps: Option<u32>,
+    /// Whether the window starts windowed or fullscreen. This can be changed later with `Scene::set_window_mode`.
+    pub mode: WindowMode,
+    /// The icon to show in the window's title bar and the taskbar, if the platform supports this, as
+    /// `(rgba, width, height)`. The image contains four bytes (red, green, blue and alpha) for each pixel, with rows
+    /// ordered from top to bottom. If this is `None`, or the image is invalid, the platform's default icon is used.
+    pub icon: Option<(Vec<u8>, u32, u32)>,
+    /// If true, the user can resize the window.
+    pub resizable: bool,
+    /// If this is `Some`, the user can't make the window smaller than this `(width, height)`.
+    pub min_size: Option<(u32, u32)>,
+    /// If this is `Some`, the user can't make the window larger than this `(width, height)`.
+    pub max_size: Option<(u32, u32)>,
+}
+
+/// Whether the window is windowed or fills a whole monitor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowMode {
+    /// A normal window with a border and title bar.
+    Windowed,
+    /// A borderless window covering the whole of the monitor that the window is on, at the monitor's current
+    /// resolution. Switching to and from this mode is quick, so it suits an Alt+Enter toggle.
+    Borderless,
+    /// Exclusive fullscreen on the given monitor, at its highest resolution. Monitors are numbered in the order the
+    /// platform lists them. If there is no monitor with the given index, the primary monitor is used instead.
+    Fullscreen {
+        /// The index of the monitor to go fullscreen on.
+        monitor_index: usize,
+    },
 }
 
 impl DisplayHints {
@@ -15,6 +50,13 @@ impl DisplayHints {
             title: String::from("Covalent"),
             width: 1024,
             height: 768,
+            vsync: true,
+            max_fps: None,
+            mode: WindowMode::Windowed,
+            icon: None,
+            resizable: true,
+            min_size: None,
+            max_size: None,
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,120 @@
 use crate::events::Event;
+use crate::scene::NodeId;
 
 /// An event automatically fired every frame.
 pub struct TickEvent {
-    /// The time that has passed between this frame and the last frame, in seconds.
+    /// The time that has passed between this frame and the last frame, in seconds, scaled by the scene's time scale
+    /// (see `Clock::set_time_scale`).
     pub(crate) delta: f64,
+    /// The number of `TickEvent`s fired before this one, so the first tick is frame zero.
+    /// Frames during which the scene is paused are not counted.
+    pub frame: u64,
+    /// The total of the `delta`s of every `TickEvent` so far, including this one, in seconds. This is the gameplay
+    /// time since the game started, so it follows the time scale and stops while the scene is paused.
+    pub elapsed: f64,
 }
 impl Event for TickEvent {}
+
+/// An event fired at a constant rate, regardless of the framerate. This may be fired zero or more times per frame,
+/// before the `TickEvent`, to simulate all of the time that has passed since the last frame.
+///
+/// Use this instead of `TickEvent` for physics and other simulations that must be deterministic, e.g. for
+/// reproducible replays. The rate is configured by the scene's `Clock`.
+pub struct FixedTickEvent {
+    /// The fixed timestep, in seconds. This is the same for every `FixedTickEvent` until the clock is changed.
+    pub delta: f64,
+}
+impl Event for FixedTickEvent {}
+
+/// An event fired once, just before the application exits, e.g. because the user closed the window.
+/// Listen for this to save the game or clean up other resources. The window may still be visible while listeners
+/// run, but no more frames will be rendered.
+pub struct ShutdownEvent {}
+impl Event for ShutdownEvent {}
+
+/// An event fired when the transformation of a node changes, e.g. because `Node::set_pos` was called, so that
+/// systems which cache where nodes are, such as the spatial index of `Scene::enable_spatial_index`, can catch up.
+///
+/// # Coalescing
+/// Changes are queued up and fired at the start of the next frame, by `Context::begin_frame`, at most once per node
+/// per frame, no matter how many times the node changed. Moving a node also moves its descendants, so they are each
+/// sent an event too. Events are fired even while the scene is paused, in order of node ID.
+///
+/// # Feedback loops
+/// Listeners may move nodes in response, e.g. to keep one node attached to another. Those changes are fired at the
+/// start of the following frame, not the current one, so listeners can't loop forever within a frame; but listeners
+/// that always move a node in response to it moving will receive an event every frame.
+pub struct NodeTransformChangedEvent {
+    /// The node whose transformation changed.
+    pub node_id: NodeId,
+}
+impl Event for NodeTransformChangedEvent {}
+
+/// An event fired every frame, but only to a rotating subset of its listeners, to spread the cost of expensive
+/// per-node work (such as AI pathfinding) across several frames.
+///
+/// Listeners are split into groups, and each frame only one group receives the event, in round-robin order.
+/// See `TickStagger` to configure the amount of groups.
+///
+/// # Latency
+/// With `n` groups, each listener only updates once every `n` frames, so it reacts to changes up to `n - 1` frames
+/// late. Only use this for work that can tolerate this delay. The `delta` of the event accounts for all of the
+/// frames since the listener last updated, so time-based motion still moves at the correct speed.
+pub struct StaggeredTickEvent {
+    /// The time that has passed since this listener last received a `StaggeredTickEvent`, in seconds.
+    pub delta: f64,
+}
+impl Event for StaggeredTickEvent {}
+
+/// Decides which listeners receive each `StaggeredTickEvent`.
+pub struct TickStagger {
+    /// The time that has passed since each group last received an event, in seconds.
+    pending: Vec<f64>,
+    /// The group that will receive the next event.
+    next_group: usize,
+}
+
+impl TickStagger {
+    /// Splits the listeners to `StaggeredTickEvent` into the given amount of groups, so each listener receives an
+    /// event once every `groups` frames.
+    ///
+    /// # Panics
+    /// If `groups` is zero.
+    pub fn set_groups(&mut self, groups: usize) {
+        assert!(groups > 0, "a tick stagger must have at least one group");
+        self.pending = vec![0.0; groups];
+        self.next_group = 0;
+    }
+
+    /// Retrieves the amount of groups that listeners are split into.
+    pub fn groups(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Advances to the next frame, which lasted `delta` seconds.
+    /// Returns the group that should receive an event this frame, and the delta to report to that group.
+    pub(crate) fn advance(&mut self, delta: f64) -> (usize, f64) {
+        for pending in &mut self.pending {
+            *pending += delta;
+        }
+        let group = self.next_group;
+        self.next_group = (self.next_group + 1) % self.pending.len();
+        (group, std::mem::replace(&mut self.pending[group], 0.0))
+    }
+
+    /// Works out which group the listener with the given ID belongs to, when listeners are split into `groups`
+    /// groups. This doesn't borrow the stagger, so it can be used while listeners run without keeping it locked.
+    pub(crate) fn group_of(id: i64, groups: usize) -> usize {
+        (id as usize) % groups
+    }
+}
+
+impl Default for TickStagger {
+    /// Splits listeners into four groups.
+    fn default() -> Self {
+        TickStagger {
+            pending: vec![0.0; 4],
+            next_group: 0,
+        }
+    }
+}
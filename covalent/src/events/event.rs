@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use rayon::prelude::*;
 use rayon::iter::Either;
 
 /// `lock_data` structs can listen for events. When the event is fired, the `lock_data` will try
 /// to get references to the required variables defined in the macro. This has the possibility to
 /// fail if one or more of the required variables could not be locked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ListenError {
     /// The required variables are stored as `Weak` references. If the `Weak` could not be upgraded
     /// to an `Arc`, the variable must have been deleted. In this case, `RequirementDeleted` will be
@@ -13,10 +14,53 @@ pub enum ListenError {
     /// One of the variables could not be locked at this time. This could be, for example, because
     /// another `lock_data` currently has the lock. The listener will be retried later, hopefully
     /// after the lock is released by the other thread.
-    LockUnavailable
+    LockUnavailable {
+        /// The name of the variable that could not be locked, as written in the `lock_data` macro.
+        /// Useful for finding out which data is contended.
+        field: &'static str
+    }
+}
+
+/// What a listener wants to happen to itself after it has successfully executed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ListenOutcome {
+    /// Keep listening for future events.
+    Keep,
+    /// Remove this listener from the event handler, even though its data still exists. Useful for one-shot
+    /// listeners, such as timers.
+    Remove,
+    /// Keep listening, but mark the event as consumed so that it is not passed on to any listeners with a lower
+    /// priority. This only has an effect on ordered event handlers; see `EventHandler::set_ordered`.
+    Consume
+}
+
+/// Listener functions that don't return anything keep listening.
+impl From<()> for ListenOutcome {
+    fn from(_: ()) -> Self {
+        ListenOutcome::Keep
+    }
 }
 
 type ListenerID = i64;
+/// The function that a `Listener` runs when its event is fired.
+type ListenFn<E> = Box<dyn Fn(&E) -> Result<ListenOutcome, ListenError> + Send + Sync>;
+
+/// Listeners whose locks were unavailable, along with the name of the field that each could not lock.
+type Contended<'a, E> = Vec<(&'a ListenerID, &'a Listener<E>, &'static str)>;
+
+/// How many times listeners whose locks are unavailable are retried before they miss the event.
+/// See the "Contention" section of the `EventHandler` documentation.
+pub const MAX_RETRIES: u32 = 64;
+
+/// Waits a little before listeners are retried for the given time, so the threads holding their locks can make
+/// progress. The first few retries only yield the thread; later ones sleep for longer and longer.
+fn backoff(retry: u32) {
+    if retry <= 8 {
+        std::thread::yield_now();
+    } else {
+        std::thread::sleep(std::time::Duration::from_micros(10 * u64::from(retry - 8)));
+    }
+}
 
 /// Listens for an event. Don't create these yourself, use the `lock_data` macro to automatically
 /// create listeners.
@@ -28,9 +72,13 @@ pub struct Listener<E>
     /// listeners if required.
     pub id: ListenerID,
 
-    /// When the function first returns false (i.e. some elements of the listen function could not
-    /// be upgraded to strong Arcs), this listener is deleted.
-    pub func: Box<dyn Fn(&E) -> Result<(), ListenError> + Send + Sync>
+    /// Listeners with a higher priority are executed first by ordered event handlers.
+    /// Unordered event handlers ignore this.
+    pub priority: i32,
+
+    /// When the function first returns `RequirementDeleted` (i.e. some elements of the listen function could not
+    /// be upgraded to strong Arcs) or `Ok(ListenOutcome::Remove)`, this listener is deleted.
+    pub func: ListenFn<E>
 }
 
 impl<E> Listener<E>
@@ -43,7 +91,7 @@ impl<E> Listener<E>
     /// If either of the weak variables fail to upgrade to strong smart pointers (`Arc` variables), the node is considered deleted.
     /// In this case, the listener can never fire. The function then returns false without doing anything.
     /// Otherwise, the function will fire, and true will be returned.
-    fn execute(&self, e: &E) -> Result<(), ListenError> {
+    fn execute(&self, e: &E) -> Result<ListenOutcome, ListenError> {
         (*self.func)(e)
     }
 }
@@ -51,9 +99,28 @@ impl<E> Listener<E>
 /// A generic event. See `EventHandler` for more information.
 pub trait Event: Send + Sync {}
 
+/// Executes listeners whenever an event is fired.
+///
+/// By default, listeners are executed in parallel, in no particular order. Event handlers can instead be made
+/// ordered with `set_ordered`, which executes listeners one at a time from the highest priority to the lowest, and
+/// lets listeners consume events so that listeners with lower priorities don't see them.
+///
+/// # Contention
+/// A listener can't run while another thread holds one of the locks it needs, e.g. because two components lock
+/// each other's data during the same tick. Such listeners are retried, yielding the thread and then sleeping for a
+/// little longer between each attempt so the other thread can make progress. After `MAX_RETRIES` retries, a
+/// listener that still can't run is skipped for this event, with a warning naming the unavailable fields. It stays
+/// registered, so it receives later events. This means contended listeners may miss events, but two listeners that
+/// lock each other's data can never spin forever.
 pub struct EventHandler<E: Event> {
     next_id: ListenerID,
-    set: HashMap<ListenerID, Listener<E>>
+    set: BTreeMap<ListenerID, Listener<E>>,
+    /// If true, listeners are executed sequentially in order of priority.
+    ordered: bool,
+    /// The maximum amount of listeners to execute in a single call to `handle`, if any.
+    listener_cap: Option<usize>,
+    /// When the listener cap is in use, the ID of the listener to resume from in the next call to `handle`.
+    next_listener: ListenerID
 }
 
 impl<E> Default for EventHandler<E>
@@ -61,7 +128,10 @@ impl<E> Default for EventHandler<E>
     fn default() -> Self {
         Self {
             next_id: 0,
-            set: HashMap::new()
+            set: BTreeMap::new(),
+            ordered: false,
+            listener_cap: None,
+            next_listener: 0
         }
     }
 }
@@ -78,13 +148,42 @@ impl<E> EventHandler<E>
         self.set.insert(l.id, l);
     }
 
+    /// Executes the given listeners in parallel, retrying those whose locks were unavailable until they succeed or
+    /// `MAX_RETRIES` is reached, in which case they miss this event.
     /// Returns a list of listeners to permanently remove from the event handler.
-    fn handle_iter<'a>(e: E, to_try: impl rayon::iter::ParallelIterator<Item=(&'a ListenerID, &'a Listener<E>)>) -> Vec<i64> where E: 'a {
-        let (to_retry, mut to_remove) : (Vec<(&'a ListenerID, &'a Listener<E>)>, Vec<ListenerID>) = to_try.filter_map(|(k, v)| {
-            match v.execute(&e) {
-                Ok(_) => {
+    fn handle_iter<'a>(e: &E, to_try: impl rayon::iter::ParallelIterator<Item=(&'a ListenerID, &'a Listener<E>)>) -> Vec<ListenerID> where E: 'a {
+        let (mut to_retry, mut to_remove) = EventHandler::try_listeners(e, to_try);
+        let mut retries = 0;
+        while !to_retry.is_empty() {
+            if retries == MAX_RETRIES {
+                let fields = to_retry.iter().map(|(_, _, field)| *field).collect::<std::collections::BTreeSet<_>>();
+                log::warn!("{} listeners could not lock their data after {} retries, so they missed an event; unavailable fields: {:?}",
+                    to_retry.len(), retries, fields);
+                break;
+            }
+            retries += 1;
+            backoff(retries);
+            let to_try = to_retry.into_par_iter().map(|(k, v, _)| (k, v));
+            let (still_to_retry, mut also_to_remove) = EventHandler::try_listeners(e, to_try);
+            to_retry = still_to_retry;
+            to_remove.append(&mut also_to_remove);
+        }
+        to_remove
+    }
+
+    /// Executes the given listeners in parallel once.
+    /// Returns the listeners whose locks were unavailable, which should be retried, along with the name of the field
+    /// each could not lock, and the listeners to permanently remove from the event handler.
+    fn try_listeners<'a>(e: &E, to_try: impl rayon::iter::ParallelIterator<Item=(&'a ListenerID, &'a Listener<E>)>) -> (Contended<'a, E>, Vec<ListenerID>) where E: 'a {
+        to_try.filter_map(|(k, v)| {
+            match v.execute(e) {
+                Ok(ListenOutcome::Keep) | Ok(ListenOutcome::Consume) => {
                     None
                 },
+                Ok(ListenOutcome::Remove) => {
+                    // Treat this like a deleted requirement, so the listener is removed below.
+                    Some((k, v, ListenError::RequirementDeleted))
+                },
                 Err(e) => {
                     // If there was some kind of error, we need to store the error with the listener.
                     Some((k, v, e))
@@ -93,25 +192,274 @@ impl<E> EventHandler<E>
         }).partition_map(|(k, v, e)| {
             // The Either::Left is for listeners that can retry. The Either::Right is for listeners that must now be deleted.
             match e {
-                ListenError::LockUnavailable => {
-                    Either::Left((k, v))
+                ListenError::LockUnavailable { field } => {
+                    Either::Left((k, v, field))
                 },
                 ListenError::RequirementDeleted => {
                     Either::Right(*k)
                 }
             }
-        });
+        })
+    }
 
-        if !to_retry.is_empty() {
-            to_remove.append(&mut EventHandler::handle_iter(e, to_retry.into_par_iter()));
+    /// Executes each listener in turn and stops early if one consumes the event.
+    /// Returns a list of listeners to permanently remove from the event handler.
+    fn handle_ordered(&self, e: &E) -> Vec<ListenerID> {
+        let mut order = self.set.values()
+            .map(|l| (std::cmp::Reverse(l.priority), l.id))
+            .collect::<Vec<_>>();
+        order.sort();
+
+        let mut to_remove = Vec::new();
+        for (_, id) in order {
+            let listener = &self.set[&id];
+            // Later listeners must not run before this one, so retry it straight away until its locks are available.
+            let mut retries = 0;
+            let result = loop {
+                match listener.execute(e) {
+                    Err(ListenError::LockUnavailable { .. }) if retries < MAX_RETRIES => {
+                        retries += 1;
+                        backoff(retries);
+                    },
+                    Err(ListenError::LockUnavailable { field }) => {
+                        log::warn!("a listener could not lock {} after {} retries, so it missed an event", field, retries);
+                        break Ok(ListenOutcome::Keep);
+                    },
+                    result => break result,
+                }
+            };
+            match result {
+                Ok(ListenOutcome::Keep) => {},
+                Ok(ListenOutcome::Consume) => break,
+                Ok(ListenOutcome::Remove) | Err(_) => to_remove.push(id),
+            }
         }
         to_remove
     }
 
-    /// Handle the given event by passing it through all provided listeners.
-    pub fn handle(&mut self, e: E) {
-        for k in EventHandler::handle_iter(e, self.set.par_iter()) {
+    /// Sets whether listeners are executed sequentially in order of priority, instead of in parallel.
+    /// Ordered event handlers ignore the listener cap.
+    pub fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
+
+    /// Returns true if listeners are executed sequentially in order of priority.
+    pub fn ordered(&self) -> bool {
+        self.ordered
+    }
+
+    /// Limits the amount of listeners that are executed in each call to `handle`, to bound the time spent handling
+    /// an event when it has a huge number of listeners. `None` (the default) removes the limit.
+    ///
+    /// When capped, listeners are executed in round-robin order: each call to `handle` continues from where the
+    /// previous call stopped. Listeners that are skipped miss that event entirely, and only receive a later one.
+    /// This trades latency for stable frame times, so it is only suitable for events that listeners can safely miss,
+    /// and that do not rely on the order in which listeners run.
+    pub fn set_listener_cap(&mut self, cap: Option<usize>) {
+        self.listener_cap = cap;
+    }
+
+    /// Retrieves the maximum amount of listeners that are executed in each call to `handle`, if any.
+    pub fn listener_cap(&self) -> Option<usize> {
+        self.listener_cap
+    }
+
+    /// Handle the given event by passing it through all provided listeners,
+    /// or the next few listeners if a listener cap is set.
+    ///
+    /// The event is only borrowed, so the same event can be passed to several event handlers.
+    pub fn handle(&mut self, e: &E) {
+        let to_remove = match self.listener_cap {
+            _ if self.ordered => self.handle_ordered(e),
+            Some(cap) if cap < self.set.len() => {
+                let chosen = self.set.range(self.next_listener..)
+                    .chain(self.set.range(..self.next_listener))
+                    .take(cap)
+                    .collect::<Vec<_>>();
+                if let Some((last, _)) = chosen.last() {
+                    self.next_listener = *last + 1;
+                }
+                EventHandler::handle_iter(e, chosen.into_par_iter())
+            },
+            _ => EventHandler::handle_iter(e, self.set.par_iter()),
+        };
+        for k in to_remove {
+            self.set.remove(&k);
+        }
+    }
+
+    /// Handle the given event by passing it through only the listeners whose IDs match the given filter.
+    pub fn handle_filtered(&mut self, e: &E, filter: impl Fn(ListenerID) -> bool + Sync) {
+        for k in EventHandler::handle_iter(e, self.set.par_iter().filter(|(k, _)| filter(**k))) {
             self.set.remove(&k);
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct TestEvent {}
+    impl Event for TestEvent {}
+
+    #[test]
+    fn listener_cap_is_round_robin() {
+        let counts = Arc::new((0..10).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+        let mut handler = EventHandler::<TestEvent>::default();
+        for _ in 0..10 {
+            let id = handler.new_id();
+            let counts = Arc::clone(&counts);
+            handler.insert(Listener {
+                id,
+                priority: 0,
+                func: Box::new(move |_| {
+                    counts[id as usize].fetch_add(1, Ordering::SeqCst);
+                    Ok(ListenOutcome::Keep)
+                })
+            });
+        }
+
+        handler.set_listener_cap(Some(3));
+        for _ in 0..4 {
+            handler.handle(&TestEvent {});
+        }
+        let counts = counts.iter().map(|c| c.load(Ordering::SeqCst)).collect::<Vec<_>>();
+        assert_eq!(vec![2, 2, 1, 1, 1, 1, 1, 1, 1, 1], counts);
+    }
+
+    #[test]
+    fn listeners_can_remove_themselves() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut handler = EventHandler::<TestEvent>::default();
+        let id = handler.new_id();
+        let count2 = Arc::clone(&count);
+        handler.insert(Listener {
+            id,
+            priority: 0,
+            func: Box::new(move |_| {
+                count2.fetch_add(1, Ordering::SeqCst);
+                Ok(ListenOutcome::Remove)
+            })
+        });
+
+        handler.handle(&TestEvent {});
+        handler.handle(&TestEvent {});
+        assert_eq!(1, count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn ordered_listeners_can_consume() {
+        use std::sync::Mutex;
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut handler = EventHandler::<TestEvent>::default();
+        handler.set_ordered(true);
+        for (priority, outcome) in [(0, ListenOutcome::Keep), (10, ListenOutcome::Keep), (5, ListenOutcome::Consume), (-5, ListenOutcome::Keep)] {
+            let id = handler.new_id();
+            let log = Arc::clone(&log);
+            handler.insert(Listener {
+                id,
+                priority,
+                func: Box::new(move |_| {
+                    log.lock().unwrap().push(priority);
+                    Ok(outcome)
+                })
+            });
+        }
+
+        handler.handle(&TestEvent {});
+        assert_eq!(vec![10, 5], *log.lock().unwrap());
+    }
+
+    crate::lock_data! {
+        FieldsData
+        free: read usize,
+        busy: write usize
+    }
+
+    #[test]
+    fn unavailable_locks_are_named() {
+        let free = Arc::new(std::sync::RwLock::new(0));
+        let busy = Arc::new(std::sync::RwLock::new(0));
+        let data = Arc::new(std::sync::RwLock::new(FieldsData {
+            free: Arc::downgrade(&free),
+            busy: Arc::downgrade(&busy),
+        }));
+        let handler = Arc::new(std::sync::RwLock::new(EventHandler::<TestEvent>::default()));
+        FieldsData::listen(&data, &handler, |_event, _free, _busy| {});
+
+        let _guard = busy.read().unwrap();
+        let handler = handler.read().unwrap();
+        let listener = handler.set.values().next().unwrap();
+        assert_eq!(Err(ListenError::LockUnavailable { field: "busy" }), listener.execute(&TestEvent {}));
+    }
+
+    #[test]
+    fn contended_listeners_give_up() {
+        use std::sync::Mutex;
+        let lock = Arc::new(Mutex::new(()));
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut handler = EventHandler::<TestEvent>::default();
+        for _ in 0..1000 {
+            let id = handler.new_id();
+            let lock = Arc::clone(&lock);
+            let count = Arc::clone(&count);
+            handler.insert(Listener {
+                id,
+                priority: 0,
+                func: Box::new(move |_| match lock.try_lock() {
+                    Ok(_) => {
+                        count.fetch_add(1, Ordering::SeqCst);
+                        Ok(ListenOutcome::Keep)
+                    },
+                    Err(_) => Err(ListenError::LockUnavailable { field: "lock" }),
+                })
+            });
+        }
+
+        // While the lock is held, every listener is retried a bounded number of times, then misses the event.
+        let guard = lock.lock().unwrap();
+        handler.handle(&TestEvent {});
+        drop(guard);
+        assert_eq!(0, count.load(Ordering::SeqCst));
+
+        // The listeners are kept, so they receive later events.
+        handler.handle(&TestEvent {});
+        assert_eq!(1000, count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn contended_ordered_listeners_are_skipped() {
+        use std::sync::Mutex;
+        let lock = Arc::new(Mutex::new(()));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut handler = EventHandler::<TestEvent>::default();
+        handler.set_ordered(true);
+        for priority in [1, 0] {
+            let id = handler.new_id();
+            let lock = Arc::clone(&lock);
+            let log = Arc::clone(&log);
+            handler.insert(Listener {
+                id,
+                priority,
+                func: Box::new(move |_| {
+                    // Only the first listener needs the contended lock.
+                    let _guard = if priority == 1 {
+                        Some(lock.try_lock().map_err(|_| ListenError::LockUnavailable { field: "lock" })?)
+                    } else {
+                        None
+                    };
+                    log.lock().unwrap().push(priority);
+                    Ok(ListenOutcome::Keep)
+                })
+            });
+        }
+
+        let guard = lock.lock().unwrap();
+        handler.handle(&TestEvent {});
+        drop(guard);
+        handler.handle(&TestEvent {});
+        assert_eq!(vec![0, 1, 0], *log.lock().unwrap());
+    }
+}
@@ -3,7 +3,8 @@ use crate::input::*;
 
 /// An event from the keyboard has been received.
 /// Adapted from the `winit` crate, version 0.22.2.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyboardEvent {
     /// Identifies the physical key pressed.
     ///
@@ -20,23 +21,128 @@ pub struct KeyboardEvent {
     /// Use when the semantics of the key are more important than the physical location of the key, such as when
     /// implementing appropriate behavior for "page up."
     pub virtual_keycode: Option<VirtualKeyCode>,
+
+    /// True if this is a repeated press generated by the operating system while the key is held down, rather than
+    /// the key actually being pressed. Listeners for actions like jumping should ignore repeats, but text input
+    /// should not. This is always false for releases.
+    ///
+    /// This is worked out by `Context::process_keyboard_event`, so graphics backends can leave it false.
+    pub is_repeat: bool,
 }
 impl Event for KeyboardEvent {}
 
 /// The mouse has been moved by a certain amount of pixels in the X and Y directions.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MouseDeltaEvent {
     /// The difference in pixels between the location of the mouse last frame and this frame.
     pub delta: cgmath::Vector2<f64>
 }
 impl Event for MouseDeltaEvent {}
 
+/// The cursor has moved within the window.
+/// Unlike `MouseDeltaEvent`, this reports where the cursor is, rather than how far the mouse moved, so is suited to
+/// pointing at things on the screen.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CursorMovedEvent {
+    /// The position of the cursor in physical pixels, relative to the top left corner of the window, with y
+    /// increasing downwards. Backends whose windowing library reports logical pixels convert them using the window's
+    /// scale factor, so positions are consistent across platforms and displays.
+    /// See also `InputState::normalised_mouse_position`.
+    pub pos: cgmath::Vector2<f64>
+}
+impl Event for CursorMovedEvent {}
+
+/// The cursor has entered the window.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CursorEnteredEvent {}
+impl Event for CursorEnteredEvent {}
+
+/// The cursor has left the window. No `CursorMovedEvent`s are fired until it enters the window again.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CursorLeftEvent {}
+impl Event for CursorLeftEvent {}
+
+/// The mouse wheel, or a touchpad, has been scrolled.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseScrollEvent {
+    /// The amount scrolled horizontally and vertically. Positive values are right and away from the user (up).
+    pub delta: cgmath::Vector2<f64>,
+    /// The unit that `delta` is measured in.
+    pub unit: ScrollUnit,
+}
+impl Event for MouseScrollEvent {}
+
+/// A mouse button has been pressed or released.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseButtonEvent {
+    /// The button that was pressed or released.
+    pub button: MouseButton,
+    /// Describes the input state of the button, i.e. pressed or released.
+    pub state: ElementState,
+}
+impl Event for MouseButtonEvent {}
+
 /// The window that covalent is running in has changed size.
 /// This event is automatically emitted once at the start of running a scene.
-#[derive(Debug)]
+/// If the render pipeline letterboxes the window (see `RenderSettings::target_aspect`), the reported size is the
+/// size of the letterboxed region.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WindowResizeEvent {
-    /// The new size of the window.
+    /// The new size of the window, in physical pixels, like the positions in `CursorMovedEvent`.
     pub new_size: cgmath::Vector2<u32>
 }
 impl Event for WindowResizeEvent {}
 
+
+/// A gamepad has been connected or disconnected.
+/// Gamepads that are already connected when covalent starts also emit this event.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadConnectionEvent {
+    /// The gamepad that was connected or disconnected.
+    pub gamepad_id: GamepadId,
+    /// True if the gamepad was connected, false if it was disconnected.
+    pub connected: bool
+}
+impl Event for GamepadConnectionEvent {}
+
+/// A button on a gamepad has been pressed or released, or one of its axes has moved.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadEvent {
+    /// A button was pressed or released.
+    Button {
+        /// The gamepad that the button belongs to.
+        gamepad_id: GamepadId,
+        /// The button that was pressed or released.
+        button: GamepadButton,
+        /// Describes the input state of the button, i.e. pressed or released.
+        state: ElementState,
+    },
+    /// An axis, such as one direction of a thumb stick, moved.
+    Axis {
+        /// The gamepad that the axis belongs to.
+        gamepad_id: GamepadId,
+        /// The axis that moved.
+        axis: GamepadAxis,
+        /// The new position of the axis, from -1 to 1. For thumb sticks, positive values are right and up.
+        value: f32,
+    },
+}
+impl Event for GamepadEvent {}
+
+impl GamepadEvent {
+    /// Retrieves the gamepad that produced this event.
+    pub fn gamepad_id(&self) -> GamepadId {
+        match self {
+            GamepadEvent::Button { gamepad_id, .. } | GamepadEvent::Axis { gamepad_id, .. } => *gamepad_id,
+        }
+    }
+}
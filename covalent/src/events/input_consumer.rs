@@ -0,0 +1,89 @@
+use crate::events::{CursorMovedEvent, GamepadEvent, KeyboardEvent, MouseButtonEvent, MouseDeltaEvent, MouseScrollEvent};
+
+/// Any event produced by an input device, such as the keyboard, mouse or a gamepad.
+/// This is the type that input consumers inspect; see `InputConsumers`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputEvent {
+    /// A key was pressed or released.
+    Keyboard(KeyboardEvent),
+    /// The mouse was moved.
+    MouseDelta(MouseDeltaEvent),
+    /// A mouse button was pressed or released.
+    MouseButton(MouseButtonEvent),
+    /// The mouse wheel was scrolled.
+    MouseScroll(MouseScrollEvent),
+    /// The cursor moved within the window.
+    CursorMoved(CursorMovedEvent),
+    /// A gamepad button was pressed or released, or a gamepad axis moved.
+    Gamepad(GamepadEvent),
+}
+
+/// A broad category of input, used to swallow whole classes of input at once.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum InputCategory {
+    /// Events from the keyboard.
+    Keyboard,
+    /// Events from the mouse, including movement and scrolling.
+    Mouse,
+    /// Events from any gamepad. Connecting and disconnecting gamepads is not input, so is never swallowed.
+    Gamepad,
+}
+
+impl InputEvent {
+    /// Retrieves the category of input device that produced this event.
+    pub fn category(&self) -> InputCategory {
+        match self {
+            InputEvent::Keyboard(_) => InputCategory::Keyboard,
+            InputEvent::MouseDelta(_) => InputCategory::Mouse,
+            InputEvent::MouseButton(_) => InputCategory::Mouse,
+            InputEvent::MouseScroll(_) => InputCategory::Mouse,
+            InputEvent::CursorMoved(_) => InputCategory::Mouse,
+            InputEvent::Gamepad(_) => InputCategory::Gamepad,
+        }
+    }
+}
+
+type ConsumerID = i64;
+/// A function that returns true to swallow the given event; see `InputConsumers::insert`.
+type Consumer = Box<dyn Fn(&InputEvent) -> bool + Send + Sync>;
+
+/// Input consumers see every input event before it is dispatched to the scene's event handlers, and may swallow
+/// the event so that no listener receives it. For example, while a modal menu is open, it could register a consumer
+/// that swallows all keyboard and mouse input so that the game underneath does not react to it.
+///
+/// Consumers are run in the order they were inserted. As soon as one consumer swallows an event, no more consumers
+/// are run, and the event is not dispatched to the scene.
+///
+/// This is coarser than stopping the propagation of individual events: it gates whole subsystems at once.
+#[derive(Default)]
+pub struct InputConsumers {
+    next_id: ConsumerID,
+    consumers: Vec<(ConsumerID, Consumer)>,
+}
+
+impl InputConsumers {
+    /// Registers an input consumer, returning an ID that can later be used to remove it.
+    /// The consumer should return true to swallow the given event.
+    pub fn insert(&mut self, consumer: impl Fn(&InputEvent) -> bool + Send + Sync + 'static) -> ConsumerID {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.consumers.push((id, Box::new(consumer)));
+        id
+    }
+
+    /// Registers an input consumer that swallows every event of the given category.
+    pub fn swallow_category(&mut self, category: InputCategory) -> ConsumerID {
+        self.insert(move |e| e.category() == category)
+    }
+
+    /// Removes the input consumer with the given ID, if it exists.
+    pub fn remove(&mut self, id: ConsumerID) {
+        self.consumers.retain(|(consumer_id, _)| *consumer_id != id);
+    }
+
+    /// Runs each consumer on the given event in turn. Returns true if the event was swallowed.
+    pub(crate) fn consume(&self, e: &InputEvent) -> bool {
+        self.consumers.iter().any(|(_, consumer)| consumer(e))
+    }
+}
@@ -0,0 +1,99 @@
+use crate::events::InputEvent;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// A single input event recorded by the `InputEventLog`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputEventLogEntry {
+    /// The time at which the event was received, in seconds since the log was created.
+    pub time: f64,
+    /// The event that was received.
+    pub event: InputEvent,
+}
+
+/// Records the most recent input events received by a scene, for diagnostics such as attaching "what I pressed"
+/// to a bug report. Only the last `capacity` events are stored; recording more events discards the oldest ones.
+///
+/// The log is disabled by default (its capacity is zero). Events are recorded before input consumers run, so
+/// swallowed events still appear in the log. With the `serde` feature enabled, entries can be serialized.
+pub struct InputEventLog {
+    entries: VecDeque<InputEventLogEntry>,
+    capacity: usize,
+    start: Instant,
+}
+
+impl InputEventLog {
+    /// Creates an input event log that stores at most `capacity` events.
+    pub fn new(capacity: usize) -> InputEventLog {
+        InputEventLog {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            start: Instant::now(),
+        }
+    }
+
+    /// Records an input event, if the log is enabled.
+    pub(crate) fn record(&mut self, event: &InputEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(InputEventLogEntry {
+            time: self.start.elapsed().as_secs_f64(),
+            event: event.clone(),
+        });
+    }
+
+    /// Removes all events from the log.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Changes the maximum amount of events stored in the log, discarding the oldest events if required.
+    /// A capacity of zero disables the log.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+        self.capacity = capacity;
+    }
+
+    /// Retrieves the maximum amount of events stored in the log.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Iterates over the recorded events, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item=&InputEventLogEntry> {
+        self.entries.iter()
+    }
+}
+
+impl Default for InputEventLog {
+    /// Creates a disabled input event log.
+    fn default() -> Self {
+        InputEventLog::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MouseDeltaEvent;
+
+    #[test]
+    fn keeps_only_recent_events() {
+        let mut log = InputEventLog::new(2);
+        for i in 0..5 {
+            log.record(&InputEvent::MouseDelta(MouseDeltaEvent { delta: cgmath::vec2(i as f64, 0.0) }));
+        }
+        let xs = log.entries().map(|entry| match &entry.event {
+            InputEvent::MouseDelta(e) => e.delta.x,
+            _ => panic!(),
+        }).collect::<Vec<_>>();
+        assert_eq!(xs, vec![3.0, 4.0]);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::events::{GamepadEvent, InputEvent};
+use crate::input::{ElementState, GamepadAxis, GamepadButton, GamepadId, MouseButton, ScanCode, VirtualKeyCode};
+use cgmath::Vector2;
+use std::collections::{HashMap, HashSet};
+
+/// The current state of the keyboard, mouse and gamepads, as of the most recent input event.
+/// Retrieve it with `Scene::input`, e.g. from a tick listener, to check whether a key is held down without writing
+/// a listener that keeps track of key presses and releases.
+///
+/// The state is updated before input consumers run, so it reflects what the user is actually doing, even if some
+/// events were swallowed.
+#[derive(Debug, Clone)]
+pub struct InputState {
+    pressed_keys: HashSet<VirtualKeyCode>,
+    pressed_scan_codes: HashSet<ScanCode>,
+    pressed_mouse_buttons: HashSet<MouseButton>,
+    mouse_position: Vector2<f64>,
+    /// The size of the whole window in physical pixels, ignoring any letterboxing.
+    window_size: Vector2<u32>,
+    pressed_gamepad_buttons: HashSet<(GamepadId, GamepadButton)>,
+    /// The position of every gamepad axis that is not at rest.
+    gamepad_axes: HashMap<(GamepadId, GamepadAxis), f32>,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            pressed_keys: HashSet::new(),
+            pressed_scan_codes: HashSet::new(),
+            pressed_mouse_buttons: HashSet::new(),
+            mouse_position: cgmath::vec2(0.0, 0.0),
+            window_size: cgmath::vec2(0, 0),
+            pressed_gamepad_buttons: HashSet::new(),
+            gamepad_axes: HashMap::new(),
+        }
+    }
+}
+
+impl InputState {
+    /// Returns true if the key with the given semantic meaning is held down.
+    pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    /// Returns true if the physical key with the given scan code is held down.
+    pub fn is_scan_code_pressed(&self, scan_code: ScanCode) -> bool {
+        self.pressed_scan_codes.contains(&scan_code)
+    }
+
+    /// Returns true if the given mouse button is held down.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_mouse_buttons.contains(&button)
+    }
+
+    /// Retrieves the last known position of the cursor in physical pixels, relative to the top left corner of the
+    /// window. See `CursorMovedEvent`.
+    pub fn mouse_position(&self) -> Vector2<f64> {
+        self.mouse_position
+    }
+
+    /// Retrieves the last known position of the cursor as a fraction of the size of the window, from (0, 0) at the top
+    /// left corner to (1, 1) at the bottom right, regardless of the window's size or scale factor. This is useful for
+    /// hit-testing UI that is laid out relative to the window. While the cursor is grabbed, this may go outside
+    /// the range 0 to 1. Before the size of the window is known, this is zero.
+    pub fn normalised_mouse_position(&self) -> Vector2<f64> {
+        if self.window_size.x == 0 || self.window_size.y == 0 {
+            return cgmath::vec2(0.0, 0.0);
+        }
+        cgmath::vec2(self.mouse_position.x / self.window_size.x as f64, self.mouse_position.y / self.window_size.y as f64)
+    }
+
+    /// Retrieves the size of the window in physical pixels, as reported by the last `WindowResizeEvent`, but ignoring
+    /// any letterboxing.
+    pub fn window_size(&self) -> Vector2<u32> {
+        self.window_size
+    }
+
+    /// Returns true if the given button of the given gamepad is held down.
+    pub fn is_gamepad_button_pressed(&self, gamepad_id: GamepadId, button: GamepadButton) -> bool {
+        self.pressed_gamepad_buttons.contains(&(gamepad_id, button))
+    }
+
+    /// Retrieves the last known position of the given axis of the given gamepad, from -1 to 1.
+    /// Axes that haven't moved since the gamepad was connected are at zero.
+    pub fn gamepad_axis(&self, gamepad_id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepad_axes.get(&(gamepad_id, axis)).copied().unwrap_or(0.0)
+    }
+
+    /// Creates the events that return every held button and moved axis of the given gamepad to rest, so that
+    /// listeners aren't left thinking they are still held after the gamepad is disconnected.
+    pub(crate) fn gamepad_release_events(&self, gamepad_id: GamepadId) -> Vec<GamepadEvent> {
+        let buttons = self.pressed_gamepad_buttons.iter()
+            .filter(|(id, _)| *id == gamepad_id)
+            .map(|(_, button)| GamepadEvent::Button { gamepad_id, button: *button, state: ElementState::Released });
+        let axes = self.gamepad_axes.keys()
+            .filter(|(id, _)| *id == gamepad_id)
+            .map(|(_, axis)| GamepadEvent::Axis { gamepad_id, axis: *axis, value: 0.0 });
+        buttons.chain(axes).collect()
+    }
+
+    /// Records the new size of the window, in physical pixels, before any letterboxing is applied.
+    pub(crate) fn resize_window(&mut self, size: Vector2<u32>) {
+        self.window_size = size;
+    }
+
+    /// Updates the state according to the given event. For keyboard events, this also works out whether the event
+    /// is a repeat, and sets `is_repeat` accordingly.
+    pub(crate) fn record(&mut self, e: &mut InputEvent) {
+        match e {
+            InputEvent::Keyboard(e) => {
+                e.is_repeat = match e.state {
+                    ElementState::Pressed => !self.pressed_scan_codes.insert(e.scan_code),
+                    ElementState::Released => {
+                        self.pressed_scan_codes.remove(&e.scan_code);
+                        false
+                    }
+                };
+                if let Some(key) = e.virtual_keycode {
+                    match e.state {
+                        ElementState::Pressed => self.pressed_keys.insert(key),
+                        ElementState::Released => self.pressed_keys.remove(&key),
+                    };
+                }
+            }
+            InputEvent::MouseButton(e) => {
+                match e.state {
+                    ElementState::Pressed => self.pressed_mouse_buttons.insert(e.button),
+                    ElementState::Released => self.pressed_mouse_buttons.remove(&e.button),
+                };
+            }
+            InputEvent::CursorMoved(e) => self.mouse_position = e.pos,
+            InputEvent::Gamepad(GamepadEvent::Button { gamepad_id, button, state }) => {
+                match state {
+                    ElementState::Pressed => self.pressed_gamepad_buttons.insert((*gamepad_id, *button)),
+                    ElementState::Released => self.pressed_gamepad_buttons.remove(&(*gamepad_id, *button)),
+                };
+            }
+            InputEvent::Gamepad(GamepadEvent::Axis { gamepad_id, axis, value }) => {
+                if *value == 0.0 {
+                    self.gamepad_axes.remove(&(*gamepad_id, *axis));
+                } else {
+                    self.gamepad_axes.insert((*gamepad_id, *axis), *value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{CursorMovedEvent, MouseButtonEvent};
+
+    #[test]
+    fn mouse_buttons_and_position() {
+        let mut state = InputState::default();
+        state.record(&mut InputEvent::MouseButton(MouseButtonEvent { button: MouseButton::Left, state: ElementState::Pressed }));
+        state.record(&mut InputEvent::CursorMoved(CursorMovedEvent { pos: cgmath::vec2(12.0, 34.0) }));
+        assert!(state.is_mouse_button_pressed(MouseButton::Left));
+        assert!(!state.is_mouse_button_pressed(MouseButton::Right));
+        assert_eq!(cgmath::vec2(12.0, 34.0), state.mouse_position());
+
+        state.record(&mut InputEvent::MouseButton(MouseButtonEvent { button: MouseButton::Left, state: ElementState::Released }));
+        assert!(!state.is_mouse_button_pressed(MouseButton::Left));
+    }
+
+    #[test]
+    fn normalised_mouse_position() {
+        let mut state = InputState::default();
+        state.record(&mut InputEvent::CursorMoved(CursorMovedEvent { pos: cgmath::vec2(200.0, 150.0) }));
+        assert_eq!(cgmath::vec2(0.0, 0.0), state.normalised_mouse_position());
+
+        state.resize_window(cgmath::vec2(800, 600));
+        assert_eq!(cgmath::vec2(200.0, 150.0), state.mouse_position());
+        assert_eq!(cgmath::vec2(0.25, 0.25), state.normalised_mouse_position());
+    }
+}
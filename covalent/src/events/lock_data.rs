@@ -2,8 +2,26 @@
 /// This allows for more intuitive concurrency, by abstracting away the lock-unlock logic
 /// and potential lock mishaps.
 ///
+/// The function passed to `listen` may either return nothing, in which case it keeps listening, or a
+/// `ListenOutcome`. Return `ListenOutcome::Remove` to stop listening, e.g. after handling an event exactly once.
+///
+/// Use `listen_with_priority` instead of `listen` to choose when the listener runs in ordered event handlers.
+///
+/// # Accessing the node
+/// Components can read or move their node by requiring it like any other field, e.g. `node: read Node` or
+/// `node: write Node`, and filling the field with `Arc::downgrade(&node)` when the component is created. The
+/// listener then receives `&Node` or `&mut Node`, so the node's transform doesn't need to be copied into the
+/// component. See `TransformComponent` for an example. If the node is deleted, the listener is removed.
+///
+/// Only require `write Node` when the node must change: while one listener write-locks a node, the listeners of
+/// other components on that node that require it can't run, and must be retried.
+///
 /// # Examples
 /// ```
+/// use covalent::lock_data;
+/// use covalent::events::{Event, EventHandler};
+/// use std::sync::{Arc, RwLock};
+///
 /// struct HelloWorldObject {
 ///     message: &'static str
 /// }
@@ -12,8 +30,6 @@
 ///     message: Option<&'static str>
 /// }
 ///
-/// use covalent::lock_data;
-/// use covalent::events::{Event, EventHandler};
 /// lock_data! {
 ///     HelloWorldData
 ///
@@ -21,26 +37,24 @@
 ///     output: write Output
 /// }
 ///
-/// fn create_locks() {
-///     use std::sync::*;
-///     use covalent::scene::{Event, EventHandler};
+/// struct HelloWorldEvent {}
+/// impl Event for HelloWorldEvent {}
 ///
-///     let hello_world = Arc::new(RwLock::new(HelloWorldObject { message: "Hello, world!" }));
-///     let output = Arc::new(RwLock::new(Output { message: None }));
+/// let hello_world = Arc::new(RwLock::new(HelloWorldObject { message: "Hello, world!" }));
+/// let output = Arc::new(RwLock::new(Output { message: None }));
 ///
-///     let data = Arc::new(RwLock::new(HelloWorldData {
-///         hello_world: Arc::downgrade(&hello_world),
-///         output: Arc::downgrade(&output)
-///     }));
+/// let data = Arc::new(RwLock::new(HelloWorldData {
+///     hello_world: Arc::downgrade(&hello_world),
+///     output: Arc::downgrade(&output)
+/// }));
 ///
-///     struct HelloWorldEvent {}
-///     impl Event for HelloWorldEvent {}
-///     let event_handler = Arc::new(RwLock::new(EventHandler::<HelloWorldEvent>::new()));
+/// let event_handler = Arc::new(RwLock::new(EventHandler::<HelloWorldEvent>::default()));
+/// HelloWorldData::listen(&data, &event_handler, |_event, hello_world, output| {
+///     output.message = Some(hello_world.message);
+/// });
 ///
-///     HelloWorldData::listen(&data, &event_handler, |event, hello_world, output| {
-///         output.message = Some(hello_world.message);
-///     });
-/// }
+/// event_handler.write().unwrap().handle(&HelloWorldEvent {});
+/// assert_eq!(Some("Hello, world!"), output.read().unwrap().message);
 /// ```
 #[macro_export]
 macro_rules! lock_data {
@@ -54,17 +68,34 @@ macro_rules! lock_data {
 
         // Implement the listen function.
         impl $struct_name {
-            fn listen<'a, E, F>(data: &std::sync::Arc<std::sync::RwLock<Self>>, handler: &std::sync::Arc<std::sync::RwLock<$crate::events::EventHandler<E>>>, func: F)
+            #[allow(dead_code)]
+            fn listen<E, F, R>(data: &std::sync::Arc<std::sync::RwLock<Self>>, handler: &std::sync::Arc<std::sync::RwLock<$crate::events::EventHandler<E>>>, func: F)
+                where E: $crate::events::Event,
+                      F: Fn(&E
+                          $(
+                          , $crate::lock_data!(@ generate parameter $mutability $data_type)
+                          )*
+                      ) -> R,
+                      F: Send + Sync + 'static,
+                      R: Into<$crate::events::ListenOutcome> {
+                Self::listen_with_priority(data, handler, 0, func);
+            }
+
+            // Listeners with a higher priority are executed first by ordered event handlers.
+            #[allow(dead_code)]
+            fn listen_with_priority<E, F, R>(data: &std::sync::Arc<std::sync::RwLock<Self>>, handler: &std::sync::Arc<std::sync::RwLock<$crate::events::EventHandler<E>>>, priority: i32, func: F)
                 where E: $crate::events::Event,
                       F: Fn(&E
                           $(
                           , $crate::lock_data!(@ generate parameter $mutability $data_type)
                           )*
-                      ),
-                      F: Send + Sync + 'static {
+                      ) -> R,
+                      F: Send + Sync + 'static,
+                      R: Into<$crate::events::ListenOutcome> {
                 let copy = std::sync::Arc::clone(data);
                 let l = $crate::events::Listener {
                     id: handler.write().unwrap().new_id(),
+                    priority,
                     func: Box::new(move |event| {
                         let self_var = copy.read().unwrap();
                         $crate::lock_data!{ @ generate locks self_var, func, event, $($name, $mutability, $data_type),+ }
@@ -88,10 +119,9 @@ macro_rules! lock_data {
         if let Some(arc) = std::sync::Weak::upgrade(&$s.$name0) {
             match $crate::lock_data!( @ generate try mutability $mutability0 arc ) {
                 Ok($crate::lock_data!( @ generate mutability $mutability0 guard )) => {
-                    $f($e, $($crate::lock_data!( @ generate mutability $mutability1 &$guard1)),*, $crate::lock_data!( @ generate mutability $mutability0 &guard));
-                    Ok(())
+                    Ok($f($e, $($crate::lock_data!( @ generate mutability $mutability1 &$guard1)),*, $crate::lock_data!( @ generate mutability $mutability0 &guard)).into())
                 },
-                _ => Err($crate::events::ListenError::LockUnavailable)
+                _ => Err($crate::events::ListenError::LockUnavailable { field: stringify!($name0) })
             }
         } else {
             Err($crate::events::ListenError::RequirementDeleted)
@@ -106,10 +136,9 @@ macro_rules! lock_data {
         if let Some(arc) = std::sync::Weak::upgrade(&$s.$name0) {
             match $crate::lock_data!( @ generate try mutability $mutability0 arc ) {
                 Ok($crate::lock_data!( @ generate mutability $mutability0 guard )) => {
-                    $f($e, $crate::lock_data!( @ generate mutability $mutability0 &guard));
-                    Ok(())
+                    Ok($f($e, $crate::lock_data!( @ generate mutability $mutability0 &guard)).into())
                 },
-                _ => Err($crate::events::ListenError::LockUnavailable)
+                _ => Err($crate::events::ListenError::LockUnavailable { field: stringify!($name0) })
             }
         } else {
             Err($crate::events::ListenError::RequirementDeleted)
@@ -130,7 +159,7 @@ macro_rules! lock_data {
                 Ok($crate::lock_data!( @ generate mutability $mutability0 guard)) => {
                     $crate::lock_data!{ @ generate locks $s, $f, $e, $($tail)* | $($mutability1),*, $mutability0 | $($guard1),*, guard }
                 },
-                _ => Err($crate::events::ListenError::LockUnavailable)
+                _ => Err($crate::events::ListenError::LockUnavailable { field: stringify!($name0) })
             }
         } else {
             Err($crate::events::ListenError::RequirementDeleted)
@@ -148,7 +177,7 @@ macro_rules! lock_data {
                 Ok($crate::lock_data!( @ generate mutability $mutability0 guard)) => {
                     $crate::lock_data!{ @ generate locks $s, $f, $e, $($tail)* | $mutability0 | guard }
                 },
-                _ => Err($crate::events::ListenError::LockUnavailable)
+                _ => Err($crate::events::ListenError::LockUnavailable { field: stringify!($name0) })
             }
         } else {
             Err($crate::events::ListenError::RequirementDeleted)
@@ -1,7 +1,9 @@
 //! This module contains commonly-used events in covalent.
-//! You can create custom events by implementing the `covalent::scene::Event` trait, and then
-//! creating an event handler for it with `covalent::scene::EventHandler::<YourEventType>::new()`.
+//! You can create custom events by implementing the `covalent::events::Event` trait, and then
+//! registering an event handler for it with `EventHandlers::register`. Fire the event with `EventHandlers::emit`.
 
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 mod common;
@@ -13,15 +15,129 @@ pub use event::*;
 mod input;
 pub use input::*;
 
+mod input_consumer;
+pub use input_consumer::*;
+
+mod input_event_log;
+pub use input_event_log::*;
+
+mod input_state;
+pub use input_state::*;
+
 mod lock_data;
 
 use crate::input::*;
 
 /// A manager for event handlers in a scene. This contains all the common event handlers.
-#[derive(Default)]
+///
+/// The `key` and `mouse_button` handlers are ordered, so listeners with a higher priority, such as a UI layer, can
+/// consume key presses and clicks before gameplay listeners see them. All other built-in event handlers execute their
+/// listeners in parallel, in no particular order; call `set_ordered` on a handler to run its listeners in order of
+/// priority instead.
 pub struct EventHandlers {
     pub tick: Arc<RwLock<EventHandler<TickEvent>>>,
+    /// Fired at a constant rate, zero or more times per frame, as decided by the scene's `Clock`.
+    pub fixed_tick: Arc<RwLock<EventHandler<FixedTickEvent>>>,
+    /// Fired every frame to a rotating subset of its listeners, as decided by `tick_stagger`.
+    pub staggered_tick: Arc<RwLock<EventHandler<StaggeredTickEvent>>>,
+    /// Decides which listeners of `staggered_tick` run each frame.
+    pub tick_stagger: Arc<RwLock<TickStagger>>,
+    /// Fired at the start of each frame for every node whose transformation changed since the last frame.
+    pub node_transform_changed: Arc<RwLock<EventHandler<NodeTransformChangedEvent>>>,
     pub key: Arc<RwLock<EventHandler<KeyboardEvent>>>,
     pub mouse_delta: Arc<RwLock<EventHandler<MouseDeltaEvent>>>,
-    pub window_resize: Arc<RwLock<EventHandler<WindowResizeEvent>>>
+    /// Fired when a mouse button is pressed or released.
+    pub mouse_button: Arc<RwLock<EventHandler<MouseButtonEvent>>>,
+    /// Fired when the mouse wheel, or a touchpad, is scrolled.
+    pub mouse_scroll: Arc<RwLock<EventHandler<MouseScrollEvent>>>,
+    /// Fired when the cursor moves within the window.
+    pub cursor_moved: Arc<RwLock<EventHandler<CursorMovedEvent>>>,
+    /// Fired when the cursor enters the window.
+    pub cursor_entered: Arc<RwLock<EventHandler<CursorEnteredEvent>>>,
+    /// Fired when the cursor leaves the window.
+    pub cursor_left: Arc<RwLock<EventHandler<CursorLeftEvent>>>,
+    /// Fired when a button on a gamepad is pressed or released, or one of its axes moves.
+    pub gamepad: Arc<RwLock<EventHandler<GamepadEvent>>>,
+    pub window_resize: Arc<RwLock<EventHandler<WindowResizeEvent>>>,
+    /// Fired when a gamepad is connected or disconnected.
+    pub gamepad_connection: Arc<RwLock<EventHandler<GamepadConnectionEvent>>>,
+    /// Fired once, just before the application exits.
+    pub shutdown: Arc<RwLock<EventHandler<ShutdownEvent>>>,
+    /// Input consumers run before any input event is dispatched to the handlers above.
+    pub input_consumers: Arc<RwLock<InputConsumers>>,
+    /// Event handlers for user-defined events, keyed by the type of event. Each value is an
+    /// `Arc<RwLock<EventHandler<E>>>` for the event type `E` given by its key.
+    custom: RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>
+}
+
+/// Creates an event handler that executes its listeners in order of priority.
+fn ordered<E: Event>() -> Arc<RwLock<EventHandler<E>>> {
+    let mut handler = EventHandler::default();
+    handler.set_ordered(true);
+    Arc::new(RwLock::new(handler))
+}
+
+impl Default for EventHandlers {
+    fn default() -> Self {
+        Self {
+            tick: Default::default(),
+            fixed_tick: Default::default(),
+            staggered_tick: Default::default(),
+            tick_stagger: Default::default(),
+            node_transform_changed: Default::default(),
+            key: ordered(),
+            mouse_delta: Default::default(),
+            mouse_button: ordered(),
+            mouse_scroll: Default::default(),
+            cursor_moved: Default::default(),
+            cursor_entered: Default::default(),
+            cursor_left: Default::default(),
+            gamepad: Default::default(),
+            window_resize: Default::default(),
+            gamepad_connection: Default::default(),
+            shutdown: Default::default(),
+            input_consumers: Default::default(),
+            custom: Default::default()
+        }
+    }
+}
+
+impl EventHandlers {
+    /// Retrieves the event handler for a user-defined event type, creating it if this is the first time the event
+    /// type has been registered. Listen to the handler with a `lock_data` struct, exactly like the built-in handlers.
+    ///
+    /// # Examples
+    /// ```
+    /// use covalent::events::Event;
+    /// struct PlayerDiedEvent { lives_left: u32 }
+    /// impl Event for PlayerDiedEvent {}
+    ///
+    /// let scene = covalent::scene::Scene::new();
+    /// let handler = scene.read().unwrap().events.register::<PlayerDiedEvent>();
+    /// // ... listen to `handler` here ...
+    /// scene.read().unwrap().events.emit(PlayerDiedEvent { lives_left: 2 });
+    /// ```
+    pub fn register<E: Event + 'static>(&self) -> Arc<RwLock<EventHandler<E>>> {
+        if let Some(handler) = self.get::<E>() {
+            return handler;
+        }
+        let mut custom = self.custom.write().unwrap();
+        let handler = custom.entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Arc::new(RwLock::new(EventHandler::<E>::default()))));
+        Arc::clone(handler.downcast_ref::<Arc<RwLock<EventHandler<E>>>>().unwrap())
+    }
+
+    /// Retrieves the event handler for a user-defined event type, if it has been registered.
+    pub fn get<E: Event + 'static>(&self) -> Option<Arc<RwLock<EventHandler<E>>>> {
+        self.custom.read().unwrap().get(&TypeId::of::<E>())
+            .map(|handler| Arc::clone(handler.downcast_ref::<Arc<RwLock<EventHandler<E>>>>().unwrap()))
+    }
+
+    /// Fires a user-defined event, executing every listener of its event handler.
+    /// If the event type has not been registered, nobody can be listening to it, so this does nothing.
+    pub fn emit<E: Event + 'static>(&self, e: E) {
+        if let Some(handler) = self.get::<E>() {
+            handler.write().unwrap().handle(&e);
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::graphics::{RenderVertex, Renderable};
+use crate::graphics::{FontId, FramebufferId, PixelFormat, RenderChannel, RenderVertex, Renderable, Sampler, ShaderId, ShaderSource, TextureId};
 
 /// Covalent supports the use of "graphics backends", distinct rendering engines for use with covalent.
 /// They all support the same rendering API, so similar code can run on multiple platforms
@@ -27,5 +27,83 @@ pub trait Backend {
     /// The `verts` parameter is a list of vertices that the mesh uses.
     /// The `inds` parameter is a list of indices into the first parameter; each group of three entries in `inds` represents
     /// a single triangle represented by the given indexed vertices.
+    /// Vertices with a normal of zero are given flat normals, as if by `fill_flat_normals`.
     fn create_mesh(&self, verts: Vec<RenderVertex>, inds: Vec<u32>) -> Renderable;
+
+    /// Frees the GPU memory used by a mesh created with `create_mesh`. The renderable may be either a `Mesh` or a
+    /// `TexturedMesh`; the texture itself is not deleted. Any other renderable is ignored.
+    ///
+    /// Renderables that refer to a deleted mesh, including copies of `r`, are still safe to use: they simply render
+    /// nothing.
+    fn delete_mesh(&self, r: &Renderable);
+
+    /// Uploads an image to the GPU so that it can be used to texture meshes; see `Renderable::with_texture`.
+    ///
+    /// The `pixels` parameter contains four bytes for each pixel, in the sRGB colour space, ordered as given by
+    /// `format`. Rows are ordered from top to bottom, as in most image formats.
+    ///
+    /// # Panics
+    /// If `pixels` does not contain exactly `width * height * 4` bytes.
+    fn load_texture(&self, pixels: Vec<u8>, width: u32, height: u32, format: PixelFormat) -> TextureId;
+
+    /// Changes how the given texture is filtered and wrapped when it is drawn. Textures use `Sampler::default()` until
+    /// this is called. Does nothing (except log a warning) if the texture doesn't exist.
+    fn set_texture_sampler(&self, texture: TextureId, sampler: Sampler);
+
+    /// Creates an off-screen framebuffer of the given size with the given render channels, which pipeline phases can
+    /// target with `RenderTarget::Framebuffer`. Render phases test against the depth buffer, so framebuffers that
+    /// they target should include `RenderChannel::Depth`.
+    ///
+    /// # Panics
+    /// If the backend doesn't support one of the given render channels.
+    fn create_framebuffer(&self, width: u32, height: u32, channels: &[RenderChannel]) -> FramebufferId;
+
+    /// Creates a texture that shows the colour channel of the given framebuffer, so that whatever pipeline phases or
+    /// `Scene::render_to_framebuffer` draw into the framebuffer can be drawn like any texture loaded with
+    /// `load_texture`. The texture shares the framebuffer's memory rather than copying it, so the same ID shows what
+    /// was most recently drawn into the framebuffer for as long as the game runs. Calling this again for the same
+    /// framebuffer gives another ID for the same texture, which can have a different sampler. The texture must not be
+    /// drawn into its own framebuffer.
+    ///
+    /// # Panics
+    /// If the framebuffer doesn't exist.
+    fn framebuffer_texture(&self, framebuffer: FramebufferId) -> TextureId;
+
+    /// Compiles a shader program from the given vertex and fragment shader source code, for use by pipeline phases
+    /// such as `PostProcess`. The shading language depends on the backend; for example, the GL backend uses GLSL.
+    ///
+    /// # Panics
+    /// If the shaders fail to compile or link. The error is logged first.
+    fn load_shader(&self, vertex_src: &str, fragment_src: &str) -> ShaderId {
+        self.load_shader_source(ShaderSource::inline(vertex_src, fragment_src))
+    }
+
+    /// Compiles a shader program from the given source, as `load_shader` does. Programs whose source is read from
+    /// files are recompiled when the game calls `Context::reload_shaders`; if the new version fails to compile, the
+    /// error is logged and the previous version is kept.
+    ///
+    /// Compiled programs are cached by their source, so loading the same source again returns the same ID without
+    /// compiling anything; see `precompile_shaders`.
+    ///
+    /// # Panics
+    /// If the source files can't be read, or the shaders fail to compile or link. The error is logged first.
+    fn load_shader_source(&self, source: ShaderSource) -> ShaderId;
+
+    /// Compiles all of the given shader programs up front, returning their IDs in the same order.
+    ///
+    /// Compiling a shader can take long enough to cause a visible stutter, so call this while the game is
+    /// initialising, before `covalent::execute`, with every shader the game will use. Later calls to `load_shader`
+    /// or `load_shader_source` with the same source then return the cached program immediately.
+    ///
+    /// # Panics
+    /// If any of the shaders can't be loaded; see `load_shader_source`.
+    fn precompile_shaders(&self, sources: &[ShaderSource]) -> Vec<ShaderId> {
+        sources.iter().map(|source| self.load_shader_source(source.clone())).collect()
+    }
+
+    /// Loads a TrueType font from the contents of a `.ttf` file, so that `Text` phases can draw text with it.
+    ///
+    /// # Panics
+    /// If the bytes are not a valid font.
+    fn load_font(&self, ttf_bytes: Vec<u8>) -> FontId;
 }
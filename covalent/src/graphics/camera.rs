@@ -1,5 +1,5 @@
 use std::sync::{Arc, RwLock};
-use cgmath::{Vector3, Point3, Matrix4, Transform, InnerSpace, SquareMatrix, Vector2};
+use cgmath::{Vector3, Point3, Matrix, Matrix4, Transform, InnerSpace, SquareMatrix, Vector2, Vector4};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// A camera is the lens through which your scene can be viewed. This tells covalent how to map the
@@ -20,6 +20,45 @@ pub trait Camera {
         matrices.combined = c;
         matrices.inverse = c.invert().unwrap_or(Matrix4::identity())
     }
+
+    /// Computes the ray in world space that passes through the given point on the screen, in normalised device
+    /// coordinates (from `(-1, -1)` at the bottom left to `(1, 1)` at the top right; see `window_to_ndc`).
+    /// Returns the point where the ray meets the near plane of the camera, and the (normalised) direction of the ray.
+    /// This is useful for working out what the user clicked on.
+    fn screen_to_ray(&self, ndc: Vector2<f32>) -> (Point3<f32>, Vector3<f32>) {
+        unproject_ray(self.get_combined_matrix().invert().unwrap_or(Matrix4::identity()), ndc)
+    }
+}
+
+/// Converts a position in a window, in pixels from the top left corner, into normalised device coordinates, which
+/// range from `(-1, -1)` at the bottom left of the window to `(1, 1)` at the top right.
+pub fn window_to_ndc(pos: Vector2<f64>, window_size: Vector2<u32>) -> Vector2<f32> {
+    cgmath::vec2(
+        (pos.x / window_size.x.max(1) as f64 * 2.0 - 1.0) as f32,
+        (1.0 - pos.y / window_size.y.max(1) as f64 * 2.0) as f32,
+    )
+}
+
+/// Computes the directions in world space that point right and up on the screen for a camera with the given combined
+/// view and projection matrix, e.g. to build billboards that face the camera. Both directions have unit length.
+pub fn camera_axes(combined: Matrix4<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    // The projection matrices of our cameras only scale the x and y axes of view space, so the first two rows of the
+    // combined matrix are the camera's right and up directions, scaled.
+    (combined.row(0).truncate().normalize(), combined.row(1).truncate().normalize())
+}
+
+/// Computes the ray through the given point in normalised device coordinates by transforming the corresponding
+/// points on the near and far planes back into world space with the given inverse of a combined matrix.
+fn unproject_ray(inverse: Matrix4<f32>, ndc: Vector2<f32>) -> (Point3<f32>, Vector3<f32>) {
+    let near = unproject(inverse, ndc, -1.0);
+    let far = unproject(inverse, ndc, 1.0);
+    (near, (far - near).normalize())
+}
+
+/// Transforms the point with the given normalised device coordinates and depth back into world space.
+fn unproject(inverse: Matrix4<f32>, ndc: Vector2<f32>, depth: f32) -> Point3<f32> {
+    let p = inverse * Vector4::new(ndc.x, ndc.y, depth, 1.0);
+    Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
 }
 
 /// A representation of the camera's matrices that can be sent to the graphics backend to be
@@ -29,6 +68,21 @@ pub struct CameraMatrices {
     pub inverse: Matrix4<f32>,
 }
 
+impl CameraMatrices {
+    /// Computes the ray in world space that passes through the given point on the screen, in normalised device
+    /// coordinates, using the `inverse` matrix. See `Camera::screen_to_ray`.
+    pub fn screen_to_ray(&self, ndc: Vector2<f32>) -> (Point3<f32>, Vector3<f32>) {
+        unproject_ray(self.inverse, ndc)
+    }
+
+    /// Finds the point in world space on the ray given by `screen_to_ray` that has the given depth in normalised
+    /// device coordinates, from -1 on the near plane to 1 on the far plane. Given a depth read back from a depth
+    /// buffer, this is the point on the surface that was drawn at that pixel.
+    pub fn screen_to_point(&self, ndc: Vector2<f32>, depth: f32) -> Point3<f32> {
+        unproject(self.inverse, ndc, depth)
+    }
+}
+
 impl Default for CameraMatrices {
     fn default() -> Self {
         Self {
@@ -38,14 +92,31 @@ impl Default for CameraMatrices {
     }
 }
 
+/// How a camera projects the scene onto the screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CameraProjection {
+    /// Near things appear large and far things appear small, like our eyes.
+    Perspective,
+    /// Things appear the same size no matter how far away they are, e.g. for top-down or editor views.
+    /// The width of the visible region is deduced from `height` and the aspect ratio.
+    Orthographic {
+        /// The height of the region of the scene that is visible on the screen, in world units.
+        height: f32
+    },
+}
+
 /// A perspective camera is used in a 3D setting. It emulates how our eyes or cameras work, making near things appear
 /// large and far things appear small.
+///
+/// The camera can be switched to an orthographic projection at runtime with `set_projection`, which keeps its
+/// position and orientation, so editors can swap between 3D and top-down views without replacing the camera.
 pub struct PerspectiveCamera {
     pos: Point3<f32>,
     dir: Vector3<f32>,
     up: Vector3<f32>,
 
     screen_resolution: Vector2<f32>,
+    projection: CameraProjection,
 
     proj: RwLock<Matrix4<f32>>,
     view: RwLock<Matrix4<f32>>,
@@ -65,6 +136,7 @@ impl PerspectiveCamera {
 
             // Supply a dummy screen resolution to provide a 1:1 aspect ratio.
             screen_resolution: cgmath::vec2(800.0, 800.0),
+            projection: CameraProjection::Perspective,
 
             proj: RwLock::new(Matrix4::one()),
             view: RwLock::new(Matrix4::one()),
@@ -77,7 +149,14 @@ impl PerspectiveCamera {
     /// Updates the matrices contained within the camera. Call if you need to retrieve a value from
     /// this camera, but the state is dirty.
     fn update_matrices(&self) {
-        *self.proj.write().unwrap() = cgmath::perspective(cgmath::Deg(60.0), self.get_aspect_ratio(), 0.01, 100.0);
+        *self.proj.write().unwrap() = match self.projection {
+            CameraProjection::Perspective => cgmath::perspective(cgmath::Deg(60.0), self.get_aspect_ratio(), 0.01, 100.0),
+            CameraProjection::Orthographic { height } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * self.get_aspect_ratio();
+                cgmath::ortho(-half_width, half_width, -half_height, half_height, 0.01, 100.0)
+            }
+        };
         *self.view.write().unwrap() = cgmath::Matrix4::look_at_dir(self.pos, self.dir, self.up);
         *self.combined.write().unwrap() = *self.proj.read().unwrap() * *self.view.read().unwrap();
         self.dirty.store(false, Ordering::SeqCst);
@@ -137,6 +216,17 @@ impl PerspectiveCamera {
         self.screen_resolution
     }
 
+    /// Sets how the camera projects the scene onto the screen.
+    pub fn set_projection(&mut self, projection: CameraProjection) {
+        self.projection = projection;
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    /// Retrieves how the camera projects the scene onto the screen.
+    pub fn get_projection(&self) -> CameraProjection {
+        self.projection
+    }
+
     /// Returns the aspect ratio supplied to this camera. This is calculated from the screen
     /// resolution: `width / height`.
     pub fn get_aspect_ratio(&self) -> f32 {
@@ -163,4 +253,51 @@ impl Camera for PerspectiveCamera {
         }
         *self.combined.read().unwrap()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centre_of_screen_looks_forwards() {
+        let cam = PerspectiveCamera::new(crate::pt3(1.0, 2.0, 3.0), crate::vec3(1.0, 0.0, 0.0), crate::vec3(0.0, 0.0, 1.0));
+        let (origin, dir) = cam.screen_to_ray(cgmath::vec2(0.0, 0.0));
+        assert!((dir - crate::vec3(1.0, 0.0, 0.0)).magnitude() < 1e-3);
+        // The ray starts on the near plane, just in front of the camera.
+        assert!((origin - crate::pt3(1.01, 2.0, 3.0)).magnitude() < 1e-3);
+
+        // The top right corner of the screen is up and to the right of the camera.
+        let (_, dir) = cam.screen_to_ray(window_to_ndc(cgmath::vec2(800.0, 0.0), cgmath::vec2(800, 800)));
+        assert!(dir.y < 0.0 && dir.z > 0.0);
+    }
+
+    #[test]
+    fn projected_points_are_unprojected() {
+        let cam = PerspectiveCamera::new(crate::pt3(1.0, 2.0, 3.0), crate::vec3(1.0, 0.0, 0.0), crate::vec3(0.0, 0.0, 1.0));
+        let matrices = Arc::new(RwLock::new(CameraMatrices::default()));
+        Camera::update_matrices(&cam, Arc::clone(&matrices));
+        let matrices = matrices.read().unwrap();
+
+        let point = crate::pt3(6.0, 2.5, 2.0);
+        let clip = matrices.combined * point.to_homogeneous();
+        let ndc = cgmath::vec2(clip.x / clip.w, clip.y / clip.w);
+        let unprojected = matrices.screen_to_point(ndc, clip.z / clip.w);
+        assert!((unprojected - point).magnitude() < 1e-2);
+
+        // The unprojected point lies on the ray through the same point on the screen.
+        let (origin, dir) = matrices.screen_to_ray(ndc);
+        assert!(((unprojected - origin).normalize() - dir).magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn camera_axes_match_orientation() {
+        let mut cam = PerspectiveCamera::new(crate::pt3(1.0, 2.0, 3.0), crate::vec3(1.0, 0.0, 0.0), crate::vec3(0.0, 0.0, 1.0));
+        for projection in [CameraProjection::Perspective, CameraProjection::Orthographic { height: 10.0 }] {
+            cam.set_projection(projection);
+            let (right, up) = camera_axes(cam.get_combined_matrix());
+            assert!((right - cam.get_right()).magnitude() < 1e-3);
+            assert!((up - crate::vec3(0.0, 0.0, 1.0)).magnitude() < 1e-3);
+        }
+    }
+}
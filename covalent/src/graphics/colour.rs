@@ -1,5 +1,5 @@
 /// A colour structure that contains red, green, blue and alpha information.
-/// These values must be between zero and one.
+/// These values should be between zero and one; values outside this range are clamped in the packed representation.
 /// Whenever any value in this struct is updated, it computes a packed representation as a u32,
 /// so retrieving this value is of zero cost.
 #[derive(Copy, Clone)]
@@ -24,16 +24,49 @@ impl Colour {
         c
     }
 
+    /// Creates a colour from its packed representation, `0xRRGGBBAA`, as used by many design tools.
+    /// See `packed` for more information about this format.
+    ///
+    /// # Examples
+    /// ```
+    /// use covalent::graphics::Colour;
+    /// let c = Colour::from_hex(0xFF7F19FF);
+    /// assert_eq!(1.0, c.r());
+    /// assert_eq!(0xFF7F19FF, c.packed());
+    /// ```
+    pub fn from_hex(hex: u32) -> Colour {
+        Colour::from_rgba_u8((hex >> 24) as u8, (hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// Creates an opaque colour from red, green and blue components between 0 and 255.
+    pub fn from_rgb_u8(r: u8, g: u8, b: u8) -> Colour {
+        Colour::from_rgba_u8(r, g, b, 255)
+    }
+
+    /// Creates a colour from red, green, blue and alpha components between 0 and 255.
+    pub fn from_rgba_u8(r: u8, g: u8, b: u8, a: u8) -> Colour {
+        // The packed value is built from the bytes directly, so converting back to bytes is always exact.
+        Colour {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+            a: a as f32 / 255.0,
+            packed: (r as u32) << 24 | (g as u32) << 16 | (b as u32) << 8 | a as u32
+        }
+    }
+
     /// Computes the packed representation of the colour,
     /// storing it in self.
     /// This is automatically called by all Colour functions,
     /// so you should never need to call it.
-    /// This results in undefined behaviour if any colour component is outside of the range [0, 1].
+    /// Components outside of the range [0, 1] are clamped, so they pack as zero or 255 rather than
+    /// corrupting neighbouring channels.
     fn compute_packed(&mut self) {
-        let r = (self.r * 255.0).min(255.0).max(0.0) as u32;
-        let g = (self.g * 255.0).min(255.0).max(0.0) as u32;
-        let b = (self.b * 255.0).min(255.0).max(0.0) as u32;
-        let a = (self.a * 255.0).min(255.0).max(0.0) as u32;
+        let pack = |x: f32| (x.clamp(0.0, 1.0) * 255.0) as u32;
+        let r = pack(self.r);
+        let g = pack(self.g);
+        let b = pack(self.b);
+        let a = pack(self.a);
         self.packed = r << 24 | g << 16 | b << 8 | a;
     }
 
@@ -97,4 +130,125 @@ impl Colour {
         self.a = a;
         self.compute_packed();
     }
+
+    /// Creates a colour from all four components, clamping each of them between zero and one.
+    fn clamped(r: f32, g: f32, b: f32, a: f32) -> Colour {
+        let mut c = Colour {
+            r: r.clamp(0.0, 1.0),
+            g: g.clamp(0.0, 1.0),
+            b: b.clamp(0.0, 1.0),
+            a: a.clamp(0.0, 1.0),
+            packed: 0
+        };
+        c.compute_packed();
+        c
+    }
+
+    /// Linearly interpolates each component between this colour and `other`. When `t` is zero, the result is this
+    /// colour; when `t` is one, the result is `other`. `t` is clamped between zero and one.
+    pub fn lerp(&self, other: &Colour, t: f32) -> Colour {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Colour::clamped(lerp(self.r, other.r), lerp(self.g, other.g), lerp(self.b, other.b), lerp(self.a, other.a))
+    }
+
+    /// Returns a copy of this colour with the given alpha (transparency) component.
+    pub fn with_alpha(&self, a: f32) -> Colour {
+        Colour::clamped(self.r, self.g, self.b, a)
+    }
+
+    /// Applies a blend function to the red, green and blue components of two colours.
+    /// The alpha of the result is the product of the alphas of the two colours.
+    fn blend(a: Colour, b: Colour, f: impl Fn(f32, f32) -> f32) -> Colour {
+        Colour::clamped(f(a.r, b.r), f(a.g, b.g), f(a.b, b.b), a.a * b.a)
+    }
+
+    /// Multiplies two colours together, which always darkens the result. Multiplying by white leaves a colour unchanged.
+    pub fn multiply(a: Colour, b: Colour) -> Colour {
+        Colour::blend(a, b, |a, b| a * b)
+    }
+
+    /// Inverts both colours, multiplies them, then inverts the result, which always lightens the result.
+    /// Screening with black leaves a colour unchanged.
+    pub fn screen(a: Colour, b: Colour) -> Colour {
+        Colour::blend(a, b, |a, b| 1.0 - (1.0 - a) * (1.0 - b))
+    }
+
+    /// Multiplies the dark parts of `a` and screens the light parts of `a` with `b`, increasing contrast.
+    /// Overlaying mid grey (0.5) leaves a colour unchanged.
+    pub fn overlay(a: Colour, b: Colour) -> Colour {
+        Colour::blend(a, b, |a, b| if a < 0.5 {
+            2.0 * a * b
+        } else {
+            1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+        })
+    }
+}
+
+/// Colours are serialized as `[r, g, b, a]`; the packed representation is recomputed when they are deserialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Colour {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&[self.r, self.g, self.b, self.a], serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Colour {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(deserializer)?;
+        let mut c = Colour::new(r, g, b);
+        c.set_a(a);
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rgb(c: Colour, expected: f32) {
+        for component in &[c.r(), c.g(), c.b()] {
+            assert!((component - expected).abs() < 1e-6, "expected {}, got {}", expected, component);
+        }
+    }
+
+    #[test]
+    fn out_of_range_components_are_clamped() {
+        let c = Colour::new(-1.0, 2.0, 0.5);
+        assert_eq!(0, (c.packed() & 0xFF000000) >> 24);
+        assert_eq!(255, (c.packed() & 0x00FF0000) >> 16);
+        assert_eq!(127, (c.packed() & 0x0000FF00) >> 8);
+        assert_eq!(255, c.packed() & 0x000000FF);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(0xFF7F19FF, Colour::from_hex(0xFF7F19FF).packed());
+        assert_eq!(0x00000000, Colour::from_hex(0x00000000).packed());
+        assert_eq!(0x12345678, Colour::from_rgba_u8(0x12, 0x34, 0x56, 0x78).packed());
+        assert_eq!(0x7F7F7FFF, Colour::from_rgb_u8(127, 127, 127).packed());
+        assert_rgb(Colour::from_rgb_u8(51, 51, 51), 0.2);
+    }
+
+    #[test]
+    fn lerp_clamps_t() {
+        let black = Colour::new(0.0, 0.0, 0.0);
+        let white = Colour::new(1.0, 1.0, 1.0).with_alpha(0.0);
+        assert_rgb(black.lerp(&white, 0.25), 0.25);
+        assert!((black.lerp(&white, 0.25).a() - 0.75).abs() < 1e-6);
+        assert_eq!(0xFFFFFF00, black.lerp(&white, 1.5).packed());
+        assert_eq!(0x000000FF, black.lerp(&white, -0.5).packed());
+    }
+
+    #[test]
+    fn blend_mid_grey() {
+        let grey = Colour::new(0.5, 0.5, 0.5);
+        assert_rgb(Colour::multiply(grey, grey), 0.25);
+        assert_rgb(Colour::screen(grey, grey), 0.75);
+        assert_rgb(Colour::overlay(grey, grey), 0.5);
+        assert_rgb(Colour::overlay(Colour::new(0.25, 0.25, 0.25), grey), 0.25);
+        assert_rgb(Colour::overlay(Colour::new(0.75, 0.75, 0.75), grey), 0.75);
+        assert_eq!(0x3F3F3FFF, Colour::multiply(grey, grey).packed());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use cgmath::{vec3, InnerSpace, Matrix, Matrix4, Vector3, Vector4};
+
+/// An axis-aligned bounding box, the smallest box with edges parallel to the axes that contains some shape.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    /// The corner of the box with the smallest coordinates along every axis.
+    pub min: Vector3<f32>,
+    /// The corner of the box with the largest coordinates along every axis.
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    /// Computes the bounding box of the given points, or `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vector3<f32>>) -> Option<Aabb> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Aabb { min: first, max: first }, |aabb, p| Aabb {
+            min: vec3(aabb.min.x.min(p.x), aabb.min.y.min(p.y), aabb.min.z.min(p.z)),
+            max: vec3(aabb.max.x.max(p.x), aabb.max.y.max(p.y), aabb.max.z.max(p.z)),
+        }))
+    }
+
+    /// Computes the bounding box of this box after it has been transformed by the given affine transformation,
+    /// for example to convert a box in a node's local space into world space. If the transformation rotates the
+    /// box, the result is larger than the box itself.
+    pub fn transformed(&self, xform: &Matrix4<f32>) -> Aabb {
+        let corners = (0..8).map(|i| {
+            let corner = vec3(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            (*xform * corner.extend(1.0)).truncate()
+        });
+        Aabb::from_points(corners).unwrap()
+    }
+}
+
+/// The region of space that a camera can see, bounded by six planes. Use this to skip drawing objects that are
+/// entirely off screen.
+#[derive(Debug, Copy, Clone)]
+pub struct Frustum {
+    /// Each plane `(a, b, c, d)` contains the points `(x, y, z)` where `ax + by + cz + d = 0`, and faces inwards, so
+    /// points inside the frustum give a non-negative value.
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum from a combined view and projection matrix, such as `CameraMatrices::combined`.
+    pub fn from_matrix(combined: Matrix4<f32>) -> Frustum {
+        // A point is visible if each of its clip space coordinates x, y and z is between -w and w. Each coordinate is
+        // a row of the matrix multiplied by the point, so each inequality is a plane made from two rows.
+        let (x, y, z, w) = (combined.row(0), combined.row(1), combined.row(2), combined.row(3));
+        Frustum {
+            planes: [w + x, w - x, w + y, w - y, w + z, w - z],
+        }
+    }
+
+    /// Checks whether any part of the given box might be inside the frustum. This may return true for some boxes
+    /// that are just outside the corners of the frustum, but never returns false for a visible box.
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Only the corner of the box furthest along the plane's normal needs to be checked.
+            let corner = vec3(
+                if plane.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            plane.truncate().dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxes_behind_the_camera_are_culled() {
+        let proj = cgmath::perspective(cgmath::Deg(60.0), 1.0, 0.01, 100.0);
+        let view = Matrix4::look_at_dir(cgmath::Point3::new(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0));
+        let frustum = Frustum::from_matrix(proj * view);
+
+        let unit = Aabb::from_points(vec![vec3(-0.5, -0.5, -0.5), vec3(0.5, 0.5, 0.5)]).unwrap();
+        let at = |x: f32, y: f32, z: f32| unit.transformed(&Matrix4::from_translation(vec3(x, y, z)));
+        assert!(frustum.intersects(&at(5.0, 0.0, 0.0)));
+        assert!(!frustum.intersects(&at(-5.0, 0.0, 0.0)));
+        assert!(!frustum.intersects(&at(5.0, 20.0, 0.0)));
+        assert!(!frustum.intersects(&at(200.0, 0.0, 0.0)));
+        // Boxes that straddle the edge of the frustum are still drawn.
+        assert!(frustum.intersects(&at(5.0, 3.2, 0.0)));
+    }
+}
@@ -13,9 +13,33 @@ pub use colour::*;
 mod camera;
 pub use camera::*;
 
-use cgmath::Vector3;
+mod window_command;
+pub use window_command::*;
+
+mod frustum;
+pub use frustum::*;
+
+mod text;
+pub use text::*;
+
+mod shader;
+pub use shader::*;
+
+mod sampler;
+pub use sampler::*;
+
+#[cfg(feature = "obj")]
+mod obj;
+#[cfg(feature = "obj")]
+pub use obj::*;
+
+pub mod shapes;
+
+use cgmath::{InnerSpace, Vector2, Vector3};
 
 /// A renderable is an object that can be rendered and displayed on screen. The graphics backend will render these.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Renderable {
     /// A renderable that renders nothing. Used when a Renderable must be supplied but you don't want to render anything.
     None,
@@ -23,15 +47,186 @@ pub enum Renderable {
     /// A primitive triangle containing three vertices.
     Triangle(RenderVertex, RenderVertex, RenderVertex),
 
+    /// A primitive quadrilateral containing four vertices, given in order around its edge. It is drawn as the two
+    /// triangles `(v0, v1, v2)` and `(v0, v2, v3)`, sharing two vertices, so it is cheaper than two `Triangle`s.
+    /// The quad should be flat and convex.
+    Quad(RenderVertex, RenderVertex, RenderVertex, RenderVertex),
+
     /// A GPU-optimised primitive capable of storing large amounts of _unchanging_ render information.
     /// The internal i64 is left as an implementation-defined feature for the backend to use.
     Mesh(i64),
+
+    /// A mesh drawn with a texture. The colour of each pixel is the vertex colour multiplied by the colour sampled
+    /// from the texture at the vertex's `uv` coordinates. Create these with `Renderable::with_texture`.
+    TexturedMesh {
+        /// The mesh to draw, as in `Renderable::Mesh`.
+        mesh_id: i64,
+        /// The texture to draw the mesh with, loaded with `Backend::load_texture`.
+        texture_id: TextureId,
+    },
+
+    /// A textured rectangle that always faces the camera (a "billboard"), placed at the position of its node.
+    /// The node's rotation and scale are ignored. Useful for characters and items in 2D games, or for particles.
+    Sprite {
+        /// The texture to draw on the sprite, loaded with `Backend::load_texture`.
+        texture_id: TextureId,
+        /// The width and height of the sprite, in world units.
+        size: Vector2<f32>,
+        /// Which point of the sprite is placed at the position of its node.
+        anchor: SpriteAnchor,
+    },
+}
+
+/// Which point of a `Sprite` is placed at the position of its node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpriteAnchor {
+    /// The centre of the sprite.
+    Centre,
+    /// The middle of the bottom edge of the sprite, so that characters stand on the position of their node.
+    BottomCentre,
 }
 
+impl SpriteAnchor {
+    /// Computes the offsets of the bottom left and top right corners of a sprite of the given size from its anchor,
+    /// in units along the camera's right and up directions.
+    pub fn corners(self, size: Vector2<f32>) -> (Vector2<f32>, Vector2<f32>) {
+        let half_width = size.x * 0.5;
+        match self {
+            SpriteAnchor::Centre => (cgmath::vec2(-half_width, -size.y * 0.5), cgmath::vec2(half_width, size.y * 0.5)),
+            SpriteAnchor::BottomCentre => (cgmath::vec2(-half_width, 0.0), cgmath::vec2(half_width, size.y)),
+        }
+    }
+}
+
+impl Renderable {
+    /// Converts a mesh into a textured mesh that samples from the given texture.
+    ///
+    /// # Panics
+    /// If this renderable is not a `Mesh`.
+    pub fn with_texture(self, texture_id: TextureId) -> Renderable {
+        match self {
+            Renderable::Mesh(mesh_id) => Renderable::TexturedMesh { mesh_id, texture_id },
+            _ => panic!("only meshes can be textured"),
+        }
+    }
+
+    /// Computes the bounding box of this renderable, relative to the node it belongs to.
+    /// Returns `None` for `Renderable::None`, for meshes, whose vertices are only known to the graphics backend, and
+    /// for sprites, which turn to face the camera.
+    pub fn bounds(&self) -> Option<Aabb> {
+        match self {
+            Renderable::Triangle(v0, v1, v2) => Aabb::from_points(vec![v0.pos, v1.pos, v2.pos]),
+            Renderable::Quad(v0, v1, v2, v3) => Aabb::from_points(vec![v0.pos, v1.pos, v2.pos, v3.pos]),
+            _ => None,
+        }
+    }
+}
+
+/// Identifies a texture that has been loaded by the graphics backend; see `Backend::load_texture`.
+pub type TextureId = i64;
+
+/// The order of the bytes of each pixel in an image given to `Backend::load_texture`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Red, green, blue, then alpha. Most image decoders produce this.
+    Rgba8,
+    /// Blue, green, red, then alpha. Many windowing and video APIs produce this, e.g. screenshots on Windows.
+    Bgra8,
+}
+
+impl PixelFormat {
+    /// Reorders the bytes of the given pixels from this format into `Rgba8`.
+    pub fn to_rgba(self, mut pixels: Vec<u8>) -> Vec<u8> {
+        if self == PixelFormat::Bgra8 {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        pixels
+    }
+}
+
+/// Identifies a shader program that has been compiled by the graphics backend; see `Backend::load_shader`.
+pub type ShaderId = i64;
+
 /// Contains all the necessary information to define a single vertex.
 /// This includes its position in world space.
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenderVertex {
     pub pos: Vector3<f32>,
-    pub col: Colour
+    pub col: Colour,
+    /// The texture coordinates of this vertex. `(0, 0)` is the bottom left of the texture, and `(1, 1)` is the top
+    /// right. This is ignored unless the vertex is part of a `TexturedMesh`.
+    pub uv: Vector2<f32>,
+    /// The direction that the surface faces at this vertex, used for lighting. This should have unit length.
+    /// If this is zero, the graphics backend will fill it in with the normal of a triangle that uses this vertex;
+    /// see `fill_flat_normals`.
+    pub normal: Vector3<f32>
+}
+
+/// Computes the unit normal of the triangle with the given corners, which points towards the side from which the
+/// triangle appears to be wound anticlockwise. Degenerate triangles have a normal of zero.
+pub fn face_normal(a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> Vector3<f32> {
+    let n = (b - a).cross(c - a);
+    if n.magnitude2() > 0.0 {
+        n.normalize()
+    } else {
+        n
+    }
+}
+
+/// Gives every vertex with a normal of zero the normal of the first triangle in `inds` that uses it.
+/// Vertices that already have a normal are left unchanged.
+///
+/// Meshes whose triangles don't share vertices will look faceted ("flat shaded"). Where vertices are shared, the
+/// normal of only one of the triangles is used, so supply normals yourself for smooth surfaces.
+pub fn fill_flat_normals(verts: &mut [RenderVertex], inds: &[u32]) {
+    let zero = Vector3::new(0.0, 0.0, 0.0);
+    let mut filled = verts.iter().map(|v| v.normal != zero).collect::<Vec<_>>();
+    for tri in inds.chunks_exact(3) {
+        let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+        if filled[a] && filled[b] && filled[c] {
+            continue;
+        }
+        let normal = face_normal(verts[a].pos, verts[b].pos, verts[c].pos);
+        for &i in &[a, b, c] {
+            if !filled[i] {
+                verts[i].normal = normal;
+                filled[i] = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{vec2, vec3};
+
+    #[test]
+    fn bgra_pixels_are_reordered() {
+        let pixels = vec![0, 0, 255, 255, 255, 0, 0, 128];
+        assert_eq!(vec![255, 0, 0, 255, 0, 0, 255, 128], PixelFormat::Bgra8.to_rgba(pixels.clone()));
+        assert_eq!(pixels, PixelFormat::Rgba8.to_rgba(pixels.clone()));
+    }
+
+    #[test]
+    fn flat_normals_follow_winding() {
+        let vert = |x: f32, y: f32| RenderVertex {
+            pos: vec3(x, y, 0.0),
+            col: Colour::new(1.0, 1.0, 1.0),
+            uv: vec2(0.0, 0.0),
+            normal: vec3(0.0, 0.0, 0.0)
+        };
+        let mut verts = vec![vert(0.0, 0.0), vert(1.0, 0.0), vert(0.0, 1.0), vert(1.0, 1.0)];
+        verts[3].normal = vec3(1.0, 0.0, 0.0);
+        // The first triangle is anticlockwise when viewed from +Z, the second is clockwise.
+        fill_flat_normals(&mut verts, &[0, 1, 2, 1, 3, 2]);
+        assert_eq!(vec3(0.0, 0.0, 1.0), verts[0].normal);
+        assert_eq!(vec3(0.0, 0.0, 1.0), verts[1].normal);
+        assert_eq!(vec3(0.0, 0.0, 1.0), verts[2].normal);
+        assert_eq!(vec3(1.0, 0.0, 0.0), verts[3].normal);
+    }
 }
@@ -0,0 +1,98 @@
+use crate::graphics::{Colour, RenderVertex};
+use cgmath::{vec2, vec3};
+use std::path::Path;
+
+/// Loads every model in a Wavefront OBJ file into a single list of vertices and indices, ready to be passed to
+/// `Backend::create_mesh`. Polygons with more than three sides are triangulated.
+///
+/// OBJ files don't store vertex colours, so every vertex is white; use `Node::tint` to colour the mesh.
+/// Vertices without texture coordinates are given a `uv` of `(0, 0)`. Vertices without normals are given a normal
+/// of zero, so the backend gives them flat normals when the mesh is created.
+///
+/// # Panics
+/// If the file can't be read or isn't a valid OBJ file.
+pub fn load_obj(path: &Path) -> (Vec<RenderVertex>, Vec<u32>) {
+    let (models, _materials) = match tobj::load_obj(path, true) {
+        Ok(result) => result,
+        Err(e) => panic!("could not load OBJ file {}: {}", path.display(), e),
+    };
+
+    let mut verts = Vec::new();
+    let mut inds = Vec::new();
+    for model in models {
+        let mesh = model.mesh;
+        // Each model's indices start at zero, so offset them past the vertices of the previous models.
+        let offset = verts.len() as u32;
+        for i in 0..mesh.positions.len() / 3 {
+            let uv = if mesh.texcoords.len() >= 2 * i + 2 {
+                vec2(mesh.texcoords[2 * i], mesh.texcoords[2 * i + 1])
+            } else {
+                vec2(0.0, 0.0)
+            };
+            let normal = if mesh.normals.len() >= 3 * i + 3 {
+                vec3(mesh.normals[3 * i], mesh.normals[3 * i + 1], mesh.normals[3 * i + 2])
+            } else {
+                vec3(0.0, 0.0, 0.0)
+            };
+            verts.push(RenderVertex {
+                pos: vec3(mesh.positions[3 * i], mesh.positions[3 * i + 1], mesh.positions[3 * i + 2]),
+                col: Colour::new(1.0, 1.0, 1.0),
+                uv,
+                normal,
+            });
+        }
+        inds.extend(mesh.indices.iter().map(|i| i + offset));
+    }
+    (verts, inds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the given OBJ source to a temporary file and loads it.
+    fn load_source(name: &str, source: &str) -> (Vec<RenderVertex>, Vec<u32>) {
+        let path = std::env::temp_dir().join(format!("covalent-{}-{}.obj", std::process::id(), name));
+        std::fs::write(&path, source).unwrap();
+        let result = load_obj(&path);
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn missing_attributes_get_defaults() {
+        let (verts, inds) = load_source("positions", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+        assert_eq!(3, verts.len());
+        assert_eq!(vec![0, 1, 2], inds);
+        assert_eq!(vec3(1.0, 0.0, 0.0), verts[1].pos);
+        for vert in &verts {
+            assert_eq!(0xFFFFFFFF, vert.col.packed());
+            assert_eq!(vec2(0.0, 0.0), vert.uv);
+            assert_eq!(vec3(0.0, 0.0, 0.0), vert.normal);
+        }
+    }
+
+    #[test]
+    fn quads_are_triangulated() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                      vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+                      vn 0 0 1\n\
+                      f 1/1/1 2/2/1 3/3/1 4/4/1\n";
+        let (verts, inds) = load_source("quad", source);
+        assert_eq!(4, verts.len());
+        assert_eq!(6, inds.len());
+        assert!(inds.iter().all(|i| (*i as usize) < verts.len()));
+        assert_eq!(vec2(1.0, 1.0), verts[2].uv);
+        assert!(verts.iter().all(|vert| vert.normal == vec3(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn indices_are_offset_across_models() {
+        let source = "o first\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+                      o second\nv 0 0 1\nv 1 0 1\nv 0 1 1\nf 4 5 6\n";
+        let (verts, inds) = load_source("models", source);
+        assert_eq!(6, verts.len());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], inds);
+        assert_eq!(vec3(0.0, 0.0, 1.0), verts[3].pos);
+    }
+}
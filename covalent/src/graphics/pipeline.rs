@@ -1,6 +1,8 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, RwLock};
-use crate::graphics::{RenderTarget, CameraMatrices};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::graphics::{RenderTarget, Camera, CameraMatrices, Colour, FontId, FramebufferId, ShaderId};
+use cgmath::{vec2, Matrix4, Vector2, Vector3};
 
 /// The `Pipeline` is the way to tell covalent how to render your scene.
 /// It contains a series of steps (`PipelinePhase`) which the graphics backend will execute sequentially.
@@ -10,17 +12,22 @@ use crate::graphics::{RenderTarget, CameraMatrices};
 /// 
 /// # Rules
 /// Although programmable, pipelines must conform to certain rules.
-/// - There must be at least one `Render` phase that targets the `Window` render target. This allows the user to see the result.
+/// - There must be at least one `Render` or `PostProcess` phase that targets the `Window` render target. This allows
+/// the user to see the result.
 /// - `RenderChannel`s must exist for the `RenderTarget` they are assigned to. Please refer to the render channel and render
 /// target documentation for more on this topic.
 pub struct Pipeline {
-    phases: BTreeMap<i32, (String, PipelinePhase)>
+    phases: BTreeMap<i32, (String, PipelinePhase)>,
+    /// True if the pipeline has been checked for likely mistakes since it was last modified.
+    /// This stops us from logging the same warnings every frame.
+    diagnosed: AtomicBool
 }
 
 impl Pipeline {
     pub fn new() -> Pipeline {
         Pipeline {
-            phases: BTreeMap::new()
+            phases: BTreeMap::new(),
+            diagnosed: AtomicBool::new(false)
         }
     }
 
@@ -33,6 +40,7 @@ impl Pipeline {
             panic!("phase {} was already contained within this pipeline, conflicting phases were \"{}\"; \"{}\"", time, name, self.phases.get(&time).unwrap().0);
         }
         self.phases.insert(time, (name, phase));
+        self.diagnosed.store(false, Ordering::SeqCst);
     }
 
     /// Asserts that the pipeline conforms to the rules set out in the `Pipeline`'s documentation.
@@ -42,15 +50,15 @@ impl Pipeline {
             match phase {
                 PipelinePhase::Render { target, .. } => {
                     match target {
-                        // When we use framebuffers / other render targets, this will be expanded upon.
                         RenderTarget::Window => {
                             contains_render_to_window = true;
                         }
-                        _ => {
-                            log::error!("Render target {:?} unimplemented", target);
-                        }
+                        RenderTarget::Framebuffer { .. } => {}
                     }
                 },
+                PipelinePhase::PostProcess { target: RenderTarget::Window, .. } => {
+                    contains_render_to_window = true;
+                },
                 _ => {}
             }
         }
@@ -58,10 +66,78 @@ impl Pipeline {
         if !contains_render_to_window {
             panic!("pipeline was invalid! no phase was detected that renders to the user's window; this is disallowed behaviour!");
         }
+
+        if !self.diagnosed.swap(true, Ordering::SeqCst) {
+            self.diagnose_phases();
+        }
+    }
+
+    /// Logs warnings about pipeline configurations that are allowed, but are usually mistakes. See `diagnostics`.
+    fn diagnose_phases(&self) {
+        for warning in self.diagnostics() {
+            log::warn!("{}", warning);
+        }
+    }
+
+    /// Finds pipeline configurations that are allowed, but are usually mistakes:
+    /// - Two `Render` phases targeting the window with no `Clear` of the window between them, where the second phase
+    ///   has no `clear_colour` and covers the whole window with opaque renderables. The second phase will draw over the
+    ///   first, which is only sometimes intended. Phases that blend, or letterbox to a `target_aspect`, are expected to
+    ///   draw on top of earlier phases (e.g. overlays), so they are not reported.
+    /// - A `PostProcess` phase whose `input` framebuffer isn't written by any earlier phase. The input would hold
+    ///   whatever was drawn to it in the previous frame, or nothing at all in the first frame, which usually shows up as
+    ///   a black screen.
+    fn diagnostics(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut written = HashSet::new();
+        let mut last_window_render: Option<&str> = None;
+        for (name, phase) in self.phases.values() {
+            if let PipelinePhase::PostProcess { input, .. } = phase {
+                if !written.contains(input) {
+                    warnings.push(format!("phase \"{}\" reads framebuffer {} before any earlier phase writes to it", name, input));
+                }
+            }
+            if let RenderTarget::Framebuffer { id } = phase.target() {
+                written.insert(id);
+            }
+
+            match phase {
+                PipelinePhase::Clear { target: RenderTarget::Window, .. }
+                | PipelinePhase::ClearGradient { target: RenderTarget::Window, .. } => {
+                    last_window_render = None;
+                },
+                PipelinePhase::Render { settings, target: RenderTarget::Window } => {
+                    if settings.clear_colour.is_some() {
+                        last_window_render = None;
+                    }
+                    let overwrites_window = settings.effective_blend() == BlendMode::Opaque && settings.target_aspect.is_none();
+                    if let (true, Some(previous)) = (overwrites_window, last_window_render) {
+                        warnings.push(format!("phases \"{}\" and \"{}\" both render to the window with no clear between them; \"{}\" will draw over \"{}\"",
+                            previous, name, name, previous));
+                    }
+                    last_window_render = Some(name);
+                },
+                PipelinePhase::PostProcess { target: RenderTarget::Window, .. } => {
+                    // Post-processing deliberately replaces the whole window, so there's nothing to warn about.
+                    last_window_render = Some(name);
+                },
+                // Phases targeting framebuffers don't affect what is drawn to the window.
+                _ => {}
+            }
+        }
+        warnings
     }
 }
 
 impl Pipeline {
+    /// Retrieves the target aspect ratio of the first phase that renders to the window, if it letterboxes its output.
+    pub(crate) fn window_target_aspect(&self) -> Option<f32> {
+        self.phases.values().find_map(|(_, phase)| match phase {
+            PipelinePhase::Render { settings, target: RenderTarget::Window } => Some(settings.target_aspect),
+            _ => None,
+        }).flatten()
+    }
+
     pub fn iter(&self) -> std::collections::btree_map::Values<i32, (String, PipelinePhase)> {
         self.check_phases();
         self.phases.values()
@@ -72,14 +148,72 @@ impl Pipeline {
 /// To render to the screen, construct a pipeline of these phases, which will be executed sequentially every frame by
 /// the graphics backend.
 pub enum PipelinePhase {
-    /// Clears a render target.
+    /// Clears a render target. Each buffer of the render target is only cleared if a value is given for it, so for
+    /// example a phase that draws a HUD on top of the scene can be preceded by a phase that only clears depth.
     Clear {
-        target: RenderTarget
+        /// The render target to clear.
+        target: RenderTarget,
+        /// If this is `Some`, the colour buffer of the render target is cleared to the given colour.
+        colour: Option<Colour>,
+        /// If this is `Some`, the depth buffer of the render target is cleared to the given value.
+        /// `Some(1.0)` is the furthest possible depth, which is usually what you want.
+        depth: Option<f32>,
+        /// If this is `Some`, the stencil buffer of the render target is also cleared to the given value.
+        /// Leave this as `None` unless a later phase uses the stencil buffer.
+        stencil: Option<i32>
+    },
+    /// Clears a render target's depth buffer, and fills its colour buffer with a vertical gradient from `top` to
+    /// `bottom`, e.g. to draw a simple sky behind the scene. The gradient always spans the whole render target.
+    ClearGradient {
+        /// The render target to clear.
+        target: RenderTarget,
+        /// The colour of the top edge of the render target.
+        top: Colour,
+        /// The colour of the bottom edge of the render target.
+        bottom: Colour
     },
     /// Render a scene using specific settings, outputting the result to the given render target.
     Render {
         settings: RenderSettings,
         target: RenderTarget
+    },
+    /// Draws a single triangle covering the whole render target with a custom shader that samples the colour
+    /// channel of the `input` framebuffer, e.g. to blur or recolour the output of an earlier `Render` phase.
+    /// `input` must not be the same framebuffer as `target`.
+    ///
+    /// The shader receives the following inputs. It may ignore any that it doesn't use.
+    /// - Vertex attribute `position` (`vec3`): the position of the vertex in normalised device coordinates.
+    /// - Vertex attribute `uv` (`vec2`): texture coordinates, which are in the range [0, 1] inside the render target.
+    /// - Uniform `tex` (`sampler2D`): the colour channel of the `input` framebuffer.
+    PostProcess {
+        /// The framebuffer whose colour channel the shader samples, which an earlier phase should draw to.
+        input: FramebufferId,
+        /// The render target to draw the processed image to.
+        target: RenderTarget,
+        /// The shader program that processes the image, loaded with `Backend::load_shader`.
+        shader: ShaderId
+    },
+    /// Draws the text queued this frame with `Scene::draw_text` on top of whatever is already in the render target,
+    /// ignoring the depth buffer and any camera. This is usually the last phase, so that text appears over the scene.
+    Text {
+        /// The render target to draw the text on.
+        target: RenderTarget,
+        /// If this is `Some`, the visible lines of the scene's `DebugConsole` are also drawn in this font, in the top
+        /// left corner of the render target.
+        console_font: Option<FontId>
+    }
+}
+
+impl PipelinePhase {
+    /// The render target that this phase draws to.
+    pub fn target(&self) -> RenderTarget {
+        match self {
+            PipelinePhase::Clear { target, .. }
+            | PipelinePhase::ClearGradient { target, .. }
+            | PipelinePhase::Render { target, .. }
+            | PipelinePhase::PostProcess { target, .. }
+            | PipelinePhase::Text { target, .. } => *target,
+        }
     }
 }
 
@@ -88,14 +222,308 @@ pub struct RenderSettings {
     /// What transformation should be used to render the scene?
     /// This is modified by `Camera` objects.
     /// This controls the position and angle of the viewpoint of the render.
-    pub camera_matrices: Arc<RwLock<CameraMatrices>>
+    pub camera_matrices: Arc<RwLock<CameraMatrices>>,
+
+    /// If this is `Some`, the scene is rendered from the point of view of this camera, and `camera_matrices` is
+    /// ignored. Sharing the camera with the render settings means changes to it, such as switching between a
+    /// perspective and orthographic projection, take effect in this phase straight away.
+    pub camera: Option<Arc<RwLock<dyn Camera + Send + Sync>>>,
+
+    /// If this is `Some`, the region of the render target that this phase draws into is cleared to this colour,
+    /// and its depth buffer is reset, before the scene is rendered.
+    /// Leave this as `None` for phases that draw on top of an earlier phase, such as overlays.
+    pub clear_colour: Option<Colour>,
+
+    /// The range that normalised depth values are mapped to when written to the depth buffer, as `(near, far)`.
+    /// The default is `(0.0, 1.0)`, which uses the whole depth buffer.
+    ///
+    /// Restricting the range (e.g. `(0.0, 0.1)`) is useful to force some geometry, such as decals or a first-person
+    /// weapon, to be drawn in front of the rest of the scene. Both values should be between zero and one.
+    /// Note that if `near` is greater than `far`, depth comparisons are effectively inverted, so closer objects
+    /// will be hidden behind further ones.
+    pub depth_range: (f32, f32),
+
+    /// The colour that everything drawn in this phase is multiplied by, in addition to each node's own `tint`.
+    /// The default is white, which leaves colours unchanged.
+    pub tint: Colour,
+
+    /// If this is `Some`, everything in this phase is drawn in the given flat colour, ignoring vertex colours,
+    /// textures and tints. This is useful for silhouettes, shadow placeholders and debugging.
+    /// The default is `None`, which keeps the usual colours.
+    pub override_colour: Option<Colour>,
+
+    /// The colour of the outline drawn around nodes that have `outline` set.
+    pub outline_colour: Colour,
+
+    /// The width, in pixels, of the outline drawn around nodes that have `outline` set.
+    /// Some graphics backends only support a width of one pixel.
+    pub outline_width: f32,
+
+    /// If this is `Some`, a crosshair is drawn at the centre of the render target once the scene has been rendered.
+    pub crosshair: Option<Crosshair>,
+
+    /// If this is `Some`, the scene is rendered into the largest centred region of the render target with the given
+    /// aspect ratio (width divided by height), and the rest of the target is filled with black bars. This keeps the
+    /// visible area of fixed-aspect games the same on any window size.
+    ///
+    /// When the first phase that renders to the window letterboxes its output, `WindowResizeEvent`s report the size
+    /// of the letterboxed region instead of the whole window, so cameras use the correct aspect ratio.
+    pub target_aspect: Option<f32>,
+
+    /// If this is `Some`, the scene is drawn with this shader program (see `Backend::load_shader`) instead of the
+    /// backend's built-in one. The same vertex attributes and uniforms are supplied as to the built-in program, such
+    /// as the `combined` and `model` matrices, so the shader may use whichever of them it needs.
+    /// Outlines and crosshairs are always drawn with the built-in programs.
+    pub shader: Option<ShaderId>,
+
+    /// If this is `Some`, the scene is lit by a directional light, like the sun, shining in this direction.
+    /// Each vertex colour is multiplied by `max(dot(normal, -light_dir), ambient)`, so surfaces facing the light are
+    /// fully bright and surfaces facing away from it are only lit by the ambient light.
+    /// The default is `None`, which draws everything unlit, as is usual for 2D scenes and UI.
+    pub light_dir: Option<Vector3<f32>>,
+
+    /// The minimum brightness of surfaces facing away from the light, between zero and one.
+    /// This is ignored unless `light_dir` is set.
+    pub ambient: f32,
+
+    /// If true, renderables whose bounding boxes are entirely outside the camera's view are skipped rather than sent
+    /// to the GPU. Disable this when debugging missing objects. Defaults to true.
+    pub frustum_cull: bool,
+
+    /// How the colours drawn in this phase are combined with what has already been drawn. See `BlendMode` for
+    /// which order to put opaque and blended phases in. The default is `Opaque`.
+    pub blend: BlendMode,
+
+    /// If true, renderables are sorted so that those furthest from the camera are drawn first, so that translucent
+    /// renderables blend with whatever is behind them. If `blend` is left as `Opaque`, transparent phases are
+    /// alpha blended; see `effective_blend`.
+    /// The default is false, since sorting has a cost and opaque phases don't need it.
+    pub transparent: bool,
+
+    /// Whether triangles are filled in, or only their edges or corners are drawn. `PolygonMode::Line` gives a
+    /// wireframe view, which is useful for checking the topology and winding of meshes. The default is `Fill`.
+    ///
+    /// This is shared, like `camera_matrices`, so that it can be changed while the pipeline is running, for example
+    /// to toggle a wireframe view with a key press.
+    pub polygon_mode: Arc<RwLock<PolygonMode>>,
+
+    /// Which triangles are skipped according to the direction they are wound on screen. Culling the back faces of
+    /// closed meshes saves the GPU from drawing surfaces that can't be seen. The default is `CullMode::None`, which
+    /// draws every triangle, since flat shapes such as planes may be seen from either side.
+    pub cull_mode: CullMode
+}
+
+/// Chooses which triangles are front-facing, according to the order their vertices appear on screen, so that the
+/// back faces can be skipped. The shapes in `graphics::shapes` are wound anticlockwise when viewed from outside, as
+/// is conventional, so they should be drawn with `CounterClockwiseFront`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CullMode {
+    /// Every triangle is drawn, whichever way it faces.
+    None,
+    /// Triangles whose vertices appear clockwise on screen face the camera; anticlockwise triangles are skipped.
+    ClockwiseFront,
+    /// Triangles whose vertices appear anticlockwise on screen face the camera; clockwise triangles are skipped.
+    CounterClockwiseFront,
+}
+
+/// How the triangles in a render phase are drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PolygonMode {
+    /// Triangles are filled in.
+    Fill,
+    /// Only the edges of triangles are drawn.
+    Line,
+    /// Only the vertices of triangles are drawn, as single pixels.
+    Point,
+}
+
+/// How a render phase combines the colours it draws with the colours already in the render target.
+///
+/// Blended phases test against the depth buffer but don't write to it, so overlapping translucent renderables
+/// don't hide each other. This means that blended renderables should be drawn in a phase after the opaque ones,
+/// so that they are hidden behind opaque renderables but not the other way around.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Colours replace what is behind them, ignoring their alpha.
+    Opaque,
+    /// Colours are mixed with what is behind them according to their alpha, as with glass or smoke.
+    /// Set `RenderSettings::transparent` in the same phase, so that renderables are drawn back to front.
+    AlphaBlend,
+    /// Colours, multiplied by their alpha, are added to what is behind them, so they only ever brighten it.
+    /// Useful for glows, fire and particles. The order in which renderables are drawn doesn't matter.
+    Additive,
+}
+
+impl RenderSettings {
+    /// Retrieves the combined view and projection matrix that this phase renders with.
+    /// This comes from `camera` if it is set, and from `camera_matrices` otherwise.
+    pub fn combined_matrix(&self) -> Matrix4<f32> {
+        match &self.camera {
+            Some(camera) => camera.read().unwrap().get_combined_matrix(),
+            None => self.camera_matrices.read().unwrap().combined,
+        }
+    }
+
+    /// The blend mode that this phase is actually drawn with. This is `blend`, except that transparent phases
+    /// with an `Opaque` blend mode are drawn with `AlphaBlend`.
+    pub fn effective_blend(&self) -> BlendMode {
+        match self.blend {
+            BlendMode::Opaque if self.transparent => BlendMode::AlphaBlend,
+            blend => blend,
+        }
+    }
+
+    /// Computes the region of a render target of the given size that the scene should be rendered into,
+    /// according to `target_aspect`. Returns the offset of the bottom left corner of the region, and its size.
+    pub fn viewport(&self, target_size: Vector2<u32>) -> (Vector2<u32>, Vector2<u32>) {
+        match self.target_aspect {
+            Some(aspect) => letterbox(target_size, aspect),
+            None => (vec2(0, 0), target_size),
+        }
+    }
+}
+
+/// Computes the largest centred region of a render target of the given size with the given aspect ratio.
+/// Returns the offset of the bottom left corner of the region, and its size.
+pub(crate) fn letterbox(target_size: Vector2<u32>, aspect: f32) -> (Vector2<u32>, Vector2<u32>) {
+    let target_aspect = target_size.x as f32 / target_size.y.max(1) as f32;
+    let size = if target_aspect > aspect {
+        // The target is too wide, so add bars to the left and right.
+        vec2(((target_size.y as f32 * aspect).round() as u32).min(target_size.x), target_size.y)
+    } else {
+        // The target is too tall, so add bars to the top and bottom.
+        vec2(target_size.x, ((target_size.x as f32 / aspect).round() as u32).min(target_size.y))
+    };
+    ((target_size - size) / 2, size)
+}
+
+/// A crosshair (aiming reticle) drawn in screen space at the centre of a render target, on top of the scene.
+#[derive(Copy, Clone)]
+pub struct Crosshair {
+    /// The length of each arm of the crosshair, in pixels, measured from the centre.
+    pub size: f32,
+    /// The thickness of each line of the crosshair, in pixels.
+    pub thickness: f32,
+    /// The colour of the crosshair.
+    pub colour: Colour,
+    /// If this is `Some`, the position in the world of the surface under the crosshair is written beside it in this
+    /// font, which helps when placing objects or debugging collisions. The backend finds the position by reading
+    /// the depth at the centre of the viewport back from the GPU, which stalls rendering, so only use this while
+    /// debugging.
+    pub readout_font: Option<FontId>
+}
+
+impl Default for Crosshair {
+    /// Creates a small white crosshair.
+    fn default() -> Self {
+        Self {
+            size: 8.0,
+            thickness: 2.0,
+            colour: Colour::new(1.0, 1.0, 1.0),
+            readout_font: None
+        }
+    }
 }
 
 impl Default for RenderSettings {
     /// Initialises render settings to the default values.
     fn default() -> Self {
         Self {
-            camera_matrices: Arc::new(RwLock::new(CameraMatrices::default()))
+            camera_matrices: Arc::new(RwLock::new(CameraMatrices::default())),
+            camera: None,
+            clear_colour: None,
+            depth_range: (0.0, 1.0),
+            tint: Colour::new(1.0, 1.0, 1.0),
+            override_colour: None,
+            outline_colour: Colour::new(1.0, 0.6, 0.0),
+            outline_width: 2.0,
+            crosshair: None,
+            target_aspect: None,
+            shader: None,
+            light_dir: None,
+            ambient: 0.3,
+            frustum_cull: true,
+            blend: BlendMode::Opaque,
+            transparent: false,
+            polygon_mode: Arc::new(RwLock::new(PolygonMode::Fill)),
+            cull_mode: CullMode::None
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_square_window() {
+        // 16:9 content in a square window has bars at the top and bottom.
+        let (offset, size) = letterbox(vec2(720, 720), 16.0 / 9.0);
+        assert_eq!(size, vec2(720, 405));
+        assert_eq!(offset, vec2(0, 157));
+
+        // 4:3 content in a 16:9 window has bars at the left and right.
+        let (offset, size) = letterbox(vec2(1280, 720), 4.0 / 3.0);
+        assert_eq!(size, vec2(960, 720));
+        assert_eq!(offset, vec2(160, 0));
+    }
+
+    fn window_render(settings: RenderSettings) -> PipelinePhase {
+        PipelinePhase::Render { settings, target: RenderTarget::Window }
+    }
+
+    #[test]
+    fn overdraw_warning_ignores_overlays() {
+        let mut pipeline = Pipeline::new();
+        pipeline.add_phase(0, "scene".to_string(), window_render(RenderSettings::default()));
+        pipeline.add_phase(1, "glass".to_string(), window_render(RenderSettings { transparent: true, ..Default::default() }));
+        pipeline.add_phase(2, "glow".to_string(), window_render(RenderSettings { blend: BlendMode::Additive, ..Default::default() }));
+        pipeline.add_phase(3, "letterboxed".to_string(), window_render(RenderSettings { target_aspect: Some(1.0), ..Default::default() }));
+        assert!(pipeline.diagnostics().is_empty());
+
+        pipeline.add_phase(4, "opaque".to_string(), window_render(RenderSettings::default()));
+        assert_eq!(1, pipeline.diagnostics().len());
+    }
+
+    #[test]
+    fn post_process_input_must_be_written_first() {
+        let mut pipeline = Pipeline::new();
+        pipeline.add_phase(0, "blur".to_string(), PipelinePhase::PostProcess { input: 7, target: RenderTarget::Window, shader: 0 });
+        pipeline.add_phase(1, "scene".to_string(), PipelinePhase::Render { settings: RenderSettings::default(), target: RenderTarget::Framebuffer { id: 7 } });
+        assert_eq!(1, pipeline.diagnostics().len());
+
+        let mut pipeline = Pipeline::new();
+        pipeline.add_phase(0, "scene".to_string(), PipelinePhase::Render { settings: RenderSettings::default(), target: RenderTarget::Framebuffer { id: 7 } });
+        pipeline.add_phase(1, "blur".to_string(), PipelinePhase::PostProcess { input: 7, target: RenderTarget::Window, shader: 0 });
+        assert!(pipeline.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn transparent_implies_alpha_blend() {
+        let mut settings = RenderSettings::default();
+        assert_eq!(BlendMode::Opaque, settings.effective_blend());
+
+        settings.transparent = true;
+        assert_eq!(BlendMode::AlphaBlend, settings.effective_blend());
+
+        settings.blend = BlendMode::Additive;
+        assert_eq!(BlendMode::Additive, settings.effective_blend());
+    }
+
+    #[test]
+    fn camera_overrides_camera_matrices() {
+        use crate::graphics::{CameraProjection, PerspectiveCamera};
+        use cgmath::SquareMatrix;
+
+        let camera = Arc::new(RwLock::new(PerspectiveCamera::new(
+            cgmath::Point3::new(0.0, 0.0, 5.0), cgmath::vec3(0.0, 0.0, -1.0), cgmath::vec3(0.0, 1.0, 0.0))));
+        let mut settings = RenderSettings::default();
+        assert_eq!(Matrix4::identity(), settings.combined_matrix());
+
+        settings.camera = Some(Arc::clone(&camera) as Arc<RwLock<dyn Camera + Send + Sync>>);
+        let perspective = settings.combined_matrix();
+        assert_eq!(camera.read().unwrap().get_combined_matrix(), perspective);
+
+        camera.write().unwrap().set_projection(CameraProjection::Orthographic { height: 4.0 });
+        assert_ne!(perspective, settings.combined_matrix());
+    }
+}
@@ -5,12 +5,21 @@
 /// 
 /// Render targets have multiple `RenderChannel`s. These are the specific output layers that covalent will render to.
 /// See the `RenderChannel` documentation for more information.
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RenderTarget {
     /// The default render target is the user's screen. This is the window that covalent opens.
     Window,
+    /// An off-screen framebuffer, created with `Backend::create_framebuffer`. Rendering to a framebuffer instead of
+    /// the window allows the result to be processed further by later phases, e.g. for post-processing effects.
+    Framebuffer {
+        /// The framebuffer to render to, as returned by `Backend::create_framebuffer`.
+        id: FramebufferId
+    },
 }
 
+/// Identifies an off-screen framebuffer created by a graphics backend.
+pub type FramebufferId = i64;
+
 impl RenderTarget {
     /// Tests whether the given render channel is supported by the given render target.
     pub fn is_render_channel_supported(&self, rc: RenderChannel) -> bool {
@@ -21,6 +30,12 @@ impl RenderTarget {
                     RenderChannel::Depth => true
                 }
             }
+            RenderTarget::Framebuffer { .. } => {
+                match rc {
+                    RenderChannel::Colour(i) => (0..8).contains(&i),
+                    RenderChannel::Depth => true
+                }
+            }
         }
     }
 }
@@ -29,6 +44,7 @@ impl RenderTarget {
 /// Not all render targets will support all types of render channel.
 /// 
 /// The Screen render target always has the `Colour(0)` render channel.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RenderChannel {
     /// Render targets will always output to a colour channel.
     /// The default colour channel is `Colour(0)`.
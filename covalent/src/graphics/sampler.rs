@@ -0,0 +1,51 @@
+/// Controls how a texture's pixels are read when it is drawn; see `Backend::set_texture_sampler`.
+///
+/// Textures loaded with `Backend::load_texture` have mipmaps: smaller copies of the texture that are used when it is
+/// drawn smaller than its actual size, which avoids shimmering on distant surfaces. The filter applies both to the
+/// pixels within a mipmap and to the choice between mipmaps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sampler {
+    /// How to combine nearby pixels when the texture is drawn larger or smaller than its actual size.
+    pub filter: SamplerFilter,
+    /// What happens to horizontal texture coordinates outside the range 0 to 1.
+    pub wrap_u: SamplerWrap,
+    /// What happens to vertical texture coordinates outside the range 0 to 1.
+    pub wrap_v: SamplerWrap,
+    /// The maximum amount of anisotropic filtering, which keeps textures sharp when they are viewed at a glancing
+    /// angle, e.g. floors. One disables it; higher values (usually up to 16) are sharper but slower. This only
+    /// applies when the texture is drawn smaller than its actual size, and backends may limit it further.
+    pub anisotropy: u16,
+}
+
+impl Default for Sampler {
+    /// Creates a sampler with linear filtering that clamps texture coordinates, without anisotropic filtering.
+    fn default() -> Self {
+        Sampler {
+            filter: SamplerFilter::Linear,
+            wrap_u: SamplerWrap::Clamp,
+            wrap_v: SamplerWrap::Clamp,
+            anisotropy: 1,
+        }
+    }
+}
+
+/// How a `Sampler` combines nearby pixels of a texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SamplerFilter {
+    /// Uses the nearest pixel of the nearest mipmap, so textures look blocky up close. Suits pixel art.
+    Nearest,
+    /// Blends the nearest four pixels in each of the two nearest mipmaps (trilinear filtering), so textures look
+    /// smooth.
+    Linear,
+}
+
+/// What a `Sampler` does with texture coordinates outside the range 0 to 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SamplerWrap {
+    /// Uses the pixel at the nearest edge of the texture.
+    Clamp,
+    /// Tiles the texture.
+    Repeat,
+    /// Tiles the texture, flipping every other tile so that neighbouring tiles meet seamlessly.
+    MirroredRepeat,
+}
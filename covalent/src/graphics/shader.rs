@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+/// The source code of a shader program; see `Backend::load_shader_source`.
+/// The shading language depends on the backend; for example, the GL backend uses GLSL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderSource {
+    /// Source code that is already in memory, for example from `include_str!`.
+    Inline {
+        /// The source code of the vertex shader.
+        vertex: String,
+        /// The source code of the fragment shader.
+        fragment: String,
+    },
+    /// Source code that is read from the given files. Programs loaded from files are recompiled by
+    /// `Context::reload_shaders`, so shaders can be edited while the game is running.
+    Files {
+        /// The path of the file containing the vertex shader.
+        vertex: PathBuf,
+        /// The path of the file containing the fragment shader.
+        fragment: PathBuf,
+    },
+}
+
+impl ShaderSource {
+    /// Creates a shader source from the given vertex and fragment shader source code.
+    pub fn inline(vertex: impl Into<String>, fragment: impl Into<String>) -> ShaderSource {
+        ShaderSource::Inline { vertex: vertex.into(), fragment: fragment.into() }
+    }
+
+    /// Creates a shader source that reads the vertex and fragment shaders from the files at the given paths.
+    pub fn from_files(vertex: impl Into<PathBuf>, fragment: impl Into<PathBuf>) -> ShaderSource {
+        ShaderSource::Files { vertex: vertex.into(), fragment: fragment.into() }
+    }
+
+    /// The files that the vertex and fragment shaders are read from, if this source was created with `from_files`.
+    pub fn paths(&self) -> Option<(&Path, &Path)> {
+        match self {
+            ShaderSource::Inline { .. } => None,
+            ShaderSource::Files { vertex, fragment } => Some((vertex, fragment)),
+        }
+    }
+
+    /// Retrieves the vertex and fragment shader source code, as `(vertex_src, fragment_src)`, reading it from disk if
+    /// needed. Errors name the file that couldn't be read.
+    pub fn read(&self) -> std::io::Result<(String, String)> {
+        match self {
+            ShaderSource::Inline { vertex, fragment } => Ok((vertex.clone(), fragment.clone())),
+            ShaderSource::Files { vertex, fragment } => Ok((read_file(vertex)?, read_file(fragment)?)),
+        }
+    }
+}
+
+fn read_file(path: &Path) -> std::io::Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_errors_name_the_missing_file() {
+        let source = ShaderSource::from_files("does/not/exist.vert", "does/not/exist.frag");
+        let e = source.read().unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        assert!(e.to_string().contains("exist.vert"));
+
+        let source = ShaderSource::inline("vertex", "fragment");
+        assert_eq!(source.read().unwrap(), ("vertex".to_string(), "fragment".to_string()));
+        assert!(source.paths().is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Generators for common primitive meshes. Each generator returns a list of vertices and a list of indices,
+//! which can be passed directly to `Backend::create_mesh`.
+//!
+//! Each generator comes in two forms. The plain form, such as `cube`, creates white vertices, which can be
+//! coloured afterwards with `Node::tint`. Generators ending in `_with` take a function that maps a vertex position
+//! (relative to the centre of the shape) to the colour of that vertex. This allows gradients or height-based
+//! colouring to be created without manually looping over the vertices.
+//!
+//! Generated vertices also have texture coordinates, so the shapes can be used as textured meshes, and normals,
+//! so they can be lit.
+
+use crate::graphics::{Colour, RenderVertex};
+use cgmath::{vec2, vec3, Vector3};
+
+/// Creates a white cube; see `cube_with`.
+pub fn cube(size: f32) -> (Vec<RenderVertex>, Vec<u32>) {
+    cube_with(size, white)
+}
+
+/// Creates an axis-aligned cube centred on the origin, with side length `size`.
+/// Each face has its own four vertices, so the cube has 24 vertices and 36 indices.
+/// Triangles are wound anticlockwise when viewed from outside the cube.
+pub fn cube_with(size: f32, colour: impl Fn(Vector3<f32>) -> Colour) -> (Vec<RenderVertex>, Vec<u32>) {
+    let h = size * 0.5;
+    // Each face is defined by its normal, and two axes `u` and `v` spanning the face such that `u × v = normal`.
+    let faces = [
+        (vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0)),
+        (vec3(-1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0)),
+        (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.0)),
+        (vec3(0.0, -1.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)),
+        (vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+        (vec3(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0)),
+    ];
+
+    let mut verts = Vec::with_capacity(24);
+    let mut inds = Vec::with_capacity(36);
+    for (normal, u, v) in faces.iter() {
+        let first = verts.len() as u32;
+        for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter() {
+            let pos = (*normal + *u * *su + *v * *sv) * h;
+            verts.push(RenderVertex { pos, col: colour(pos), uv: vec2((su + 1.0) * 0.5, (sv + 1.0) * 0.5), normal: *normal });
+        }
+        inds.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    (verts, inds)
+}
+
+/// Creates a white UV sphere; see `uv_sphere_with`.
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> (Vec<RenderVertex>, Vec<u32>) {
+    uv_sphere_with(radius, segments, rings, white)
+}
+
+/// Creates a UV sphere centred on the origin, with poles on the Z axis.
+/// `segments` is the amount of subdivisions around the equator, and `rings` is the amount of subdivisions
+/// from pole to pole. The sphere has `(segments + 1) * (rings + 1)` vertices.
+/// Triangles are wound anticlockwise when viewed from outside the sphere.
+///
+/// # Panics
+/// If `segments` is less than three or `rings` is less than two, this function will panic.
+pub fn uv_sphere_with(radius: f32, segments: u32, rings: u32, colour: impl Fn(Vector3<f32>) -> Colour) -> (Vec<RenderVertex>, Vec<u32>) {
+    assert!(segments >= 3 && rings >= 2, "a sphere needs at least three segments and two rings");
+
+    let mut verts = Vec::with_capacity(((segments + 1) * (rings + 1)) as usize);
+    for ring in 0..=rings {
+        let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+        for segment in 0..=segments {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / segments as f32;
+            let normal = vec3(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos());
+            let pos = normal * radius;
+            let uv = vec2(segment as f32 / segments as f32, 1.0 - ring as f32 / rings as f32);
+            verts.push(RenderVertex { pos, col: colour(pos), uv, normal });
+        }
+    }
+
+    let mut inds = Vec::with_capacity((segments * rings * 6) as usize);
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * (segments + 1) + segment;
+            let b = a + segments + 1;
+            inds.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    (verts, inds)
+}
+
+/// Creates a white plane; see `plane_with`.
+pub fn plane(width: f32, depth: f32, subdivisions: u32) -> (Vec<RenderVertex>, Vec<u32>) {
+    plane_with(width, depth, subdivisions, white)
+}
+
+/// Creates a flat plane centred on the origin and facing up the Z axis, spanning `width` along the X axis and
+/// `depth` along the Y axis. The plane is split into a grid of `subdivisions` by `subdivisions` squares, so it has
+/// `(subdivisions + 1) * (subdivisions + 1)` vertices; more subdivisions allow finer vertex colouring.
+/// Texture coordinates span the whole plane once. Triangles are wound anticlockwise when viewed from above.
+///
+/// # Panics
+/// If `subdivisions` is zero, this function will panic.
+pub fn plane_with(width: f32, depth: f32, subdivisions: u32, colour: impl Fn(Vector3<f32>) -> Colour) -> (Vec<RenderVertex>, Vec<u32>) {
+    assert!(subdivisions >= 1, "a plane needs at least one subdivision");
+
+    let n = subdivisions + 1;
+    let mut verts = Vec::with_capacity((n * n) as usize);
+    for y in 0..n {
+        for x in 0..n {
+            let uv = vec2(x as f32 / subdivisions as f32, y as f32 / subdivisions as f32);
+            let pos = vec3((uv.x - 0.5) * width, (uv.y - 0.5) * depth, 0.0);
+            verts.push(RenderVertex { pos, col: colour(pos), uv, normal: vec3(0.0, 0.0, 1.0) });
+        }
+    }
+
+    let mut inds = Vec::with_capacity((subdivisions * subdivisions * 6) as usize);
+    for y in 0..subdivisions {
+        for x in 0..subdivisions {
+            let a = y * n + x;
+            let b = a + n;
+            inds.extend_from_slice(&[a, a + 1, b + 1, a, b + 1, b]);
+        }
+    }
+    (verts, inds)
+}
+
+/// The colour function used by the plain generators.
+fn white(_pos: Vector3<f32>) -> Colour {
+    Colour::new(1.0, 1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_has_four_vertices_per_face() {
+        let (verts, inds) = cube_with(2.0, |_| Colour::new(1.0, 1.0, 1.0));
+        assert_eq!(24, verts.len());
+        assert_eq!(36, inds.len());
+        assert!(verts.iter().all(|v| v.pos.x.abs() == 1.0 || v.pos.y.abs() == 1.0 || v.pos.z.abs() == 1.0));
+    }
+
+    #[test]
+    fn plane_faces_up() {
+        let (verts, inds) = plane(2.0, 4.0, 4);
+        assert_eq!(25, verts.len());
+        assert_eq!(4 * 4 * 6, inds.len());
+        assert!(verts.iter().all(|v| v.pos.x.abs() <= 1.0 && v.pos.y.abs() <= 2.0 && v.pos.z == 0.0));
+        for tri in inds.chunks(3) {
+            let n = crate::graphics::face_normal(verts[tri[0] as usize].pos, verts[tri[1] as usize].pos, verts[tri[2] as usize].pos);
+            assert_eq!(vec3(0.0, 0.0, 1.0), n);
+        }
+    }
+
+    #[test]
+    fn gradient_sphere() {
+        // Fade from blue at the bottom pole to red at the top pole.
+        let (verts, inds) = uv_sphere_with(1.0, 16, 8, |pos| {
+            let t = pos.z * 0.5 + 0.5;
+            Colour::new(t, 0.0, 1.0 - t)
+        });
+        assert_eq!(17 * 9, verts.len());
+        assert_eq!(16 * 8 * 6, inds.len());
+        assert!(inds.iter().all(|&i| (i as usize) < verts.len()));
+
+        let top = &verts[0];
+        assert_eq!(255, (top.col.packed() & 0xFF000000) >> 24);
+        assert_eq!(0, (top.col.packed() & 0x0000FF00) >> 8);
+        let bottom = &verts[verts.len() - 1];
+        assert_eq!(0, (bottom.col.packed() & 0xFF000000) >> 24);
+        assert_eq!(255, (bottom.col.packed() & 0x0000FF00) >> 8);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::graphics::Colour;
+use cgmath::Vector2;
+
+/// Identifies a font that has been loaded by the graphics backend; see `Backend::load_font`.
+pub type FontId = i64;
+
+/// A string of text to be drawn in screen space, on top of the scene. Text is drawn for a single frame, so queue it
+/// with `Scene::draw_text` every tick that it should be visible, e.g. to show a score.
+#[derive(Clone)]
+pub struct Text {
+    /// The font to draw the text in, loaded with `Backend::load_font`.
+    pub font: FontId,
+    /// The text to draw, on a single line.
+    pub string: String,
+    /// The position of the top left corner of the text, in pixels from the top left corner of the render target.
+    pub pos: Vector2<f32>,
+    /// The height of a line of text, in pixels.
+    pub size: f32,
+    /// The colour of the text.
+    pub colour: Colour,
+}
@@ -0,0 +1,21 @@
+use crate::input::CursorIcon;
+use crate::WindowMode;
+
+/// A change to the window that covalent is running in, requested by the game.
+///
+/// Window commands are queued up on the scene, since components can't access the window directly. The graphics
+/// backend takes the queued commands once per frame by calling `Context::drain_window_commands`, and applies them
+/// on the render thread.
+#[derive(Debug, Clone, Copy)]
+pub enum WindowCommand {
+    /// Changes the appearance of the mouse cursor while it is over the window.
+    SetCursorIcon(CursorIcon),
+    /// If true, confines the mouse cursor to the window, so it can't leave while the mouse is moved. This is usual
+    /// for first-person cameras, which otherwise stop turning once the cursor reaches the edge of the screen.
+    /// Some platforms don't support this.
+    SetCursorGrab(bool),
+    /// Shows or hides the mouse cursor while it is over the window.
+    SetCursorVisible(bool),
+    /// Switches the window between windowed and fullscreen modes.
+    SetWindowMode(WindowMode),
+}
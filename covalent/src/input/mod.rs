@@ -5,6 +5,10 @@
 //! This allows for more flexibility with designing a graphics backend, and also leaves room
 //! to just not make a graphics backend at all (e.g. for server-side code).
 
+/// Identifies a single connected gamepad. IDs are assigned by the graphics backend, and may be reused after a
+/// gamepad is disconnected.
+pub type GamepadId = usize;
+
 /// Hardware-dependent keyboard scan code.
 /// Copied from the `winit` crate, version 0.22.2.
 pub type ScanCode = u32;
@@ -12,15 +16,91 @@ pub type ScanCode = u32;
 /// Describes the input state of a key.
 /// Copied from the `winit` crate, version 0.22.2.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ElementState {
     Pressed,
     Released,
 }
 
+/// Describes a button of a mouse controller.
+/// Adapted from the `winit` crate, version 0.22.2.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+/// The unit in which a mouse scroll amount is measured. Mouse wheels usually scroll by lines, and touchpads usually
+/// scroll by pixels.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollUnit {
+    /// The amount is a number of lines (or rows and columns) to scroll, as if using the arrow keys.
+    Lines,
+    /// The amount is a number of pixels to scroll.
+    Pixels,
+}
+
+/// Describes a button of a gamepad, named by its position on the controller.
+/// Copied from the `gilrs` crate, version 0.7.
+///
+/// The face buttons are named by compass direction, so `South` is A on an Xbox controller and Cross on a
+/// PlayStation controller.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    C,
+    Z,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Unknown,
+}
+
+/// Describes an analogue axis of a gamepad, such as one direction of a thumb stick.
+/// Copied from the `gilrs` crate, version 0.7.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    RightZ,
+    DPadX,
+    DPadY,
+    Unknown,
+}
+
 /// Symbolic name for a keyboard key.
 /// Copied from the `winit` crate, version 0.22.2.
+///
+/// With the `serde` feature enabled, keys are serialized by the name of their variant (e.g. `"Space"`), so
+/// serialized key codes remain stable even if variants are added or reordered.
 #[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 #[allow(missing_docs)]
 pub enum VirtualKeyCode {
@@ -218,3 +298,66 @@ pub enum VirtualKeyCode {
     Paste,
     Cut,
 }
+
+/// Describes the appearance of the mouse cursor.
+/// Copied from the `winit` crate, version 0.22.2.
+///
+/// Not every platform supports every cursor icon. If an icon is unavailable, the default arrow is shown instead.
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy)]
+#[allow(missing_docs)]
+pub enum CursorIcon {
+    /// The platform-dependent default cursor.
+    #[default]
+    Default,
+    /// A simple crosshair.
+    Crosshair,
+    /// A hand (often used to indicate links in web browsers).
+    Hand,
+    /// Self explanatory.
+    Arrow,
+    /// Indicates something is to be moved.
+    Move,
+    /// Indicates text that may be selected or edited.
+    Text,
+    /// Program busy indicator.
+    Wait,
+    /// Help indicator (often rendered as a "?")
+    Help,
+    /// Progress indicator. Shows that processing is being done. But in contrast
+    /// with "Wait" the user may still interact with the program. Often rendered
+    /// as a spinning beach ball, or an arrow with a watch or hourglass.
+    Progress,
+
+    /// Cursor showing that something cannot be done.
+    NotAllowed,
+    ContextMenu,
+    Cell,
+    VerticalText,
+    Alias,
+    Copy,
+    NoDrop,
+    /// Indicates something can be grabbed.
+    Grab,
+    /// Indicates something is grabbed.
+    Grabbing,
+    AllScroll,
+    ZoomIn,
+    ZoomOut,
+
+    /// Indicate that some edge is to be moved. For example, the 'SeResize' cursor
+    /// is used when the movement starts from the south-east corner of the box.
+    EResize,
+    NResize,
+    NeResize,
+    NwResize,
+    SResize,
+    SeResize,
+    SwResize,
+    WResize,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+}
@@ -21,15 +21,26 @@
 #![warn(missing_docs)]
 
 use std::time;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 mod display_hints;
-pub use display_hints::DisplayHints;
+pub use display_hints::{DisplayHints, WindowMode};
+
+mod clock;
+pub use clock::Clock;
+
+mod debug_console;
+pub use debug_console::{DebugConsole, DebugConsoleLine};
+
+mod profiler;
+pub use profiler::FrameProfiler;
 
 pub mod graphics;
 pub mod input;
 pub mod scene;
 pub mod events;
+pub mod physics;
 
 pub use cgmath;
 pub use cgmath::{vec1, vec2, vec3, vec4};
@@ -55,7 +66,10 @@ pub fn pt3<S>(x: S, y: S, z: S) -> cgmath::Point3<S> {
 /// constant specified in the stopwatch constructor.
 pub struct InterpolatedStopwatch {
     times: Vec<time::Instant>,
-    offset: usize
+    offset: usize,
+    /// The amount of times `tick` has been called, up to the length of `times`. Until the buffer is full, the
+    /// remaining entries all hold the time the stopwatch was created, and must not be counted as real intervals.
+    ticks: usize
 }
 
 impl InterpolatedStopwatch {
@@ -66,7 +80,8 @@ impl InterpolatedStopwatch {
         }
         InterpolatedStopwatch {
             times: vec,
-            offset: 0
+            offset: 0,
+            ticks: 0
         }
     }
 
@@ -85,15 +100,28 @@ impl InterpolatedStopwatch {
         let old_time = self.times[prev_offset];
         let time = self.times[self.offset].duration_since(old_time);
         self.offset = (self.offset + 1) % self.times.len();
+        self.ticks = (self.ticks + 1).min(self.times.len());
         time
     }
 
+    /// Retrieves the average time between calls to `tick`, over the last `n` calls.
+    /// Returns zero if `tick` has not been called yet.
     pub fn average_time(&self) -> time::Duration {
         let prev_offset = match self.offset {
             0 => self.times.len() - 1,
             _ => self.offset - 1
         };
-        self.times[prev_offset].duration_since(self.times[self.offset]).div_f64(self.times.len() as f64)
+        // Until the buffer is full, the oldest entry is the time the stopwatch was created, so there is one interval
+        // per tick. Once it is full, the oldest entry is a tick, so there is one fewer interval than entries.
+        let intervals = if self.ticks < self.times.len() {
+            self.ticks
+        } else {
+            self.times.len() - 1
+        };
+        if intervals == 0 {
+            return time::Duration::from_secs(0);
+        }
+        self.times[prev_offset].duration_since(self.times[self.offset]).div_f64(intervals as f64)
     }
 }
 
@@ -123,16 +151,24 @@ impl InterpolatedStopwatch {
 /// to make sure that all nodes are actually moved the same amount per frame.
 pub struct Context {
     frame_stopwatch: RefCell<InterpolatedStopwatch>,
+    gamepads: RefCell<Vec<input::GamepadId>>,
     graphics_pipeline: graphics::Pipeline,
-    scene: Arc<RwLock<scene::Scene>>
+    scene: Arc<RwLock<scene::Scene>>,
+    /// The number of `TickEvent`s fired so far.
+    frame: Cell<u64>,
+    /// The total `delta` of every `TickEvent` fired so far, in seconds.
+    elapsed: Cell<f64>
 }
 
 impl Context {
     fn new(pipeline: graphics::Pipeline, scene: Arc<RwLock<scene::Scene>>) -> Context {
         Context {
             frame_stopwatch: RefCell::from(InterpolatedStopwatch::new(512)),
+            gamepads: RefCell::new(Vec::new()),
             graphics_pipeline: pipeline,
-            scene
+            scene,
+            frame: Cell::new(0),
+            elapsed: Cell::new(0.0)
         }
     }
 
@@ -143,9 +179,52 @@ impl Context {
 
         // Asynchronously process frame.
         let delta = self.frame_stopwatch.borrow_mut().tick();
-        self.scene.read().unwrap().events.tick.write().unwrap().handle(events::TickEvent {
-            delta: delta.as_secs_f64()
+        let scene = self.scene.read().unwrap();
+        scene.frame_profiler.write().unwrap().record(delta);
+
+        // Editors and menus may move nodes while the game is paused, so report moved nodes even then.
+        let moved = scene.take_transform_changes();
+        if !moved.is_empty() {
+            let mut handler = scene.events.node_transform_changed.write().unwrap();
+            for node_id in moved {
+                handler.handle(&events::NodeTransformChangedEvent { node_id });
+            }
+        }
+
+        if scene.clock.read().unwrap().is_paused() {
+            return;
+        }
+
+        let (fixed_steps, fixed_timestep) = {
+            let mut clock = scene.clock.write().unwrap();
+            (clock.fixed_steps(delta.as_secs_f64()), clock.fixed_timestep())
+        };
+        for _ in 0..fixed_steps {
+            scene.events.fixed_tick.write().unwrap().handle(&events::FixedTickEvent {
+                delta: fixed_timestep
+            });
+        }
+
+        let delta = scene.clock.read().unwrap().clamp_delta(delta.as_secs_f64());
+        let frame = self.frame.get();
+        let elapsed = self.elapsed.get() + delta;
+        self.frame.set(frame + 1);
+        self.elapsed.set(elapsed);
+        scene.events.tick.write().unwrap().handle(&events::TickEvent {
+            delta,
+            frame,
+            elapsed
         });
+
+        // Don't keep the stagger locked while its listeners run, in case they change the amount of groups.
+        let (group, groups, delta) = {
+            let mut stagger = scene.events.tick_stagger.write().unwrap();
+            let (group, delta) = stagger.advance(delta);
+            (group, stagger.groups(), delta)
+        };
+        scene.events.staggered_tick.write().unwrap().handle_filtered(&events::StaggeredTickEvent {
+            delta
+        }, |id| events::TickStagger::group_of(id, groups) == group);
     }
     
     /// Should be called by the graphics backend as soon as rendering the frame is complete.
@@ -155,26 +234,263 @@ impl Context {
 
     /// Should be called by the graphics backend once every frame to retrieve the current graphics pipeline.
     pub fn render_phases(&self) -> (Arc<RwLock<scene::Scene>>, std::collections::btree_map::Values<i32, (String, graphics::PipelinePhase)>) {
-        //log::trace!("{:.1} FPS", 1.0 / self.frame_stopwatch.borrow().average_time().as_secs_f64());
+        //log::trace!("{:.1} FPS", self.fps());
         (Arc::clone(&self.scene), self.graphics_pipeline.iter())
     }
 
+    /// Retrieves the average time taken by each of the last few hundred frames.
+    pub fn average_frame_time(&self) -> time::Duration {
+        // The stopwatch is only mutably borrowed briefly at the start of `begin_frame`, never while listeners run.
+        self.frame_stopwatch.borrow().average_time()
+    }
+
+    /// Retrieves the average framerate over the last few hundred frames, in frames per second.
+    /// Returns zero if no time has been measured yet.
+    pub fn fps(&self) -> f64 {
+        let frame_time = self.average_frame_time().as_secs_f64();
+        if frame_time > 0.0 {
+            1.0 / frame_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Sets the largest `delta` (in seconds) that a single `TickEvent` may report.
+    /// This is a shortcut for `Clock::set_max_delta` on the current `Scene`'s clock.
+    pub fn set_max_delta(&self, max_delta: Option<f64>) {
+        self.scene.read().unwrap().clock.write().unwrap().set_max_delta(max_delta);
+    }
+
+    /// Retrieves the largest `delta` (in seconds) that a single `TickEvent` may report, if any.
+    pub fn max_delta(&self) -> Option<f64> {
+        self.scene.read().unwrap().clock.read().unwrap().max_delta()
+    }
+
+    /// Sets the `delta` (in seconds) of every `FixedTickEvent`.
+    /// This is a shortcut for `Clock::set_fixed_timestep` on the current `Scene`'s clock.
+    pub fn set_fixed_timestep(&self, fixed_timestep: f64) {
+        self.scene.read().unwrap().clock.write().unwrap().set_fixed_timestep(fixed_timestep);
+    }
+
+    /// Retrieves the `delta` (in seconds) of every `FixedTickEvent`.
+    pub fn fixed_timestep(&self) -> f64 {
+        self.scene.read().unwrap().clock.read().unwrap().fixed_timestep()
+    }
+
+    /// Pauses or resumes gameplay, while still rendering and handling input.
+    /// This is a shortcut for `Clock::set_paused` on the current `Scene`'s clock.
+    pub fn set_paused(&self, paused: bool) {
+        self.scene.read().unwrap().clock.write().unwrap().set_paused(paused);
+    }
+
+    /// Returns true if gameplay is paused; see `set_paused`.
+    pub fn is_paused(&self) -> bool {
+        self.scene.read().unwrap().clock.read().unwrap().is_paused()
+    }
+
+    /// Sets how fast gameplay time passes relative to real time, e.g. `0.5` for slow motion.
+    /// This is a shortcut for `Clock::set_time_scale` on the current `Scene`'s clock.
+    pub fn set_time_scale(&self, time_scale: f64) {
+        self.scene.read().unwrap().clock.write().unwrap().set_time_scale(time_scale);
+    }
+
+    /// Retrieves how fast gameplay time passes relative to real time; see `set_time_scale`.
+    pub fn time_scale(&self) -> f64 {
+        self.scene.read().unwrap().clock.read().unwrap().time_scale()
+    }
+
+    /// Retrieves the debug console of the current `Scene`. Its visible lines are drawn every frame by any
+    /// `PipelinePhase::Text` with a `console_font`.
+    pub fn debug_console(&self) -> Arc<RwLock<DebugConsole>> {
+        Arc::clone(&self.scene.read().unwrap().debug_console)
+    }
+
+    /// Requests that the mouse cursor changes to the given icon while it is over the window.
+    /// Components without access to the context can call `Scene::set_cursor_icon` instead.
+    pub fn set_cursor_icon(&self, icon: input::CursorIcon) {
+        self.scene.read().unwrap().set_cursor_icon(icon);
+    }
+
+    /// Requests that the window switches to the given mode, e.g. fullscreen.
+    /// Components without access to the context can call `Scene::set_window_mode` instead.
+    pub fn set_window_mode(&self, mode: WindowMode) {
+        self.scene.read().unwrap().set_window_mode(mode);
+    }
+
+    /// Requests that the mouse cursor is confined to the window (or released, if `grab` is false).
+    /// Components without access to the context can call `Scene::set_cursor_grab` instead.
+    pub fn set_cursor_grab(&self, grab: bool) {
+        self.scene.read().unwrap().set_cursor_grab(grab);
+    }
+
+    /// Requests that the mouse cursor is shown or hidden while it is over the window.
+    /// Components without access to the context can call `Scene::set_cursor_visible` instead.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.scene.read().unwrap().set_cursor_visible(visible);
+    }
+
+    /// Requests that the graphics backend recompiles every shader program that was loaded with
+    /// `ShaderSource::Files`, so shaders can be edited while the game is running. If a program fails to compile, the
+    /// error is logged and the previous version is kept.
+    /// Components without access to the context can call `Scene::reload_shaders` instead.
+    pub fn reload_shaders(&self) {
+        self.scene.read().unwrap().reload_shaders();
+    }
+
+    /// Should be called by the graphics backend once per frame, before rendering.
+    /// Returns true if the game has called `reload_shaders` since the last call.
+    pub fn take_shader_reload_request(&self) -> bool {
+        self.scene.read().unwrap().take_shader_reload_request()
+    }
+
+    /// Should be called by the graphics backend once per frame, before rendering.
+    /// Returns the window changes that the game has requested since the last call, in the order they were requested.
+    pub fn drain_window_commands(&self) -> Vec<graphics::WindowCommand> {
+        self.scene.read().unwrap().drain_window_commands()
+    }
+
+    /// Should be called by the graphics backend once per frame, after `begin_frame`.
+    /// Returns the text that the game has queued to be drawn this frame with `Scene::draw_text`.
+    pub fn drain_text(&self) -> Vec<graphics::Text> {
+        self.scene.read().unwrap().drain_text()
+    }
+
+    /// Should be called by the graphics backend once per frame, on the render thread, after `begin_frame` and before
+    /// executing the pipeline's phases. Returns the framebuffers that the game has asked to render the current `Scene`
+    /// into with `Scene::render_to_framebuffer`, along with the camera for each. The backend should clear each
+    /// framebuffer to transparent black, then draw the scene into it as a `Render` phase with default `RenderSettings`
+    /// and the given camera would.
+    pub fn framebuffer_cameras(&self) -> Vec<(graphics::FramebufferId, Arc<RwLock<dyn graphics::Camera + Send + Sync>>)> {
+        self.scene.read().unwrap().framebuffer_cameras()
+    }
+
+    /// Retrieves the current `Scene`'s input event log. Set its capacity to a non-zero value to start recording
+    /// recent input events, e.g. to attach to a bug report.
+    pub fn input_event_log(&self) -> Arc<RwLock<events::InputEventLog>> {
+        Arc::clone(&self.scene.read().unwrap().input_event_log)
+    }
+
+    /// Should be called by the graphics backend just before the application exits, e.g. when the user closes the
+    /// window. This fires a `ShutdownEvent` in the current `Scene`, and only returns once every listener has run.
+    pub fn process_shutdown(&self) {
+        log::info!("Shutting down");
+        self.scene.read().unwrap().events.shutdown.write().unwrap().handle(&events::ShutdownEvent {});
+    }
+
     /// Should be called by the graphics backend whenever a key is pressed/released.
-    /// This will trigger an event handler in the current `Scene`.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
     pub fn process_keyboard_event(&self, e: events::KeyboardEvent) {
-        self.scene.read().unwrap().events.key.write().unwrap().handle(e);
+        self.process_input_event(events::InputEvent::Keyboard(e));
     }
 
     /// Should be called by the graphics backend whenever the mouse is moved.
-    /// This will trigger an event handler in the current `Scene`.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
     pub fn process_mouse_delta_event(&self, e: events::MouseDeltaEvent) {
-        self.scene.read().unwrap().events.mouse_delta.write().unwrap().handle(e);
+        self.process_input_event(events::InputEvent::MouseDelta(e));
+    }
+
+    /// Should be called by the graphics backend whenever a mouse button is pressed/released.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
+    pub fn process_mouse_button_event(&self, e: events::MouseButtonEvent) {
+        self.process_input_event(events::InputEvent::MouseButton(e));
+    }
+
+    /// Should be called by the graphics backend whenever the mouse wheel is scrolled.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
+    pub fn process_mouse_scroll_event(&self, e: events::MouseScrollEvent) {
+        self.process_input_event(events::InputEvent::MouseScroll(e));
+    }
+
+    /// Should be called by the graphics backend whenever the cursor moves within the window.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
+    pub fn process_cursor_moved_event(&self, e: events::CursorMovedEvent) {
+        self.process_input_event(events::InputEvent::CursorMoved(e));
+    }
+
+    /// Should be called by the graphics backend whenever the cursor enters the window.
+    /// This will trigger an event handler in the current `Scene`.
+    pub fn process_cursor_entered_event(&self, e: events::CursorEnteredEvent) {
+        self.scene.read().unwrap().events.cursor_entered.write().unwrap().handle(&e);
+    }
+
+    /// Should be called by the graphics backend whenever the cursor leaves the window.
+    /// This will trigger an event handler in the current `Scene`.
+    pub fn process_cursor_left_event(&self, e: events::CursorLeftEvent) {
+        self.scene.read().unwrap().events.cursor_left.write().unwrap().handle(&e);
+    }
+
+    /// Should be called by the graphics backend whenever a gamepad button is pressed/released or a gamepad axis moves.
+    /// This will trigger an event handler in the current `Scene`, unless an input consumer swallows the event.
+    pub fn process_gamepad_event(&self, e: events::GamepadEvent) {
+        self.process_input_event(events::InputEvent::Gamepad(e));
+    }
+
+    /// Updates the current `Scene`'s input state, runs its input consumers on the given event, then dispatches it to
+    /// the relevant event handler if it was not swallowed.
+    fn process_input_event(&self, mut e: events::InputEvent) {
+        let scene = self.scene.read().unwrap();
+        scene.input.write().unwrap().record(&mut e);
+        scene.input_event_log.write().unwrap().record(&e);
+        if let events::InputEvent::Keyboard(key) = &e {
+            scene.frame_profiler.write().unwrap().handle_key(key);
+        }
+        if scene.events.input_consumers.read().unwrap().consume(&e) {
+            return;
+        }
+        match e {
+            events::InputEvent::Keyboard(e) => scene.events.key.write().unwrap().handle(&e),
+            events::InputEvent::MouseDelta(e) => scene.events.mouse_delta.write().unwrap().handle(&e),
+            events::InputEvent::MouseButton(e) => scene.events.mouse_button.write().unwrap().handle(&e),
+            events::InputEvent::MouseScroll(e) => scene.events.mouse_scroll.write().unwrap().handle(&e),
+            events::InputEvent::CursorMoved(e) => scene.events.cursor_moved.write().unwrap().handle(&e),
+            events::InputEvent::Gamepad(e) => scene.events.gamepad.write().unwrap().handle(&e),
+        }
     }
 
     /// Should be called by the graphics backend whenever the window is resized.
     /// This will trigger an event handler in the current `Scene`.
     pub fn process_window_resize_event(&self, e: events::WindowResizeEvent) {
-        self.scene.read().unwrap().events.window_resize.write().unwrap().handle(e);
+        let scene = self.scene.read().unwrap();
+        scene.input.write().unwrap().resize_window(e.new_size);
+        let e = match self.graphics_pipeline.window_target_aspect() {
+            Some(aspect) => events::WindowResizeEvent {
+                new_size: graphics::letterbox(e.new_size, aspect).1
+            },
+            None => e,
+        };
+        scene.events.window_resize.write().unwrap().handle(&e);
+    }
+
+    /// Should be called by the graphics backend whenever a gamepad is connected or disconnected,
+    /// including once for each gamepad that is already connected when the application starts.
+    /// This will trigger an event handler in the current `Scene`.
+    ///
+    /// If a gamepad is disconnected while some of its buttons are held or its axes are away from rest, a `GamepadEvent`
+    /// releasing each button and returning each axis to zero is processed first, so listeners don't think that they
+    /// are still held.
+    pub fn process_gamepad_connection_event(&self, e: events::GamepadConnectionEvent) {
+        if !e.connected {
+            let releases = self.scene.read().unwrap().input.read().unwrap().gamepad_release_events(e.gamepad_id);
+            for release in releases {
+                self.process_gamepad_event(release);
+            }
+        }
+        {
+            let mut gamepads = self.gamepads.borrow_mut();
+            if e.connected {
+                if !gamepads.contains(&e.gamepad_id) {
+                    gamepads.push(e.gamepad_id);
+                }
+            } else {
+                gamepads.retain(|id| *id != e.gamepad_id);
+            }
+        }
+        self.scene.read().unwrap().events.gamepad_connection.write().unwrap().handle(&e);
+    }
+
+    /// Retrieves the list of gamepads that are currently connected, in the order they were connected.
+    pub fn gamepads(&self) -> Vec<input::GamepadId> {
+        self.gamepads.borrow().clone()
     }
 }
 
@@ -187,10 +503,239 @@ pub fn execute(scene: Arc<RwLock<scene::Scene>>, pipeline: graphics::Pipeline, g
     gback.main_loop(Context::new(pipeline, scene));
 }
 
+/// Tells `run_headless` when to stop stepping the scene.
+pub enum HeadlessDuration {
+    /// Run exactly this many steps, then return.
+    Steps(u64),
+    /// Keep running until the given flag is set to `true`. The flag is checked once before every step,
+    /// so it can be set from another thread, or from inside an event listener.
+    Until(Arc<AtomicBool>),
+}
+
+/// Executes the given scene without any graphics backend, for example on a dedicated server.
+/// Every step calls `begin_frame` and `end_frame` on a covalent context exactly like a graphics backend would,
+/// so scene events (e.g. `TickEvent`) and components behave identically to a windowed application.
+/// No rendering is performed, and no window or GPU is required.
+///
+/// `tick_rate` is the amount of steps to execute per second. If a step takes less time than `1 / tick_rate`,
+/// the calling thread sleeps for the remainder. If a step takes longer, the next step starts immediately;
+/// missed steps are not caught up on.
+///
+/// This function blocks the calling thread until `duration` has elapsed, then fires a `ShutdownEvent`.
+///
+/// # Panics
+/// If `tick_rate` is not strictly positive, this function will panic.
+pub fn run_headless(scene: Arc<RwLock<scene::Scene>>, tick_rate: f64, duration: HeadlessDuration) {
+    assert!(tick_rate > 0.0, "tick rate must be positive, was {}", tick_rate);
+
+    let ctx = Context::new(graphics::Pipeline::new(), scene);
+    let step_time = time::Duration::from_secs_f64(1.0 / tick_rate);
+    let mut next_step = time::Instant::now();
+    let mut steps = 0u64;
+
+    loop {
+        let finished = match &duration {
+            HeadlessDuration::Steps(n) => steps >= *n,
+            HeadlessDuration::Until(flag) => flag.load(Ordering::SeqCst),
+        };
+        if finished {
+            break;
+        }
+
+        ctx.begin_frame();
+        ctx.end_frame();
+        steps += 1;
+
+        next_step += step_time;
+        let now = time::Instant::now();
+        if next_step > now {
+            std::thread::sleep(next_step - now);
+        } else {
+            // We're running behind, so don't try to catch up on the steps we missed.
+            next_step = now;
+        }
+    }
+    ctx.process_shutdown();
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, RwLock};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::events::{Event, EventHandler, ListenOutcome, Listener};
+
+    /// Registers a listener that calls `f` with every event the given handler receives.
+    fn listen<E: Event>(handler: &RwLock<EventHandler<E>>, f: impl Fn(&E) + Send + Sync + 'static) {
+        let mut handler = handler.write().unwrap();
+        let id = handler.new_id();
+        handler.insert(Listener {
+            id,
+            priority: 0,
+            func: Box::new(move |e| {
+                f(e);
+                Ok(ListenOutcome::Keep)
+            })
+        });
+    }
+
+    /// Registers a listener that counts the events the given handler receives.
+    fn count_events<E: Event>(handler: &RwLock<EventHandler<E>>) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_copy = Arc::clone(&count);
+        listen(handler, move |_| {
+            count_copy.fetch_add(1, Ordering::SeqCst);
+        });
+        count
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn held_keys_repeat() {
+        use std::sync::Mutex;
+        let scene = crate::scene::Scene::new();
+        let repeats = Arc::new(Mutex::new(Vec::new()));
+        let repeats_copy = Arc::clone(&repeats);
+        listen(&scene.read().unwrap().events.key, move |e| repeats_copy.lock().unwrap().push(e.is_repeat));
+        let ctx = crate::Context::new(crate::graphics::Pipeline::new(), scene);
+        let key = |state| crate::events::KeyboardEvent {
+            scan_code: 17,
+            state,
+            virtual_keycode: Some(crate::input::VirtualKeyCode::W),
+            is_repeat: false,
+        };
+
+        // Holding the key, then releasing it and pressing it again.
+        ctx.process_keyboard_event(key(crate::input::ElementState::Pressed));
+        ctx.process_keyboard_event(key(crate::input::ElementState::Pressed));
+        ctx.process_keyboard_event(key(crate::input::ElementState::Pressed));
+        ctx.process_keyboard_event(key(crate::input::ElementState::Released));
+        ctx.process_keyboard_event(key(crate::input::ElementState::Pressed));
+        assert_eq!(vec![false, true, true, false, false], *repeats.lock().unwrap());
+    }
+
+    #[test]
+    fn disconnected_gamepads_are_released() {
+        use std::sync::Mutex;
+        use crate::events::{GamepadConnectionEvent, GamepadEvent};
+        use crate::input::{ElementState, GamepadAxis, GamepadButton};
+        let scene = crate::scene::Scene::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_copy = Arc::clone(&log);
+        listen(&scene.read().unwrap().events.gamepad, move |e| log_copy.lock().unwrap().push(format!("{:?}", e)));
+        let log_copy = Arc::clone(&log);
+        listen(&scene.read().unwrap().events.gamepad_connection, move |e| log_copy.lock().unwrap().push(format!("{:?}", e)));
+        let ctx = crate::Context::new(crate::graphics::Pipeline::new(), Arc::clone(&scene));
+
+        ctx.process_gamepad_connection_event(GamepadConnectionEvent { gamepad_id: 0, connected: true });
+        ctx.process_gamepad_event(GamepadEvent::Button { gamepad_id: 0, button: GamepadButton::South, state: ElementState::Pressed });
+        ctx.process_gamepad_event(GamepadEvent::Axis { gamepad_id: 0, axis: GamepadAxis::LeftStickX, value: 0.5 });
+        // Another gamepad's held buttons are unaffected.
+        ctx.process_gamepad_event(GamepadEvent::Button { gamepad_id: 1, button: GamepadButton::South, state: ElementState::Pressed });
+        log.lock().unwrap().clear();
+
+        ctx.process_gamepad_connection_event(GamepadConnectionEvent { gamepad_id: 0, connected: false });
+        let scene = scene.read().unwrap();
+        let input = scene.input();
+        assert!(!input.is_gamepad_button_pressed(0, GamepadButton::South));
+        assert_eq!(0.0, input.gamepad_axis(0, GamepadAxis::LeftStickX));
+        assert!(input.is_gamepad_button_pressed(1, GamepadButton::South));
+        assert_eq!(vec![
+            format!("{:?}", GamepadEvent::Button { gamepad_id: 0, button: GamepadButton::South, state: ElementState::Released }),
+            format!("{:?}", GamepadEvent::Axis { gamepad_id: 0, axis: GamepadAxis::LeftStickX, value: 0.0 }),
+            format!("{:?}", GamepadConnectionEvent { gamepad_id: 0, connected: false }),
+        ], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn paused_scenes_do_not_tick() {
+        let scene = crate::scene::Scene::new();
+        let ticks = count_events(&scene.read().unwrap().events.tick);
+        let ctx = crate::Context::new(crate::graphics::Pipeline::new(), scene);
+
+        ctx.set_paused(true);
+        ctx.begin_frame();
+        ctx.begin_frame();
+        assert_eq!(0, ticks.load(Ordering::SeqCst));
+        ctx.set_paused(false);
+        ctx.begin_frame();
+        assert_eq!(1, ticks.load(Ordering::SeqCst));
+        // Paused frames don't count towards the frame number of tick events.
+        assert_eq!(1, ctx.frame.get());
+    }
+
+    #[test]
+    fn stopwatch_warm_up() {
+        use std::time::Duration;
+        let mut stopwatch = crate::InterpolatedStopwatch::new(512);
+        assert_eq!(Duration::from_secs(0), stopwatch.average_time());
+        for _ in 0..3 {
+            std::thread::sleep(Duration::from_millis(20));
+            stopwatch.tick();
+        }
+        // Only the three real intervals count towards the average, not the 509 empty slots.
+        let average = stopwatch.average_time();
+        assert!(average >= Duration::from_millis(20), "average was {:?}", average);
+        assert!(average < Duration::from_millis(200), "average was {:?}", average);
+    }
+
+    #[test]
+    fn run_headless_fires_one_tick_per_step() {
+        let scene = crate::scene::Scene::new();
+        let ticks = count_events(&scene.read().unwrap().events.tick);
+        let shutdowns = count_events(&scene.read().unwrap().events.shutdown);
+
+        crate::run_headless(scene, 1000.0, crate::HeadlessDuration::Steps(3));
+        assert_eq!(3, ticks.load(Ordering::SeqCst));
+        assert_eq!(1, shutdowns.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn staggered_ticks_are_round_robin() {
+        let scene = crate::scene::Scene::new();
+        let counts = (0..8).map(|_| count_events(&scene.read().unwrap().events.staggered_tick)).collect::<Vec<_>>();
+
+        // With the default four groups, two of the eight listeners update each frame.
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(3));
+        assert_eq!(6, counts.iter().map(|c| c.load(Ordering::SeqCst)).sum::<usize>());
+        crate::run_headless(scene, 1000.0, crate::HeadlessDuration::Steps(1));
+        assert!(counts.iter().all(|c| c.load(Ordering::SeqCst) == 1));
+    }
+
+    #[test]
+    fn transform_changes_are_coalesced() {
+        use std::sync::Mutex;
+        let scene = crate::scene::Scene::new();
+        let moved = Arc::new(Mutex::new(Vec::new()));
+        let moved_copy = Arc::clone(&moved);
+        listen(&scene.read().unwrap().events.node_transform_changed, move |e| moved_copy.lock().unwrap().push(e.node_id));
+        let parent = scene.write().unwrap().new_node();
+        let child = scene.write().unwrap().new_node();
+        let still = scene.write().unwrap().new_node();
+        parent.write().unwrap().add_child(Arc::clone(&child));
+        for i in 0..3 {
+            parent.write().unwrap().set_pos(crate::vec3(i as f32, 0.0, 0.0));
+        }
+
+        // The parent moved three times and the child moved with it, but each is only reported once.
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(2));
+        let mut moved = moved.lock().unwrap().clone();
+        moved.sort();
+        assert_eq!(vec![parent.read().unwrap().id(), child.read().unwrap().id()], moved);
+        assert!(!moved.contains(&still.read().unwrap().id()));
+    }
+
+    #[test]
+    fn staggered_listeners_can_change_the_groups() {
+        let scene = crate::scene::Scene::new();
+        let stagger = Arc::clone(&scene.read().unwrap().events.tick_stagger);
+        listen(&scene.read().unwrap().events.staggered_tick, move |_| stagger.write().unwrap().set_groups(2));
+
+        // This would deadlock if the stagger were locked while its listeners run.
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(1));
+        assert_eq!(2, scene.read().unwrap().events.tick_stagger.read().unwrap().groups());
+    }
 }
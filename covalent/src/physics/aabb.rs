@@ -0,0 +1,118 @@
+use cgmath::{EuclideanSpace, Matrix4, Point3, Vector3};
+
+/// An axis-aligned bounding box, the smallest box with edges parallel to the axes that contains some shape.
+/// Boxes include their boundary, so boxes that only touch still intersect.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    /// The corner of the box with the smallest coordinates.
+    pub min: Point3<f32>,
+    /// The corner of the box with the largest coordinates.
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    /// Creates a box with the given opposite corners, which need not be the minimum and maximum corners.
+    pub fn new(a: Point3<f32>, b: Point3<f32>) -> Aabb {
+        Aabb {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// Creates a box with the given centre, extending `half_extents` along each axis in both directions.
+    pub fn from_centre(centre: Point3<f32>, half_extents: Vector3<f32>) -> Aabb {
+        Aabb::new(centre - half_extents, centre + half_extents)
+    }
+
+    /// Checks whether this box and the given box overlap.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x
+            && self.min.y <= other.max.y && other.min.y <= self.max.y
+            && self.min.z <= other.max.z && other.min.z <= self.max.z
+    }
+
+    /// Checks whether the given point is inside this box.
+    pub fn contains_point(&self, p: Point3<f32>) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x
+            && self.min.y <= p.y && p.y <= self.max.y
+            && self.min.z <= p.z && p.z <= self.max.z
+    }
+
+    /// Finds where the ray starting at `origin` and travelling in direction `dir` first hits this box.
+    /// Returns the distance along the ray to the hit, in multiples of the length of `dir`, so if `dir` has unit
+    /// length this is the distance in world units. Rays that start inside the box hit it at a distance of zero.
+    /// Returns `None` if the ray misses the box.
+    pub fn ray_intersect(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<f32> {
+        self.ray_span(origin, dir).map(|(near, _)| near)
+    }
+
+    /// Finds the range of distances along the ray, in the same units as `ray_intersect`, for which the ray is inside
+    /// this box. Returns `None` if the ray misses the box.
+    pub(crate) fn ray_span(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<(f32, f32)> {
+        // The ray is inside the box when it is between the two planes (a "slab") bounding the box along every axis.
+        // Find the range of distances for which it is inside each slab, then intersect the ranges.
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for axis in 0..3 {
+            let (o, d, min, max) = (origin[axis], dir[axis], self.min[axis], self.max[axis]);
+            if d == 0.0 {
+                // The ray is parallel to this slab, so it is either always or never inside it.
+                if o < min || o > max {
+                    return None;
+                }
+            } else {
+                let (t0, t1) = ((min - o) / d, (max - o) / d);
+                near = near.max(t0.min(t1));
+                far = far.min(t0.max(t1));
+                if near > far {
+                    return None;
+                }
+            }
+        }
+        Some((near, far))
+    }
+
+    /// Computes the bounding box of this box after it has been transformed by the given affine transformation,
+    /// for example to convert a box in a node's local space into world space. If the transformation rotates the
+    /// box, the result is larger than the box itself.
+    pub fn transformed(&self, xform: &Matrix4<f32>) -> Aabb {
+        let bounds = crate::graphics::Aabb { min: self.min.to_vec(), max: self.max.to_vec() }.transformed(xform);
+        Aabb::from(bounds)
+    }
+}
+
+impl From<crate::graphics::Aabb> for Aabb {
+    fn from(aabb: crate::graphics::Aabb) -> Self {
+        Aabb {
+            min: Point3::from_vec(aabb.min),
+            max: Point3::from_vec(aabb.max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pt3, vec3};
+
+    #[test]
+    fn overlapping_boxes_intersect() {
+        let unit = Aabb::new(pt3(0.0, 0.0, 0.0), pt3(1.0, 1.0, 1.0));
+        assert!(unit.intersects(&Aabb::new(pt3(0.5, 0.5, 0.5), pt3(2.0, 2.0, 2.0))));
+        assert!(unit.intersects(&Aabb::new(pt3(1.0, 0.0, 0.0), pt3(2.0, 1.0, 1.0))));
+        assert!(!unit.intersects(&Aabb::new(pt3(0.0, 1.5, 0.0), pt3(1.0, 2.0, 1.0))));
+        assert!(unit.contains_point(pt3(0.5, 1.0, 0.0)));
+        assert!(!unit.contains_point(pt3(0.5, 1.1, 0.0)));
+    }
+
+    #[test]
+    fn rays_hit_the_nearest_face() {
+        let unit = Aabb::from_centre(pt3(5.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0));
+        assert_eq!(Some(4.0), unit.ray_intersect(pt3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0)));
+        assert_eq!(Some(2.0), unit.ray_intersect(pt3(0.0, 0.5, 0.0), vec3(2.0, 0.0, 0.0)));
+        assert_eq!(Some(0.0), unit.ray_intersect(pt3(5.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)));
+        assert_eq!(None, unit.ray_intersect(pt3(0.0, 0.0, 0.0), vec3(-1.0, 0.0, 0.0)));
+        assert_eq!(None, unit.ray_intersect(pt3(0.0, 2.0, 0.0), vec3(1.0, 0.0, 0.0)));
+    }
+}
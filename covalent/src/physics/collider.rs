@@ -0,0 +1,242 @@
+use crate::physics::{Aabb, SpatialGrid};
+use crate::scene::{Component, Node, NodeId, Scene};
+use cgmath::{Point3, Vector3};
+use std::sync::{Arc, RwLock, Weak};
+
+/// Gives its node a solid box that can be found with `Scene::query_aabb`, e.g. so that bullets can tell which enemies
+/// they hit.
+pub struct ColliderComponent {
+    node: Weak<RwLock<Node>>,
+    /// The box, in the local space of the node, so it moves, rotates and scales along with the node.
+    aabb: Aabb,
+}
+impl Component for ColliderComponent {
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        vec![("aabb".to_string(), format!("{:?}", self.aabb))]
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self.aabb).ok()
+    }
+}
+
+impl ColliderComponent {
+    /// Attaches a collider with the given box, in the node's local space, to the given node, returning it so that its
+    /// box can be changed later.
+    pub fn new(node: Arc<RwLock<Node>>, aabb: Aabb) -> Arc<RwLock<ColliderComponent>> {
+        let component = Arc::new(RwLock::new(ColliderComponent {
+            node: Arc::downgrade(&node),
+            aabb,
+        }));
+
+        if let Some(scene) = node.read().unwrap().scene().upgrade() {
+            let scene = scene.read().unwrap();
+            let mut colliders = scene.colliders.write().unwrap();
+            colliders.push(Arc::downgrade(&component));
+            let mut spatial_index = scene.spatial_index.write().unwrap();
+            if let Some(grid) = &mut *spatial_index {
+                let node = node.read().unwrap();
+                grid.insert(node.id(), &component, &aabb.transformed(&node.get_xform()));
+            }
+        }
+
+        node.write().unwrap().components.push(Arc::clone(&component) as Arc<RwLock<dyn Component>>);
+        component
+    }
+
+    /// Sets the box of this collider, in the local space of its node.
+    pub fn set_aabb(&mut self, aabb: Aabb) {
+        self.aabb = aabb;
+    }
+
+    /// Retrieves the box of this collider, in the local space of its node.
+    pub fn get_aabb(&self) -> Aabb {
+        self.aabb
+    }
+
+    /// Computes the box of this collider in world space, along with the ID of its node.
+    /// Returns `None` if the node has been deleted.
+    pub(crate) fn world_aabb(&self) -> Option<(NodeId, Aabb)> {
+        let node = self.node.upgrade()?;
+        let node = node.read().unwrap();
+        Some((node.id(), self.aabb.transformed(&node.get_xform())))
+    }
+}
+
+impl Scene {
+    /// Finds every node with a `ColliderComponent` whose box overlaps the given box in world space, in order of
+    /// their IDs.
+    ///
+    /// Without a spatial index, this checks every collider in the scene in turn, so it is only suitable for small
+    /// numbers of colliders; see `enable_spatial_index`.
+    /// The node of each collider is locked for reading, so this must not be called while any node with a collider is
+    /// locked for writing, e.g. by a `lock_data!` listener with `node: write Node`, or it will deadlock.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<NodeId> {
+        let mut hits = self.collider_candidates(|grid| grid.candidates(aabb)).into_iter()
+            .filter_map(|collider| collider.read().unwrap().world_aabb())
+            .filter(|(_, collider_aabb)| collider_aabb.intersects(aabb))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        hits.sort();
+        hits
+    }
+
+    /// Finds every node with a `ColliderComponent` whose box is hit by the ray starting at `origin` and travelling
+    /// in direction `dir`, no further than `max_distance` (in multiples of the length of `dir`). Returns the ID of each
+    /// node along with the distance to its box, nearest first. See `Aabb::ray_intersect`.
+    ///
+    /// The same caveats as `query_aabb` apply.
+    pub fn query_ray(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32) -> Vec<(NodeId, f32)> {
+        let mut hits = self.collider_candidates(|grid| grid.ray_candidates(origin, dir, max_distance)).into_iter()
+            .filter_map(|collider| collider.read().unwrap().world_aabb())
+            .filter_map(|(id, collider_aabb)| collider_aabb.ray_intersect(origin, dir).map(|distance| (id, distance)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        hits.sort_by(|(a_id, a), (b_id, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal).then(a_id.cmp(b_id)));
+        hits
+    }
+
+    /// Starts keeping track of which colliders are in each cube of a uniform grid, with cubes of the given width, so
+    /// that `query_aabb` and `query_ray` only need to check the colliders near the region they are interested in.
+    /// This is worthwhile for scenes with hundreds of colliders or more. Cells should be a little larger than typical
+    /// colliders.
+    ///
+    /// The index listens for `NodeTransformChangedEvent`s, so colliders are moved to their new cells at the start of
+    /// the frame after their nodes move. Until then, queries may miss colliders that have moved far enough to enter
+    /// new cells; call `rebuild_spatial_index` to update every collider straight away.
+    ///
+    /// # Panics
+    /// If `cell_size` is not positive.
+    pub fn enable_spatial_index(&self, cell_size: f32) {
+        *self.spatial_index.write().unwrap() = Some(SpatialGrid::new(cell_size));
+        self.rebuild_spatial_index();
+    }
+
+    /// Stops keeping track of colliders in a spatial index, so queries check every collider again.
+    pub fn disable_spatial_index(&self) {
+        *self.spatial_index.write().unwrap() = None;
+    }
+
+    /// Checks whether colliders are being tracked in a spatial index; see `enable_spatial_index`.
+    pub fn has_spatial_index(&self) -> bool {
+        self.spatial_index.read().unwrap().is_some()
+    }
+
+    /// Updates the spatial index with the current positions of every collider. Does nothing if there is no index.
+    /// The same caveats as `query_aabb` apply.
+    pub fn rebuild_spatial_index(&self) {
+        let mut colliders = self.colliders.write().unwrap();
+        colliders.retain(|collider| collider.strong_count() > 0);
+        let mut spatial_index = self.spatial_index.write().unwrap();
+        if let Some(grid) = &mut *spatial_index {
+            *grid = SpatialGrid::new(grid.cell_size());
+            for collider in colliders.iter().filter_map(Weak::upgrade) {
+                let world_aabb = collider.read().unwrap().world_aabb();
+                if let Some((node, aabb)) = world_aabb {
+                    grid.insert(node, &collider, &aabb);
+                }
+            }
+        }
+    }
+
+    /// Finds the colliders that a query might hit, asking the spatial index if there is one, or returning every
+    /// collider if not.
+    fn collider_candidates(&self, query: impl FnOnce(&SpatialGrid) -> Vec<Arc<RwLock<ColliderComponent>>>) -> Vec<Arc<RwLock<ColliderComponent>>> {
+        let mut colliders = self.colliders.write().unwrap();
+        // Forget colliders whose nodes have been deleted.
+        colliders.retain(|collider| collider.strong_count() > 0);
+        match &*self.spatial_index.read().unwrap() {
+            Some(grid) => query(grid),
+            None => colliders.iter().filter_map(Weak::upgrade).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pt3, vec3};
+
+    #[test]
+    fn query_finds_overlapping_colliders() {
+        let scene = Scene::new();
+        let unit = Aabb::from_centre(pt3(0.0, 0.0, 0.0), vec3(0.5, 0.5, 0.5));
+        let near = scene.write().unwrap().new_node();
+        let far = scene.write().unwrap().new_node();
+        ColliderComponent::new(Arc::clone(&near), unit);
+        ColliderComponent::new(Arc::clone(&far), unit);
+        far.write().unwrap().set_pos(vec3(10.0, 0.0, 0.0));
+
+        let scene = scene.read().unwrap();
+        let near_id = near.read().unwrap().id();
+        let far_id = far.read().unwrap().id();
+        assert_eq!(vec![near_id], scene.query_aabb(&Aabb::new(pt3(0.4, 0.0, 0.0), pt3(1.0, 1.0, 1.0))));
+        assert_eq!(vec![far_id], scene.query_aabb(&Aabb::from_centre(pt3(9.0, 0.0, 0.0), vec3(0.6, 0.1, 0.1))));
+        // Colliders move with their nodes.
+        near.write().unwrap().set_pos(vec3(5.0, 0.0, 0.0));
+        assert!(scene.query_aabb(&unit).is_empty());
+    }
+
+    #[test]
+    fn spatial_index_matches_linear_queries() {
+        let scene = Scene::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                let node = scene.write().unwrap().new_node();
+                node.write().unwrap().set_pos(vec3(i as f32, j as f32, 0.0));
+                ColliderComponent::new(node, Aabb::from_centre(pt3(0.0, 0.0, 0.0), vec3(0.4, 0.4, 0.4)));
+            }
+        }
+
+        let scene = scene.read().unwrap();
+        let region = Aabb::new(pt3(2.5, 3.5, -1.0), pt3(6.0, 4.2, 1.0));
+        let linear_aabb = scene.query_aabb(&region);
+        let linear_ray = scene.query_ray(pt3(-1.0, 7.0, 0.0), vec3(1.0, 0.0, 0.0), 10.0);
+        scene.enable_spatial_index(2.0);
+        assert_eq!(4, linear_aabb.len());
+        assert_eq!(linear_aabb, scene.query_aabb(&region));
+        assert_eq!(10, linear_ray.len());
+        assert_eq!(linear_ray, scene.query_ray(pt3(-1.0, 7.0, 0.0), vec3(1.0, 0.0, 0.0), 10.0));
+    }
+
+    #[test]
+    fn spatial_index_walks_only_cells_along_rays() {
+        let scene = Scene::new();
+        for i in 0..20 {
+            let node = scene.write().unwrap().new_node();
+            node.write().unwrap().set_pos(vec3(i as f32, i as f32, i as f32));
+            ColliderComponent::new(node, Aabb::from_centre(pt3(0.0, 0.0, 0.0), vec3(0.4, 0.4, 0.4)));
+        }
+
+        let scene = scene.read().unwrap();
+        let (origin, dir) = (pt3(-3.0, -3.0, -3.0), vec3(1.0, 1.0, 1.0));
+        let linear = scene.query_ray(origin, dir, f32::INFINITY);
+        scene.enable_spatial_index(1.0);
+        let start = std::time::Instant::now();
+        assert_eq!(linear, scene.query_ray(origin, dir, f32::INFINITY));
+        assert_eq!(linear, scene.query_ray(origin, dir, f32::MAX));
+        assert!(scene.query_ray(origin, -dir, f32::INFINITY).is_empty());
+        assert!(start.elapsed() < std::time::Duration::from_secs(1), "rays without a limit should end");
+        assert_eq!(20, linear.len());
+        assert_eq!(3, scene.query_ray(origin, dir, 5.5).len());
+    }
+
+    #[test]
+    fn spatial_index_follows_moving_nodes() {
+        let scene = Scene::new();
+        scene.read().unwrap().enable_spatial_index(2.0);
+        let parent = scene.write().unwrap().new_node();
+        let child = scene.write().unwrap().new_node();
+        parent.write().unwrap().add_child(Arc::clone(&child));
+        ColliderComponent::new(Arc::clone(&child), Aabb::from_centre(pt3(0.0, 0.0, 0.0), vec3(0.5, 0.5, 0.5)));
+        let child_id = child.read().unwrap().id();
+
+        // Moving the parent moves the child's collider into new cells at the start of the next frame.
+        parent.write().unwrap().set_pos(vec3(20.0, 0.0, 0.0));
+        let target = Aabb::from_centre(pt3(20.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0));
+        assert!(scene.read().unwrap().query_aabb(&target).is_empty());
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(1));
+        assert_eq!(vec![child_id], scene.read().unwrap().query_aabb(&target));
+    }
+}
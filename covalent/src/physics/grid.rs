@@ -0,0 +1,171 @@
+use crate::events::{EventHandler, ListenError, ListenOutcome, Listener, NodeTransformChangedEvent};
+use crate::physics::{Aabb, ColliderComponent};
+use crate::scene::NodeId;
+use cgmath::{Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock, Weak};
+
+/// The coordinates of a cell of a `SpatialGrid`, in units of its cell size.
+type Cell = (i32, i32, i32);
+/// A collider in a `SpatialGrid`, held weakly so that the grid doesn't keep it alive.
+type WeakCollider = Weak<RwLock<ColliderComponent>>;
+
+/// Divides space into cubes of equal size, remembering which colliders overlap each cube, so that queries only need
+/// to check the colliders near the region they are interested in. See `Scene::enable_spatial_index`.
+pub(crate) struct SpatialGrid {
+    /// The width of each cell along every axis.
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<WeakCollider>>,
+    /// The colliders of each node, along with the cells that each collider was added to, so that they can be moved
+    /// when their node moves.
+    placed: HashMap<NodeId, Vec<(WeakCollider, Vec<Cell>)>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        assert!(cell_size > 0.0, "the cells of a spatial index must have a positive size");
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            placed: HashMap::new(),
+        }
+    }
+
+    /// Listens for nodes moving, so that the colliders in the given spatial index, if there is one, move with them.
+    /// The listener is removed once the index is deleted along with its scene.
+    pub fn subscribe(index: &Arc<RwLock<Option<SpatialGrid>>>, handler: &RwLock<EventHandler<NodeTransformChangedEvent>>) {
+        let index = Arc::downgrade(index);
+        let mut handler = handler.write().unwrap();
+        let id = handler.new_id();
+        handler.insert(Listener {
+            id,
+            priority: 0,
+            func: Box::new(move |e| {
+                let index = index.upgrade().ok_or(ListenError::RequirementDeleted)?;
+                if let Some(grid) = &mut *index.write().unwrap() {
+                    grid.node_moved(e.node_id);
+                }
+                Ok(ListenOutcome::Keep)
+            }),
+        });
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Adds a collider belonging to the given node to every cell that the given box, in world space, overlaps.
+    pub fn insert(&mut self, node: NodeId, collider: &Arc<RwLock<ColliderComponent>>, aabb: &Aabb) {
+        let cells = self.cells_overlapping(aabb).collect::<Vec<_>>();
+        for cell in &cells {
+            self.cells.entry(*cell).or_default().push(Arc::downgrade(collider));
+        }
+        self.placed.entry(node).or_default().push((Arc::downgrade(collider), cells));
+    }
+
+    /// Moves the colliders of the given node into the cells that their boxes overlap now. Colliders whose nodes have
+    /// been deleted are forgotten. This locks the node for reading.
+    pub fn node_moved(&mut self, node: NodeId) {
+        for (collider, cells) in self.placed.remove(&node).unwrap_or_default() {
+            for cell in cells {
+                if let Some(colliders) = self.cells.get_mut(&cell) {
+                    colliders.retain(|c| !c.ptr_eq(&collider));
+                }
+            }
+            if let Some(collider) = collider.upgrade() {
+                let world_aabb = collider.read().unwrap().world_aabb();
+                if let Some((node, aabb)) = world_aabb {
+                    self.insert(node, &collider, &aabb);
+                }
+            }
+        }
+    }
+
+    /// Finds every collider in a cell that the given box overlaps. Each collider is returned once, in no particular
+    /// order. Their boxes are not checked, so some may not overlap the given box.
+    pub fn candidates(&self, aabb: &Aabb) -> Vec<Arc<RwLock<ColliderComponent>>> {
+        let mut seen = HashSet::new();
+        self.cells_overlapping(aabb)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter_map(Weak::upgrade)
+            .filter(|collider| seen.insert(Arc::as_ptr(collider)))
+            .collect()
+    }
+
+    /// Finds every collider in a cell that the ray starting at `origin` and travelling in direction `dir` passes
+    /// through, no further than `max_distance` (in multiples of the length of `dir`). Each collider is returned once, in
+    /// no particular order. Their boxes are not checked, so the ray may miss some of them.
+    pub fn ray_candidates(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32) -> Vec<Arc<RwLock<ColliderComponent>>> {
+        let mut seen = HashSet::new();
+        self.cells_along_ray(origin, dir, max_distance).into_iter()
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter_map(Weak::upgrade)
+            .filter(|collider| seen.insert(Arc::as_ptr(collider)))
+            .collect()
+    }
+
+    /// Finds the coordinates of every cell that the given ray passes through, in order, by stepping from each cell
+    /// into whichever neighbour the ray enters next (Amanatides and Woo, "A Fast Voxel Traversal Algorithm for Ray
+    /// Tracing"). Only the part of the ray inside the cells that have held colliders is walked, so this also works for
+    /// rays of unlimited length.
+    fn cells_along_ray(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32) -> Vec<Cell> {
+        let mut keys = self.cells.iter().filter(|(_, colliders)| !colliders.is_empty()).map(|(cell, _)| *cell);
+        let first = match keys.next() {
+            Some(cell) => cell,
+            None => return Vec::new(),
+        };
+        let (lo, hi) = keys.fold((first, first), |(lo, hi), (x, y, z)| {
+            ((lo.0.min(x), lo.1.min(y), lo.2.min(z)), (hi.0.max(x), hi.1.max(y), hi.2.max(z)))
+        });
+        let corner = |(x, y, z): Cell| Point3::new(x as f32, y as f32, z as f32) * self.cell_size;
+        let occupied = Aabb::new(corner(lo), corner((hi.0 + 1, hi.1 + 1, hi.2 + 1)));
+        let (start, end) = match occupied.ray_span(origin, dir) {
+            Some((near, far)) if near <= max_distance => (near, far.min(max_distance)),
+            _ => return Vec::new(),
+        };
+
+        let (lo, hi) = ([lo.0, lo.1, lo.2], [hi.0, hi.1, hi.2]);
+        let entry = origin + dir * start;
+        let mut cell = [0; 3];
+        let mut step = [0; 3];
+        // The distance along the ray at which it crosses into the next cell along each axis.
+        let mut next_crossing = [f32::INFINITY; 3];
+        // The distance along the ray between crossings along each axis.
+        let mut crossing_interval = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            // Rounding may put the entry point just outside the occupied cells.
+            cell[axis] = ((entry[axis] / self.cell_size).floor() as i32).max(lo[axis]).min(hi[axis]);
+            if dir[axis] != 0.0 {
+                step[axis] = if dir[axis] > 0.0 { 1 } else { -1 };
+                let boundary = (cell[axis] + step[axis].max(0)) as f32 * self.cell_size;
+                next_crossing[axis] = (boundary - origin[axis]) / dir[axis];
+                crossing_interval[axis] = self.cell_size / dir[axis].abs();
+            }
+        }
+
+        let mut cells = Vec::new();
+        loop {
+            cells.push((cell[0], cell[1], cell[2]));
+            let axis = (0..3).fold(0, |a, b| if next_crossing[b] < next_crossing[a] { b } else { a });
+            if step[axis] == 0 || next_crossing[axis] > end {
+                break;
+            }
+            cell[axis] += step[axis];
+            if cell[axis] < lo[axis] || cell[axis] > hi[axis] {
+                break;
+            }
+            next_crossing[axis] += crossing_interval[axis];
+        }
+        cells
+    }
+
+    /// Iterates over the coordinates of every cell that the given box overlaps.
+    fn cells_overlapping(&self, aabb: &Aabb) -> impl Iterator<Item = Cell> {
+        let cell_of = |x: f32| (x / self.cell_size).floor() as i32;
+        let (x0, y0, z0) = (cell_of(aabb.min.x), cell_of(aabb.min.y), cell_of(aabb.min.z));
+        let (x1, y1, z1) = (cell_of(aabb.max.x), cell_of(aabb.max.y), cell_of(aabb.max.z));
+        (x0..=x1).flat_map(move |x| (y0..=y1).flat_map(move |y| (z0..=z1).map(move |z| (x, y, z))))
+    }
+}
@@ -0,0 +1,13 @@
+//! Simple collision detection, enough to tell when gameplay objects such as bullets and enemies touch.
+//!
+//! Attach a `ColliderComponent` to each node that can be hit, then find the nodes overlapping a region of the scene
+//! with `Scene::query_aabb` or `Scene::query_ray`. Large scenes should also call `Scene::enable_spatial_index`.
+
+mod aabb;
+pub use aabb::*;
+
+mod collider;
+pub use collider::*;
+
+mod grid;
+pub(crate) use grid::SpatialGrid;
@@ -0,0 +1,220 @@
+use crate::events::KeyboardEvent;
+use crate::graphics::{Colour, RenderVertex, Renderable};
+use crate::input::{ElementState, VirtualKeyCode};
+use cgmath::{vec2, vec3};
+use std::time::Duration;
+
+/// A frame profiler records the distribution of frame times in a histogram, so that occasional slow frames
+/// (micro-stutters) can be spotted even when the average framerate looks fine.
+///
+/// Frame times are sorted into `bin_count` bins, each `bin_width` wide. Frame times that are too long for the last
+/// bin are counted in an extra overflow bin.
+///
+/// The histogram can be drawn on screen as a bar chart; see `set_visible` and `set_toggle_key`.
+pub struct FrameProfiler {
+    bin_width: Duration,
+    /// The amount of frames whose time fell into each bin. The last entry is the overflow bin.
+    bins: Vec<u64>,
+    samples: u64,
+    max: Duration,
+    visible: bool,
+    toggle_key: Option<VirtualKeyCode>,
+}
+
+impl FrameProfiler {
+    /// Creates a frame profiler with `bin_count` bins, each `bin_width` wide.
+    ///
+    /// # Panics
+    /// If `bin_width` is zero or `bin_count` is zero, this function will panic.
+    pub fn new(bin_width: Duration, bin_count: usize) -> FrameProfiler {
+        assert!(bin_width > Duration::from_secs(0) && bin_count > 0, "frame profiler must have at least one non-empty bin");
+        FrameProfiler {
+            bin_width,
+            bins: vec![0; bin_count + 1],
+            samples: 0,
+            max: Duration::from_secs(0),
+            visible: false,
+            toggle_key: None,
+        }
+    }
+
+    /// Records the time taken by a single frame.
+    pub fn record(&mut self, frame_time: Duration) {
+        let overflow = self.bins.len() - 1;
+        let bin = ((frame_time.as_secs_f64() / self.bin_width.as_secs_f64()) as usize).min(overflow);
+        self.bins[bin] += 1;
+        self.samples += 1;
+        self.max = self.max.max(frame_time);
+    }
+
+    /// Forgets all recorded frame times.
+    pub fn reset(&mut self) {
+        for bin in self.bins.iter_mut() {
+            *bin = 0;
+        }
+        self.samples = 0;
+        self.max = Duration::from_secs(0);
+    }
+
+    /// Retrieves the amount of frames recorded in each bin. Bin `i` counts frames that took between
+    /// `i * bin_width` and `(i + 1) * bin_width`. The last entry counts frames that were too long for any other bin.
+    pub fn bins(&self) -> &[u64] {
+        &self.bins
+    }
+
+    /// Retrieves the width of each bin.
+    pub fn bin_width(&self) -> Duration {
+        self.bin_width
+    }
+
+    /// Retrieves the amount of frames recorded.
+    pub fn sample_count(&self) -> u64 {
+        self.samples
+    }
+
+    /// Retrieves the longest frame time recorded.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Estimates the frame time below which the given fraction of frames fall, e.g. `percentile(0.99)` for the
+    /// 99th percentile. This is accurate to within one bin width: the upper edge of the bin containing the
+    /// percentile is returned. If the percentile lies in the overflow bin, the longest recorded frame time is returned.
+    /// Returns zero if no frames have been recorded.
+    pub fn percentile(&self, fraction: f64) -> Duration {
+        let target = ((fraction.clamp(0.0, 1.0) * self.samples as f64).ceil() as u64).max(1);
+        let mut cumulative = 0;
+        for (i, count) in self.bins.iter().enumerate().take(self.bins.len() - 1) {
+            cumulative += count;
+            if cumulative >= target {
+                return self.bin_width * (i as u32 + 1);
+            }
+        }
+        self.max
+    }
+
+    /// Shows or hides the histogram on screen. While the profiler is visible, the graphics backend draws `graph`
+    /// over the rest of each frame. The profiler is hidden by default.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Returns true if the histogram is drawn on screen; see `set_visible`.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Sets the key that shows and hides the histogram while the game is running, or `None` so that no key does.
+    /// There is no toggle key by default. Pressing the key doesn't stop the key event from reaching listeners.
+    pub fn set_toggle_key(&mut self, key: Option<VirtualKeyCode>) {
+        self.toggle_key = key;
+    }
+
+    /// Retrieves the key that shows and hides the histogram; see `set_toggle_key`.
+    pub fn toggle_key(&self) -> Option<VirtualKeyCode> {
+        self.toggle_key
+    }
+
+    /// Shows or hides the histogram if the given event is a press of the toggle key. Repeats are ignored, so holding
+    /// the key down doesn't make the histogram flicker.
+    pub(crate) fn handle_key(&mut self, e: &KeyboardEvent) {
+        if e.state == ElementState::Pressed && !e.is_repeat && e.virtual_keycode.is_some() && e.virtual_keycode == self.toggle_key {
+            self.visible = !self.visible;
+        }
+    }
+
+    /// Builds a bar chart of the histogram out of quads, for the graphics backend to draw while the profiler is
+    /// visible. The chart fills the square from `(0, 0)` to `(1, 1)`, with one bar for each bin from left to right,
+    /// as tall as its bin is relative to the fullest bin. Empty bins have no bar. The bar for the overflow bin is red,
+    /// so that stalls stand out.
+    pub fn graph(&self) -> Vec<Renderable> {
+        let tallest = self.bins.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let bar_width = 1.0 / self.bins.len() as f32;
+        let overflow = self.bins.len() - 1;
+        self.bins.iter().enumerate().filter(|(_, count)| **count > 0).map(|(i, count)| {
+            let col = if i == overflow {
+                Colour::new(1.0, 0.2, 0.2)
+            } else {
+                Colour::new(0.2, 0.9, 0.3)
+            };
+            let vertex = |x: f32, y: f32| RenderVertex {
+                pos: vec3(x, y, 0.0),
+                col,
+                uv: vec2(0.0, 0.0),
+                normal: vec3(0.0, 0.0, 0.0),
+            };
+            let (left, right, top) = (i as f32 * bar_width, (i + 1) as f32 * bar_width, *count as f32 / tallest);
+            Renderable::Quad(vertex(left, 0.0), vertex(right, 0.0), vertex(right, top), vertex(left, top))
+        }).collect()
+    }
+}
+
+impl Default for FrameProfiler {
+    /// Creates a frame profiler with one-millisecond bins, up to a tenth of a second.
+    fn default() -> Self {
+        FrameProfiler::new(Duration::from_millis(1), 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn frame_profiler_percentiles() {
+        let mut profiler = FrameProfiler::new(Duration::from_millis(1), 50);
+        // 90 smooth frames at 16.5ms, then ten stutters at 40.5ms, then one huge stall.
+        for _ in 0..90 {
+            profiler.record(Duration::from_micros(16_500));
+        }
+        for _ in 0..10 {
+            profiler.record(Duration::from_micros(40_500));
+        }
+        profiler.record(Duration::from_secs(2));
+
+        assert_eq!(101, profiler.sample_count());
+        assert_eq!(90, profiler.bins()[16]);
+        assert_eq!(10, profiler.bins()[40]);
+        assert_eq!(1, profiler.bins()[50]);
+        assert_eq!(Duration::from_millis(17), profiler.percentile(0.5));
+        assert_eq!(Duration::from_millis(41), profiler.percentile(0.95));
+        assert_eq!(Duration::from_secs(2), profiler.percentile(1.0));
+    }
+
+    #[test]
+    fn frame_profiler_graph_is_toggled_by_key() {
+        let scene = crate::scene::Scene::new();
+        let profiler = Arc::clone(&scene.read().unwrap().frame_profiler);
+        profiler.write().unwrap().set_toggle_key(Some(VirtualKeyCode::F3));
+        profiler.write().unwrap().record(Duration::from_micros(16_500));
+        profiler.write().unwrap().record(Duration::from_secs(2));
+        let ctx = crate::Context::new(crate::graphics::Pipeline::new(), scene);
+        let key = |state| KeyboardEvent {
+            scan_code: 61,
+            state,
+            virtual_keycode: Some(VirtualKeyCode::F3),
+            is_repeat: false,
+        };
+
+        // Holding the key down only toggles the graph once.
+        ctx.process_keyboard_event(key(ElementState::Pressed));
+        ctx.process_keyboard_event(key(ElementState::Pressed));
+        assert!(profiler.read().unwrap().is_visible());
+        ctx.process_keyboard_event(key(ElementState::Released));
+        ctx.process_keyboard_event(key(ElementState::Pressed));
+        assert!(!profiler.read().unwrap().is_visible());
+
+        // There is one bar for each non-empty bin, and the overflow bin's bar is red.
+        let graph = profiler.read().unwrap().graph();
+        assert_eq!(2, graph.len());
+        match &graph[1] {
+            Renderable::Quad(v0, _, _, v3) => {
+                assert_eq!(1.0, v3.pos.y);
+                assert_eq!(1.0, v0.col.r());
+                assert!(v0.col.g() < 0.5);
+            },
+            _ => panic!("expected a quad"),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::scene::*;
+use std::sync::{RwLock, Arc};
+use cgmath::{Vector3, Quaternion};
+use crate::graphics::{Colour, Renderable};
+
+/// Used to populate a scene from within `Scene::build`.
+/// The builder does not hold any locks on the scene, so components may be freely attached to nodes.
+pub struct SceneBuilder {
+    pub(crate) scene: Arc<RwLock<Scene>>
+}
+
+impl SceneBuilder {
+    /// Retrieves the scene that is being built.
+    pub fn scene(&self) -> &Arc<RwLock<Scene>> {
+        &self.scene
+    }
+
+    /// Creates a new node in the scene, returning a builder that can be used to set up the node.
+    /// Call `NodeBuilder::finish` to retrieve the node itself.
+    pub fn node(&mut self) -> NodeBuilder {
+        NodeBuilder {
+            node: self.scene.write().unwrap().new_node()
+        }
+    }
+}
+
+/// A fluent interface for setting up a single node. Create one using `SceneBuilder::node`.
+///
+/// # Examples
+/// ```
+/// use covalent::scene::{Scene, TickDebugComponent};
+/// let scene = Scene::build(|builder| {
+///     builder.node()
+///         .pos(covalent::vec3(1.0, 2.0, 3.0))
+///         .component(TickDebugComponent::new)
+///         .finish();
+/// });
+/// ```
+pub struct NodeBuilder {
+    node: Arc<RwLock<Node>>
+}
+
+impl NodeBuilder {
+    /// Sets the position of the node.
+    pub fn pos(self, pos: Vector3<f32>) -> Self {
+        self.node.write().unwrap().set_pos(pos);
+        self
+    }
+
+    /// Sets the rotation of the node.
+    pub fn rot(self, rot: Quaternion<f32>) -> Self {
+        self.node.write().unwrap().set_rot(rot);
+        self
+    }
+
+    /// Sets the scale of the node.
+    pub fn scl(self, scl: Vector3<f32>) -> Self {
+        self.node.write().unwrap().set_scl(scl);
+        self
+    }
+
+    /// Sets the renderable that this node will render.
+    pub fn renderable(self, renderable: Renderable) -> Self {
+        self.node.write().unwrap().renderable = Some(Arc::new(renderable));
+        self
+    }
+
+    /// Sets whether an outline is drawn around this node's renderable.
+    pub fn outline(self, outline: bool) -> Self {
+        self.node.write().unwrap().outline = outline;
+        self
+    }
+
+    /// Sets the colour that this node's renderable is multiplied by when it is drawn.
+    pub fn tint(self, tint: Colour) -> Self {
+        self.node.write().unwrap().tint = tint;
+        self
+    }
+
+    /// Attaches a component to the node. The given function is called with the node, and should construct
+    /// the component, for example `TickDebugComponent::new`.
+    pub fn component(self, f: impl FnOnce(Arc<RwLock<Node>>)) -> Self {
+        f(Arc::clone(&self.node));
+        self
+    }
+
+    /// Finishes setting up the node, returning a handle to it.
+    pub fn finish(self) -> Arc<RwLock<Node>> {
+        self.node
+    }
+}
@@ -3,8 +3,23 @@
 mod node;
 pub use node::*;
 
-use std::sync::{RwLock, Arc, Weak};
-use crate::events::EventHandlers;
+mod builder;
+pub use builder::*;
+
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{RwLock, RwLockReadGuard, Arc, Weak, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::events::{Event, EventHandler, EventHandlers, InputEventLog, InputState};
+use crate::{Clock, DebugConsole, FrameProfiler};
+use crate::graphics::{Camera, FramebufferId, Text, WindowCommand};
+use crate::input::CursorIcon;
+use crate::WindowMode;
+use crate::physics::{ColliderComponent, SpatialGrid};
 
 /// The scene contains everything that the user can see or hear, and anything that interacts with that.
 /// Covalent will automatically render everything in this scene according to the active render pipeline.
@@ -13,30 +28,479 @@ use crate::events::EventHandlers;
 /// are internally mutable.
 pub struct Scene {
     self_ref: Weak<RwLock<Scene>>,
-    nodes: Vec<Arc<RwLock<Node>>>,
-    pub events: EventHandlers
+    /// The nodes in the scene. Node IDs are handed out in increasing order, so this iterates in the order that the
+    /// nodes were created.
+    nodes: BTreeMap<NodeId, Arc<RwLock<Node>>>,
+    /// The ID that will be given to the next node to be created.
+    next_node_id: u64,
+    pub events: EventHandlers,
+    /// Controls how time passes in this scene.
+    pub clock: Arc<RwLock<Clock>>,
+    /// An on-screen console that components can write debugging messages to.
+    pub debug_console: Arc<RwLock<DebugConsole>>,
+    /// Records the distribution of frame times while this scene is running.
+    pub frame_profiler: Arc<RwLock<FrameProfiler>>,
+    /// Optionally records recent input events for diagnostics. This is disabled by default.
+    pub input_event_log: Arc<RwLock<InputEventLog>>,
+    /// The current state of the keyboard and mouse, updated by the `Context` as input events arrive.
+    pub(crate) input: RwLock<InputState>,
+    /// Changes to the window that have been requested but not yet applied by the graphics backend.
+    window_commands: Mutex<Vec<WindowCommand>>,
+    /// True if `reload_shaders` has been called since the graphics backend last checked.
+    shader_reload_requested: AtomicBool,
+    /// Text to be drawn in the next frame.
+    text: Mutex<Vec<Text>>,
+    /// The framebuffers that this scene is rendered into every frame, and the camera for each; see
+    /// `render_to_framebuffer`.
+    framebuffer_cameras: Mutex<BTreeMap<FramebufferId, Arc<RwLock<dyn Camera + Send + Sync>>>>,
+    /// The colliders attached to nodes in this scene; see `query_aabb`.
+    pub(crate) colliders: RwLock<Vec<Weak<RwLock<ColliderComponent>>>>,
+    /// Speeds up collider queries in large scenes; see `enable_spatial_index`.
+    pub(crate) spatial_index: Arc<RwLock<Option<SpatialGrid>>>,
+    /// The nodes whose transformations have changed since the last frame; see `NodeTransformChangedEvent`.
+    transform_changes: Arc<Mutex<BTreeSet<NodeId>>>
 }
 
 impl Scene {
     pub fn new() -> Arc<RwLock<Scene>> {
         let scene = Arc::new(RwLock::new(Scene {
             self_ref: Weak::new(),
-            nodes: Vec::new(),
-            events: EventHandlers::default()
+            nodes: BTreeMap::new(),
+            next_node_id: 0,
+            events: EventHandlers::default(),
+            clock: Arc::new(RwLock::new(Clock::default())),
+            debug_console: Arc::new(RwLock::new(DebugConsole::default())),
+            frame_profiler: Arc::new(RwLock::new(FrameProfiler::default())),
+            input_event_log: Arc::new(RwLock::new(InputEventLog::default())),
+            input: RwLock::new(InputState::default()),
+            window_commands: Mutex::new(Vec::new()),
+            shader_reload_requested: AtomicBool::new(false),
+            text: Mutex::new(Vec::new()),
+            framebuffer_cameras: Mutex::new(BTreeMap::new()),
+            colliders: RwLock::new(Vec::new()),
+            spatial_index: Arc::new(RwLock::new(None)),
+            transform_changes: Arc::new(Mutex::new(BTreeSet::new()))
         }));
-        scene.write().unwrap().self_ref = Arc::downgrade(&scene);
+        {
+            let mut s = scene.write().unwrap();
+            s.self_ref = Arc::downgrade(&scene);
+            SpatialGrid::subscribe(&s.spatial_index, &s.events.node_transform_changed);
+        }
         scene
     }
 
+    /// Creates a new scene, then populates it using the given function.
+    /// This is a more concise alternative to calling `new_node` and locking each node in turn.
+    ///
+    /// The scene is not locked while `f` runs, so components may be attached to nodes as they are built.
+    pub fn build(f: impl FnOnce(&mut SceneBuilder)) -> Arc<RwLock<Scene>> {
+        let mut builder = SceneBuilder {
+            scene: Scene::new()
+        };
+        f(&mut builder);
+        builder.scene
+    }
+
     /// Creates a new node and adds it to the scene.
     pub fn new_node(&mut self) -> Arc<RwLock<Node>> {
-        let n = Node::default(Weak::upgrade(&self.self_ref).unwrap());
-        self.nodes.push(Arc::clone(&n));
+        let id = NodeId(self.next_node_id);
+        self.next_node_id += 1;
+        let n = Node::default(Weak::upgrade(&self.self_ref).unwrap(), id, Arc::clone(&self.transform_changes));
+        self.nodes.insert(id, Arc::clone(&n));
         n
     }
 
+    /// Creates the given number of nodes and adds them to the scene, returning them in the order they were created.
+    /// This is equivalent to calling `new_node` repeatedly, but the scene only needs to be locked once, which is much
+    /// faster when spawning thousands of nodes.
+    pub fn new_nodes(&mut self, count: usize) -> Vec<Arc<RwLock<Node>>> {
+        let scene = Weak::upgrade(&self.self_ref).unwrap();
+        (0..count).map(|_| {
+            let id = NodeId(self.next_node_id);
+            self.next_node_id += 1;
+            let n = Node::default(Arc::clone(&scene), id, Arc::clone(&self.transform_changes));
+            self.nodes.insert(id, Arc::clone(&n));
+            n
+        }).collect()
+    }
+
+    /// Creates a new node with the given ID and adds it to the scene, unless a node with that ID already exists.
+    /// Used when loading saved scenes, so that nodes keep their IDs.
+    #[cfg(feature = "serde")]
+    pub(crate) fn new_node_with_id(&mut self, id: NodeId) -> Option<Arc<RwLock<Node>>> {
+        if self.nodes.contains_key(&id) {
+            return None;
+        }
+        self.next_node_id = self.next_node_id.max(id.0 + 1);
+        let n = Node::default(Weak::upgrade(&self.self_ref).unwrap(), id, Arc::clone(&self.transform_changes));
+        self.nodes.insert(id, Arc::clone(&n));
+        Some(n)
+    }
+
+    /// Retrieves the node with the given ID, if it is still in the scene.
+    pub fn get_node(&self, id: NodeId) -> Option<Arc<RwLock<Node>>> {
+        self.nodes.get(&id).map(Arc::clone)
+    }
+
+    /// Removes the given node from the scene. Does nothing if the node is not in the scene.
+    ///
+    /// Once every other `Arc` to the node is dropped, the node and its components are deleted. Listeners belonging
+    /// to those components then fail to upgrade their `Weak` references, so they are removed from their event
+    /// handlers the next time their event fires.
+    pub fn remove_node(&mut self, node: &Arc<RwLock<Node>>) {
+        let id = node.read().unwrap().id();
+        if self.nodes.get(&id).is_some_and(|n| Arc::ptr_eq(n, node)) {
+            self.nodes.remove(&id);
+        }
+    }
+
+    /// Removes every node from the scene. See `remove_node`.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
     /// Iterates over all the 3D nodes in the scene.
     pub fn iter_3d(&self) -> impl Iterator<Item=&Arc<RwLock<Node>>> {
-        self.nodes.iter()
+        self.nodes.values()
+    }
+
+    /// Retrieves the number of nodes in the scene, without iterating over them.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Checks whether the scene contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Retrieves the event handler for a user-defined event type, creating it if needed.
+    /// See `EventHandlers::register`.
+    pub fn register_event<E: Event + 'static>(&self) -> Arc<RwLock<EventHandler<E>>> {
+        self.events.register::<E>()
+    }
+
+    /// Fires a user-defined event. See `EventHandlers::emit`.
+    pub fn emit<E: Event + 'static>(&self, e: E) {
+        self.events.emit(e);
+    }
+
+    /// Retrieves the current state of the keyboard and mouse, e.g. to check whether a key is held down.
+    pub fn input(&self) -> RwLockReadGuard<'_, InputState> {
+        self.input.read().unwrap()
+    }
+
+    /// Requests that the mouse cursor changes to the given icon while it is over the window.
+    /// The change is applied by the graphics backend at the start of the next frame.
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.push_window_command(WindowCommand::SetCursorIcon(icon));
+    }
+
+    /// Requests that the window switches to the given mode, e.g. so that players can toggle fullscreen.
+    /// The change is applied by the graphics backend at the start of the next frame.
+    pub fn set_window_mode(&self, mode: WindowMode) {
+        self.push_window_command(WindowCommand::SetWindowMode(mode));
+    }
+
+    /// Requests that the mouse cursor is confined to the window (or released, if `grab` is false).
+    /// The change is applied by the graphics backend at the start of the next frame.
+    pub fn set_cursor_grab(&self, grab: bool) {
+        self.push_window_command(WindowCommand::SetCursorGrab(grab));
+    }
+
+    /// Requests that the mouse cursor is shown or hidden while it is over the window.
+    /// The change is applied by the graphics backend at the start of the next frame.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.push_window_command(WindowCommand::SetCursorVisible(visible));
+    }
+
+    /// Queues up a change to the window, to be applied by the graphics backend.
+    pub fn push_window_command(&self, command: WindowCommand) {
+        self.window_commands.lock().unwrap().push(command);
+    }
+
+    /// Removes and returns all window commands that have been queued up, in the order they were pushed.
+    pub(crate) fn drain_window_commands(&self) -> Vec<WindowCommand> {
+        std::mem::take(&mut *self.window_commands.lock().unwrap())
+    }
+
+    /// Requests that the graphics backend recompiles every shader program that was loaded from files, e.g. after
+    /// editing them. The programs are recompiled at the start of the next frame.
+    pub fn reload_shaders(&self) {
+        self.shader_reload_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if `reload_shaders` has been called since the last call to this function.
+    pub(crate) fn take_shader_reload_request(&self) -> bool {
+        self.shader_reload_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Removes and returns the nodes whose transformations have changed since the last call, along with all of their
+    /// descendants, in order of ID. Nodes that have since been removed from the scene are skipped.
+    /// Each node is locked for reading, so no node may be locked for writing by the caller.
+    pub(crate) fn take_transform_changes(&self) -> Vec<NodeId> {
+        let changed = std::mem::take(&mut *self.transform_changes.lock().unwrap());
+        let mut with_descendants = BTreeSet::new();
+        let mut stack = changed.into_iter().filter_map(|id| self.get_node(id)).collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            let node = node.read().unwrap();
+            if with_descendants.insert(node.id()) {
+                stack.extend(node.children().iter().cloned());
+            }
+        }
+        with_descendants.into_iter().collect()
+    }
+
+    /// Queues up text to be drawn in screen space by the `Text` phases of the pipeline, in this frame only.
+    /// Text is drawn in the order it was queued, so later text is drawn over earlier text.
+    pub fn draw_text(&self, text: Text) {
+        self.text.lock().unwrap().push(text);
+    }
+
+    /// Removes and returns all text that has been queued up, in the order it was queued.
+    pub(crate) fn drain_text(&self) -> Vec<Text> {
+        std::mem::take(&mut *self.text.lock().unwrap())
+    }
+
+    /// Renders this scene from the point of view of the given camera into the given framebuffer every frame, before
+    /// the pipeline's phases, until `stop_rendering_to_framebuffer` is called. The framebuffer is cleared to
+    /// transparent black first, then drawn like a `Render` phase with default `RenderSettings`, so it should include
+    /// `RenderChannel::Depth`. Use `Backend::framebuffer_texture` to draw the result like any other texture, e.g. for
+    /// a minimap, a security-camera screen or a planar reflection.
+    ///
+    /// Each framebuffer is rendered from at most one camera, so calling this again for the same framebuffer replaces
+    /// its camera. This only records the request, so it can be called from any thread; the scene is rendered on the
+    /// render thread by the graphics backend, which services the requests each frame.
+    pub fn render_to_framebuffer(&self, framebuffer: FramebufferId, camera: Arc<RwLock<dyn Camera + Send + Sync>>) {
+        self.framebuffer_cameras.lock().unwrap().insert(framebuffer, camera);
+    }
+
+    /// Stops rendering this scene into the given framebuffer every frame; see `render_to_framebuffer`. The framebuffer
+    /// keeps whatever was last drawn into it.
+    pub fn stop_rendering_to_framebuffer(&self, framebuffer: FramebufferId) {
+        self.framebuffer_cameras.lock().unwrap().remove(&framebuffer);
     }
-}
\ No newline at end of file
+
+    /// Retrieves the framebuffers that this scene should be rendered into this frame, along with their cameras, in
+    /// order of framebuffer ID.
+    pub(crate) fn framebuffer_cameras(&self) -> Vec<(FramebufferId, Arc<RwLock<dyn Camera + Send + Sync>>)> {
+        self.framebuffer_cameras.lock().unwrap().iter().map(|(id, camera)| (*id, Arc::clone(camera))).collect()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::InnerSpace;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountTicksComponent {}
+    impl Component for CountTicksComponent {}
+
+    crate::lock_data! {
+        CountTicksData
+        component: read CountTicksComponent
+    }
+
+    impl CountTicksComponent {
+        fn new(node: Arc<RwLock<Node>>, ticks: Arc<AtomicUsize>) {
+            let component = Arc::new(RwLock::new(CountTicksComponent {}));
+            let data = Arc::new(RwLock::new(CountTicksData {
+                component: Arc::downgrade(&component),
+            }));
+            if let Some(scene) = node.read().unwrap().scene().upgrade() {
+                CountTicksData::listen(&data, &scene.read().unwrap().events.tick, move |_event, _component| {
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+            node.write().unwrap().components.push(component);
+        }
+    }
+
+    struct HeightComponent {
+        height: f32,
+    }
+    impl Component for HeightComponent {}
+
+    crate::lock_data! {
+        HeightData
+        node: read Node,
+        component: write HeightComponent
+    }
+
+    #[test]
+    fn listeners_can_read_their_node() {
+        let scene = Scene::new();
+        let node = scene.write().unwrap().new_node();
+        node.write().unwrap().set_pos(cgmath::vec3(0.0, 0.0, 2.5));
+        let component = Arc::new(RwLock::new(HeightComponent { height: 0.0 }));
+        let data = Arc::new(RwLock::new(HeightData {
+            node: Arc::downgrade(&node),
+            component: Arc::downgrade(&component),
+        }));
+        HeightData::listen(&data, &scene.read().unwrap().events.tick, |_event, node, component| {
+            component.height = node.get_xform().w.z;
+        });
+
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(1));
+        assert_eq!(2.5, component.read().unwrap().height);
+    }
+
+    #[test]
+    fn setters_recompute_xform() {
+        use cgmath::{vec3, vec4, Quaternion, Rad, Rotation3};
+        let scene = Scene::new();
+        let node = scene.write().unwrap().new_node();
+        let mut node = node.write().unwrap();
+        node.set_scl(vec3(2.0, 2.0, 2.0));
+        node.set_rot(Quaternion::from_angle_z(Rad(std::f32::consts::FRAC_PI_2)));
+        node.set_pos(vec3(1.0, 0.0, 0.0));
+
+        // (1, 0, 0) is scaled to (2, 0, 0), rotated to (0, 2, 0), then translated to (1, 2, 0).
+        let p = node.get_xform() * vec4(1.0, 0.0, 0.0, 1.0);
+        assert!((p - vec4(1.0, 2.0, 0.0, 1.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn children_follow_parents() {
+        use cgmath::{vec3, vec4};
+        let scene = Scene::new();
+        let tank = scene.write().unwrap().new_node();
+        let turret = scene.write().unwrap().new_node();
+        turret.write().unwrap().set_pos(vec3(0.0, 0.0, 1.0));
+        tank.write().unwrap().add_child(Arc::clone(&turret));
+        tank.write().unwrap().set_pos(vec3(5.0, 0.0, 0.0));
+
+        let p = turret.read().unwrap().get_xform() * vec4(0.0, 0.0, 0.0, 1.0);
+        assert!((p - vec4(5.0, 0.0, 1.0, 1.0)).magnitude() < 1e-5);
+        assert!(Arc::ptr_eq(&tank, &turret.read().unwrap().parent().unwrap()));
+
+        // Re-attaching the turret to the same parent doesn't duplicate it.
+        tank.write().unwrap().add_child(Arc::clone(&turret));
+        assert_eq!(1, tank.read().unwrap().children().len());
+
+        tank.write().unwrap().remove_child(&turret);
+        assert!(turret.read().unwrap().parent().is_none());
+        let p = turret.read().unwrap().get_xform() * vec4(0.0, 0.0, 0.0, 1.0);
+        assert!((p - vec4(0.0, 0.0, 1.0, 1.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn custom_events() {
+        struct ExplosionEvent {
+            strength: usize
+        }
+        impl Event for ExplosionEvent {}
+
+        let scene = Scene::new();
+        let component = Arc::new(RwLock::new(CountTicksComponent {}));
+        let data = Arc::new(RwLock::new(CountTicksData {
+            component: Arc::downgrade(&component),
+        }));
+        let total = Arc::new(AtomicUsize::new(0));
+        let total2 = Arc::clone(&total);
+        CountTicksData::listen(&data, &scene.read().unwrap().register_event::<ExplosionEvent>(), move |event, _component| {
+            total2.fetch_add(event.strength, Ordering::SeqCst);
+        });
+
+        scene.read().unwrap().emit(ExplosionEvent { strength: 3 });
+        scene.read().unwrap().emit(ExplosionEvent { strength: 4 });
+        assert_eq!(7, total.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn transform_component_moves_node() {
+        use cgmath::{vec3, Quaternion, Rad, Rotation3};
+        use crate::events::TickEvent;
+        let scene = Scene::new();
+        let node = scene.write().unwrap().new_node();
+        let component = TransformComponent::new(Arc::clone(&node), vec3(2.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0));
+        let tick = || scene.read().unwrap().events.tick.write().unwrap().handle(&TickEvent { delta: 0.5, frame: 0, elapsed: 0.5 });
+
+        tick();
+        assert!((node.read().unwrap().get_pos() - vec3(1.0, 0.0, 0.0)).magnitude() < 1e-5);
+
+        component.write().unwrap().set_velocity(vec3(0.0, 0.0, 0.0));
+        component.write().unwrap().set_angular_velocity(vec3(0.0, 0.0, std::f32::consts::PI));
+        tick();
+        let expected = Quaternion::from_angle_z(Rad(std::f32::consts::FRAC_PI_2));
+        assert!((node.read().unwrap().get_rot() - expected).magnitude() < 1e-5);
+        assert!((node.read().unwrap().get_pos() - vec3(1.0, 0.0, 0.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn rotate_component_spins_node() {
+        use cgmath::{vec3, Quaternion, Rad, Rotation3};
+        use crate::events::TickEvent;
+        let scene = Scene::new();
+        let node = scene.write().unwrap().new_node();
+        let component = RotateComponent::new(Arc::clone(&node), vec3(0.0, 0.0, 2.0), std::f32::consts::PI);
+        assert!((component.read().unwrap().get_axis() - vec3(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+        let tick = || scene.read().unwrap().events.tick.write().unwrap().handle(&TickEvent { delta: 0.5, frame: 0, elapsed: 0.5 });
+
+        tick();
+        let expected = Quaternion::from_angle_z(Rad(std::f32::consts::FRAC_PI_2));
+        assert!((node.read().unwrap().get_rot() - expected).magnitude() < 1e-5);
+
+        component.write().unwrap().set_rate(0.0);
+        tick();
+        assert!((node.read().unwrap().get_rot() - expected).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn get_node_by_id() {
+        let scene = Scene::new();
+        let a = scene.write().unwrap().new_node();
+        let b = scene.write().unwrap().new_node();
+        let a_id = a.read().unwrap().id();
+        let b_id = b.read().unwrap().id();
+        assert_ne!(a_id, b_id);
+        assert!(Arc::ptr_eq(&b, &scene.read().unwrap().get_node(b_id).unwrap()));
+
+        scene.write().unwrap().remove_node(&a);
+        assert!(scene.read().unwrap().get_node(a_id).is_none());
+        assert!(scene.read().unwrap().get_node(b_id).is_some());
+    }
+
+    #[test]
+    fn removed_nodes_stop_listening() {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let scene = Scene::new();
+        let nodes = scene.write().unwrap().new_nodes(100);
+        for node in &nodes {
+            CountTicksComponent::new(Arc::clone(node), Arc::clone(&ticks));
+        }
+
+        for node in nodes.iter().step_by(2) {
+            scene.write().unwrap().remove_node(node);
+        }
+        drop(nodes);
+        assert_eq!(50, scene.read().unwrap().node_count());
+
+        crate::run_headless(Arc::clone(&scene), 1000.0, crate::HeadlessDuration::Steps(1));
+        assert_eq!(50, ticks.swap(0, Ordering::SeqCst));
+
+        scene.write().unwrap().clear();
+        assert!(scene.read().unwrap().is_empty());
+        crate::run_headless(scene, 1000.0, crate::HeadlessDuration::Steps(1));
+        assert_eq!(0, ticks.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn each_framebuffer_has_one_camera() {
+        let scene = Scene::new();
+        let scene = scene.read().unwrap();
+        let camera = || -> Arc<RwLock<dyn Camera + Send + Sync>> {
+            Arc::new(RwLock::new(crate::graphics::PerspectiveCamera::new(crate::pt3(0.0, 0.0, 5.0), crate::vec3(0.0, 0.0, -1.0), crate::vec3(0.0, 1.0, 0.0))))
+        };
+        let (first, second) = (camera(), camera());
+        scene.render_to_framebuffer(3, Arc::clone(&first));
+        scene.render_to_framebuffer(1, camera());
+        scene.render_to_framebuffer(3, Arc::clone(&second));
+        let cameras = scene.framebuffer_cameras();
+        assert_eq!(vec![1, 3], cameras.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+        assert!(Arc::ptr_eq(&second, &cameras[1].1));
+
+        scene.stop_rendering_to_framebuffer(1);
+        assert_eq!(vec![3], scene.framebuffer_cameras().iter().map(|(id, _)| *id).collect::<Vec<_>>());
+    }
+}
@@ -1,9 +1,17 @@
 use crate::scene::*;
-use std::sync::{RwLock, Arc, Weak};
-use cgmath::{vec3, Vector3, Quaternion, Matrix4, Transform};
-use crate::graphics::Renderable;
+use std::collections::BTreeSet;
+use std::sync::{RwLock, Arc, Weak, Mutex};
+use cgmath::{vec2, vec3, Vector2, Vector3, Quaternion, Matrix4, Transform, InnerSpace, Rotation3};
+use crate::graphics::{Colour, Renderable};
 use crate::input::ElementState;
 
+/// A stable identifier for a node, unique within the scene that created it.
+/// Unlike an `Arc` to the node, holding a `NodeId` does not keep the node alive, so it is well suited to save files
+/// and to keys of maps in gameplay code. Retrieve the node itself with `Scene::get_node`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub u64);
+
 /// The node is the root of anything that is in the scene.
 /// Nodes have a list of `Behaviour`s, which represent the functionality of the node.
 pub struct Node {
@@ -11,6 +19,15 @@ pub struct Node {
     self_ref: Weak<RwLock<Self>>,
     /// A reference to the scene that contains this node.
     scene: Weak<RwLock<Scene>>,
+    /// The identifier of this node within its scene.
+    id: NodeId,
+    /// The scene's queue of nodes whose transformations have changed; see `NodeTransformChangedEvent`. This is shared
+    /// directly, rather than reached through `scene`, so that moving a node never needs to lock the scene.
+    transform_changes: Arc<Mutex<BTreeSet<NodeId>>>,
+    /// The node that this node is attached to, if any. This node's transformation is relative to its parent.
+    parent: Weak<RwLock<Node>>,
+    /// The nodes attached to this node.
+    children: Vec<Arc<RwLock<Node>>>,
     /// The position of the node.
     pos: Vector3<f32>,
     /// The rotation of the node.
@@ -25,6 +42,17 @@ pub struct Node {
 
     /// A reference to the renderable that we are going to try to render with this instance, if we want to actually render something.
     pub renderable: Option<Arc<Renderable>>,
+
+    /// If true, an outline is drawn around this node's renderable, e.g. to highlight a selected object.
+    /// The colour and width of the outline are given by the `RenderSettings` of each render phase.
+    pub outline: bool,
+
+    /// The colour that this node's renderable is multiplied by when it is drawn. The default is white, which
+    /// leaves the renderable's colours unchanged.
+    ///
+    /// Changing the tint is much cheaper than recreating a mesh with new vertex colours, so it is well suited to
+    /// short effects such as flashing a node red when it is hit.
+    pub tint: Colour,
 }
 
 impl Node {
@@ -33,10 +61,14 @@ impl Node {
     /// 
     /// Creates a new node with default settings and no instances or renderable.
     /// Does not implement `Default`: we want to encapsulate every node in an `Arc<RwLock<>>`.
-    pub(crate) fn default(scene: Arc<RwLock<Scene>>) -> Arc<RwLock<Self>> {
+    pub(crate) fn default(scene: Arc<RwLock<Scene>>, id: NodeId, transform_changes: Arc<Mutex<BTreeSet<NodeId>>>) -> Arc<RwLock<Self>> {
         let node = Arc::new(RwLock::new(Node {
             self_ref: Weak::new(),
             scene: Arc::downgrade(&scene),
+            id,
+            transform_changes,
+            parent: Weak::new(),
+            children: Vec::new(),
             pos: vec3(0.0, 0.0, 0.0),
             rot: Quaternion::new(1.0, 0.0, 0.0, 0.0),
             scl: vec3(1.0, 1.0, 1.0),
@@ -44,7 +76,9 @@ impl Node {
 
             components: Vec::new(),
 
-            renderable: None
+            renderable: None,
+            outline: false,
+            tint: Colour::new(1.0, 1.0, 1.0),
         }));
         node.write().unwrap().self_ref = Arc::downgrade(&node);
         return node;
@@ -53,10 +87,165 @@ impl Node {
     pub fn scene(&self) -> &Weak<RwLock<Scene>> {
         &self.scene
     }
+
+    /// Retrieves the identifier of this node, which never changes.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Retrieves the position of this node.
+    pub fn get_pos(&self) -> Vector3<f32> {
+        self.pos
+    }
+
+    /// Sets the position of this node, recomputing its transformation matrix.
+    /// A `NodeTransformChangedEvent` is fired for this node at the start of the next frame.
+    pub fn set_pos(&mut self, pos: Vector3<f32>) {
+        self.pos = pos;
+        self.compute_xform();
+    }
+
+    /// Retrieves the rotation of this node.
+    pub fn get_rot(&self) -> Quaternion<f32> {
+        self.rot
+    }
+
+    /// Sets the rotation of this node, recomputing its transformation matrix.
+    /// A `NodeTransformChangedEvent` is fired for this node at the start of the next frame.
+    pub fn set_rot(&mut self, rot: Quaternion<f32>) {
+        self.rot = rot;
+        self.compute_xform();
+    }
+
+    /// Retrieves the scale of this node.
+    pub fn get_scl(&self) -> Vector3<f32> {
+        self.scl
+    }
+
+    /// Sets the scale of this node, recomputing its transformation matrix.
+    /// A `NodeTransformChangedEvent` is fired for this node at the start of the next frame.
+    pub fn set_scl(&mut self, scl: Vector3<f32>) {
+        self.scl = scl;
+        self.compute_xform();
+    }
+
+    /// Retrieves the matrix that transforms from this node's local space into world space.
+    /// This applies the scale, then the rotation, then the position of the node, then the transformation of each of
+    /// its ancestors in turn.
+    ///
+    /// This briefly locks each ancestor for reading, so don't call it while holding a write lock on an ancestor.
+    pub fn get_xform(&self) -> Matrix4<f32> {
+        match self.parent.upgrade() {
+            Some(parent) => parent.read().unwrap().get_xform() * self.xform,
+            None => self.xform
+        }
+    }
+
+    /// Retrieves the matrix that transforms from this node's local space into its parent's local space.
+    /// If the node has no parent, this is the same as `get_xform`.
+    pub fn get_local_xform(&self) -> Matrix4<f32> {
+        self.xform
+    }
+
+    /// Retrieves the node that this node is attached to, if any.
+    pub fn parent(&self) -> Option<Arc<RwLock<Node>>> {
+        self.parent.upgrade()
+    }
+
+    /// Retrieves the nodes attached to this node.
+    pub fn children(&self) -> &[Arc<RwLock<Node>>] {
+        &self.children
+    }
+
+    /// Attaches `child` to this node, detaching it from its previous parent if it had one. From now on, the child's
+    /// position, rotation and scale are relative to this node, so it follows this node around.
+    ///
+    /// Both nodes should already be in the same scene. Attaching a node does not add it to the scene, and removing
+    /// a node from the scene does not remove its children.
+    ///
+    /// This locks `child`, the child's previous parent, and this node's ancestors, so none of them may be locked by
+    /// the caller.
+    ///
+    /// # Panics
+    /// In debug builds, if `child` is this node or one of its ancestors, which would create a cycle.
+    pub fn add_child(&mut self, child: Arc<RwLock<Node>>) {
+        debug_assert!(!self.is_self_or_ancestor(&child), "attaching a node to node {:?} would create a cycle", self.id);
+
+        let old_parent = {
+            let mut child = child.write().unwrap();
+            self.queue_transform_change(child.id);
+            std::mem::replace(&mut child.parent, Weak::clone(&self.self_ref))
+        };
+        if Weak::ptr_eq(&old_parent, &self.self_ref) {
+            // This node is already locked, so it can't be locked again below.
+            self.children.retain(|c| !Arc::ptr_eq(c, &child));
+        } else if let Some(old_parent) = old_parent.upgrade() {
+            old_parent.write().unwrap().children.retain(|c| !Arc::ptr_eq(c, &child));
+        }
+        self.children.push(child);
+    }
+
+    /// Detaches `child` from this node, if it is one of its children. The child's position, rotation and scale are
+    /// then relative to the world again.
+    pub fn remove_child(&mut self, child: &Arc<RwLock<Node>>) {
+        if let Some(i) = self.children.iter().position(|c| Arc::ptr_eq(c, child)) {
+            self.children.remove(i);
+            let mut child = child.write().unwrap();
+            child.parent = Weak::new();
+            self.queue_transform_change(child.id);
+        }
+    }
+
+    /// Returns true if `node` is this node or one of its ancestors.
+    pub(crate) fn is_self_or_ancestor(&self, node: &Arc<RwLock<Node>>) -> bool {
+        if Weak::ptr_eq(&self.self_ref, &Arc::downgrade(node)) {
+            return true;
+        }
+        let mut ancestor = self.parent.upgrade();
+        while let Some(a) = ancestor {
+            if Arc::ptr_eq(&a, node) {
+                return true;
+            }
+            ancestor = a.read().unwrap().parent.upgrade();
+        }
+        false
+    }
+
+    /// Recomputes the transformation matrix from the position, rotation and scale of this node.
+    fn compute_xform(&mut self) {
+        let scl = self.scl;
+        self.xform = Matrix4::from_translation(self.pos) * Matrix4::from(self.rot) * Matrix4::from_nonuniform_scale(scl.x, scl.y, scl.z);
+        self.queue_transform_change(self.id);
+    }
+
+    /// Queues up a `NodeTransformChangedEvent` for the given node, unless one is already queued.
+    fn queue_transform_change(&self, id: NodeId) {
+        self.transform_changes.lock().unwrap().insert(id);
+    }
 }
 
 /// Components listen for events to execute event-driven code.
-pub trait Component: Send + Sync {}
+pub trait Component: Send + Sync {
+    /// The name of this component's type, used when printing debug information.
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Named values of this component's fields, formatted as strings, for debug inspectors to display.
+    /// This is a lightweight substitute for reflection: components choose which fields to expose.
+    /// By default, no fields are exposed.
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Saves the state of this component, so that it can be written out with the rest of the scene by
+    /// `Scene::to_json`. To load the component again, register a loader for it in a `ComponentRegistry`.
+    /// By default, components cannot be saved and are left out of saved scenes.
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
 
 // TICK DEBUG COMPONENT
 
@@ -64,7 +253,16 @@ pub struct TickDebugComponent {
     node: Weak<RwLock<Node>>,
     tick_num: i32
 }
-impl Component for TickDebugComponent {}
+impl Component for TickDebugComponent {
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        vec![("tick_num".to_string(), self.tick_num.to_string())]
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({}))
+    }
+}
 
 crate::lock_data! {
     TickDebugData
@@ -93,6 +291,170 @@ impl TickDebugComponent {
     }
 }
 
+// TRANSFORM COMPONENT
+
+/// Moves and rotates its node at a constant rate every tick.
+pub struct TransformComponent {
+    /// The distance the node moves per second along each axis.
+    velocity: Vector3<f32>,
+    /// The axis the node rotates around, scaled by the angle in radians that it rotates per second.
+    angular_velocity: Vector3<f32>,
+}
+impl Component for TransformComponent {
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        vec![
+            ("velocity".to_string(), format!("{:?}", self.velocity)),
+            ("angular_velocity".to_string(), format!("{:?}", self.angular_velocity)),
+        ]
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(TransformComponentData {
+            velocity: self.velocity,
+            angular_velocity: self.angular_velocity,
+        }).ok()
+    }
+}
+
+crate::lock_data! {
+    TransformData
+    node: write Node,
+    component: read TransformComponent
+}
+
+impl TransformComponent {
+    /// Attaches a transform component to the given node, returning it so that its velocities can be changed later.
+    pub fn new(node: Arc<RwLock<Node>>, velocity: Vector3<f32>, angular_velocity: Vector3<f32>) -> Arc<RwLock<TransformComponent>> {
+        let component = Arc::new(RwLock::new(TransformComponent {
+            velocity,
+            angular_velocity,
+        }));
+
+        let data = Arc::new(RwLock::new(TransformData {
+            node: Arc::downgrade(&node),
+            component: Arc::downgrade(&component),
+        }));
+
+        if let Some(scene) = node.read().unwrap().scene.upgrade() {
+            TransformData::listen(&data, &scene.read().unwrap().events.tick, |event, node, component| {
+                let delta = event.delta as f32;
+                let pos = node.get_pos() + component.velocity * delta;
+                node.set_pos(pos);
+
+                let speed = component.angular_velocity.magnitude();
+                if speed > 0.0 {
+                    let spin = Quaternion::from_axis_angle(component.angular_velocity / speed, cgmath::Rad(speed * delta));
+                    let rot = spin * node.get_rot();
+                    node.set_rot(rot);
+                }
+            });
+        }
+
+        node.write().unwrap().components.push(Arc::clone(&component) as Arc<RwLock<dyn Component>>);
+        component
+    }
+
+    /// Sets the distance the node moves per second along each axis.
+    pub fn set_velocity(&mut self, velocity: Vector3<f32>) {
+        self.velocity = velocity;
+    }
+
+    /// Retrieves the distance the node moves per second along each axis.
+    pub fn get_velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Sets the rotation of the node per second, as the axis to rotate around scaled by the angle in radians.
+    pub fn set_angular_velocity(&mut self, angular_velocity: Vector3<f32>) {
+        self.angular_velocity = angular_velocity;
+    }
+
+    /// Retrieves the rotation of the node per second, as the axis to rotate around scaled by the angle in radians.
+    pub fn get_angular_velocity(&self) -> Vector3<f32> {
+        self.angular_velocity
+    }
+}
+
+// ROTATE COMPONENT
+
+/// Spins its node around a fixed axis at a constant rate every tick.
+pub struct RotateComponent {
+    /// The unit axis the node rotates around.
+    axis: Vector3<f32>,
+    /// The angle in radians that the node rotates per second.
+    rate: f32,
+}
+impl Component for RotateComponent {
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        vec![
+            ("axis".to_string(), format!("{:?}", self.axis)),
+            ("rate".to_string(), self.rate.to_string()),
+        ]
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(RotateComponentData {
+            axis: self.axis,
+            rate: self.rate,
+        }).ok()
+    }
+}
+
+crate::lock_data! {
+    RotateData
+    node: write Node,
+    component: read RotateComponent
+}
+
+impl RotateComponent {
+    /// Attaches a rotate component to the given node, spinning it around `axis` by `rate` radians per second.
+    /// The axis need not be normalised. Returns the component so that its axis and rate can be changed later.
+    pub fn new(node: Arc<RwLock<Node>>, axis: Vector3<f32>, rate: f32) -> Arc<RwLock<RotateComponent>> {
+        let component = Arc::new(RwLock::new(RotateComponent {
+            axis: axis.normalize(),
+            rate,
+        }));
+
+        let data = Arc::new(RwLock::new(RotateData {
+            node: Arc::downgrade(&node),
+            component: Arc::downgrade(&component),
+        }));
+
+        if let Some(scene) = node.read().unwrap().scene.upgrade() {
+            RotateData::listen(&data, &scene.read().unwrap().events.tick, |event, node, component| {
+                let spin = Quaternion::from_axis_angle(component.axis, cgmath::Rad(component.rate * event.delta as f32));
+                let rot = spin * node.get_rot();
+                node.set_rot(rot);
+            });
+        }
+
+        node.write().unwrap().components.push(Arc::clone(&component) as Arc<RwLock<dyn Component>>);
+        component
+    }
+
+    /// Sets the axis the node rotates around. The axis need not be normalised.
+    pub fn set_axis(&mut self, axis: Vector3<f32>) {
+        self.axis = axis.normalize();
+    }
+
+    /// Retrieves the unit axis the node rotates around.
+    pub fn get_axis(&self) -> Vector3<f32> {
+        self.axis
+    }
+
+    /// Sets the angle in radians that the node rotates per second.
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+    }
+
+    /// Retrieves the angle in radians that the node rotates per second.
+    pub fn get_rate(&self) -> f32 {
+        self.rate
+    }
+}
+
 // CAMERA MOTION COMPONENT
 
 pub struct CameraMotionComponent {
@@ -106,10 +468,24 @@ pub struct CameraMotionComponent {
     key_up: bool,
     key_down: bool,
 
+    /// The position of the left stick of a gamepad, which moves the camera like WASD.
+    move_stick: Vector2<f32>,
+    /// The position of the right stick of a gamepad, which turns the camera like the mouse.
+    look_stick: Vector2<f32>,
+
     pitch: f32,
     yaw: f32,
 }
-impl Component for CameraMotionComponent {}
+impl Component for CameraMotionComponent {
+    fn debug_fields(&self) -> Vec<(String, String)> {
+        vec![
+            ("pos".to_string(), format!("{:?}", self.cam.get_pos())),
+            ("pitch".to_string(), self.pitch.to_string()),
+            ("yaw".to_string(), self.yaw.to_string()),
+            ("projection".to_string(), format!("{:?}", self.cam.get_projection())),
+        ]
+    }
+}
 
 crate::lock_data! {
     CameraMotionData
@@ -133,6 +509,9 @@ impl CameraMotionComponent {
             key_up: false,
             key_down: false,
 
+            move_stick: vec2(0.0, 0.0),
+            look_stick: vec2(0.0, 0.0),
+
             pitch: 0.0,
             yaw: 0.0,
         }));
@@ -142,7 +521,7 @@ impl CameraMotionComponent {
         }));
 
         if let Some(scene) = node.read().unwrap().scene.upgrade() {
-            CameraMotionData::listen(&data, &scene.read().unwrap().events.tick, |_event, component| {
+            CameraMotionData::listen(&data, &scene.read().unwrap().events.tick, |event, component| {
                 // Update motion according to the keys pressed.
                 let mut offset_pos = crate::vec3(0.0, 0.0, 0.0);
 
@@ -164,6 +543,11 @@ impl CameraMotionComponent {
                 if component.key_down {
                     offset_pos -= component.cam.get_up();
                 }
+                offset_pos += component.cam.get_dir() * component.move_stick.y + component.cam.get_right() * component.move_stick.x;
+
+                // Turn at up to two radians per second with the right stick.
+                component.pitch -= component.look_stick.y * 2.0 * event.delta as f32;
+                component.yaw += component.look_stick.x * 2.0 * event.delta as f32;
 
                 component.cam.set_pos(component.cam.get_pos() + offset_pos * 0.001);
                 let xy = component.pitch.cos();
@@ -210,6 +594,13 @@ impl CameraMotionComponent {
                             ElementState::Released => { false },
                         }
                     }
+                    Some(crate::input::VirtualKeyCode::O) if event.state == ElementState::Pressed => {
+                        // Toggle between perspective and orthographic views.
+                        component.cam.set_projection(match component.cam.get_projection() {
+                            crate::graphics::CameraProjection::Perspective => crate::graphics::CameraProjection::Orthographic { height: 4.0 },
+                            crate::graphics::CameraProjection::Orthographic { .. } => crate::graphics::CameraProjection::Perspective,
+                        });
+                    }
                     _ => {}
                 }
             });
@@ -219,6 +610,30 @@ impl CameraMotionComponent {
                 component.yaw += event.delta.x as f32 * 0.001f32;
             });
 
+            CameraMotionData::listen(&data, &scene.read().unwrap().events.mouse_scroll, |event, component| {
+                // Zoom the orthographic view in and out. Scrolling by pixels is much finer than by lines.
+                if let crate::graphics::CameraProjection::Orthographic { height } = component.cam.get_projection() {
+                    let lines = match event.unit {
+                        crate::input::ScrollUnit::Lines => event.delta.y as f32,
+                        crate::input::ScrollUnit::Pixels => event.delta.y as f32 / 20.0,
+                    };
+                    let height = (height * 0.9f32.powf(lines)).max(0.1);
+                    component.cam.set_projection(crate::graphics::CameraProjection::Orthographic { height });
+                }
+            });
+
+            CameraMotionData::listen(&data, &scene.read().unwrap().events.gamepad, |event, component| {
+                if let crate::events::GamepadEvent::Axis { axis, value, .. } = event {
+                    match axis {
+                        crate::input::GamepadAxis::LeftStickX => component.move_stick.x = *value,
+                        crate::input::GamepadAxis::LeftStickY => component.move_stick.y = *value,
+                        crate::input::GamepadAxis::RightStickX => component.look_stick.x = *value,
+                        crate::input::GamepadAxis::RightStickY => component.look_stick.y = *value,
+                        _ => {}
+                    }
+                }
+            });
+
             CameraMotionData::listen(&data, &scene.read().unwrap().events.window_resize, |event, component| {
                 log::info!("Window resized to {:?}", event.new_size);
                 component.cam.set_screen_resolution(cgmath::vec2(event.new_size.x as f32, event.new_size.y as f32));
@@ -228,3 +643,91 @@ impl CameraMotionComponent {
         node.write().unwrap().components.push(component);
     }
 }
+
+// SCENE DUMP COMPONENT
+
+/// When a given key is pressed, logs every node in the scene along with its transform and components.
+/// Useful for debugging complex scenes.
+pub struct SceneDumpComponent {
+    node: Weak<RwLock<Node>>,
+    key: crate::input::VirtualKeyCode,
+}
+impl Component for SceneDumpComponent {
+    #[cfg(feature = "serde")]
+    fn save_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SceneDumpComponentData { key: self.key }).ok()
+    }
+}
+
+crate::lock_data! {
+    SceneDumpData
+    component: read SceneDumpComponent
+}
+
+impl SceneDumpComponent {
+    /// Attaches a scene dump component to the given node, which logs the scene whenever `key` is pressed.
+    pub fn new(node: Arc<RwLock<Node>>, key: crate::input::VirtualKeyCode) {
+        let component = Arc::new(RwLock::new(SceneDumpComponent {
+            node: Arc::downgrade(&node),
+            key,
+        }));
+
+        let data = Arc::new(RwLock::new(SceneDumpData {
+            component: Arc::downgrade(&component),
+        }));
+
+        if let Some(scene) = node.read().unwrap().scene.upgrade() {
+            SceneDumpData::listen(&data, &scene.read().unwrap().events.key, |event, component| {
+                if event.virtual_keycode == Some(component.key) && event.state == ElementState::Pressed && !event.is_repeat {
+                    component.dump();
+                }
+            });
+        }
+
+        node.write().unwrap().components.push(component);
+    }
+
+    /// Logs the contents of the scene containing this component's node. Children are listed under their parents.
+    fn dump(&self) {
+        let scene = match self.node.upgrade().and_then(|node| node.read().unwrap().scene.upgrade()) {
+            Some(scene) => scene,
+            None => return,
+        };
+        let scene = scene.read().unwrap();
+
+        let mut output = String::from("Scene dump:");
+        for (id, node) in &scene.nodes {
+            match node.try_read() {
+                Ok(node) if node.parent().is_some() => {}
+                Ok(node) => Self::dump_node(&node, 1, &mut output),
+                // We can't tell whether a locked node has a parent, so it is listed at the top level.
+                Err(_) => output += &format!("\n  node {} (locked)", id.0),
+            }
+        }
+        log::info!("{}", output);
+    }
+
+    /// Writes the given node, its components and, recursively, its children to `output`, indented by two spaces for
+    /// each level of `depth`.
+    fn dump_node(node: &Node, depth: usize, output: &mut String) {
+        let indent = "  ".repeat(depth);
+        *output += &format!("\n{}node {}: pos {:?}, rot {:?}, scl {:?}", indent, node.id().0, node.get_pos(), node.get_rot(), node.get_scl());
+        for component in &node.components {
+            match component.try_read() {
+                Ok(component) => {
+                    *output += &format!("\n{}  {}", indent, component.type_name());
+                    for (name, value) in component.debug_fields() {
+                        *output += &format!("\n{}    {}: {}", indent, name, value);
+                    }
+                },
+                Err(_) => *output += &format!("\n{}  (locked)", indent),
+            }
+        }
+        for child in node.children() {
+            match child.try_read() {
+                Ok(child) => Self::dump_node(&child, depth + 1, output),
+                Err(_) => *output += &format!("\n{}  node (locked)", indent),
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Saving and loading scenes as JSON. This module is only available with the `serde` feature.
+
+use crate::scene::*;
+use crate::graphics::{Colour, Renderable};
+use cgmath::{Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A description of a whole scene, which can be serialized to save the scene and deserialized to load it.
+/// See `Scene::to_json` and `Scene::from_json`.
+#[derive(Serialize, Deserialize)]
+pub struct SceneDescription {
+    /// The nodes in the scene, in the order they were created.
+    pub nodes: Vec<NodeDescription>,
+}
+
+/// A description of a single node in a `SceneDescription`.
+#[derive(Serialize, Deserialize)]
+pub struct NodeDescription {
+    /// The ID of the node. Loaded nodes keep their IDs, so `NodeId`s stored elsewhere, e.g. in save files, stay valid.
+    pub id: NodeId,
+    /// The ID of the node that this node is attached to, if any.
+    #[serde(default)]
+    pub parent: Option<NodeId>,
+    /// The position of the node, relative to its parent.
+    pub pos: Vector3<f32>,
+    /// The rotation of the node, relative to its parent.
+    pub rot: Quaternion<f32>,
+    /// The scale of the node, relative to its parent.
+    pub scl: Vector3<f32>,
+    /// See `Node::tint`.
+    pub tint: Colour,
+    /// See `Node::outline`.
+    #[serde(default)]
+    pub outline: bool,
+    /// The renderable of the node. Meshes and textures are stored by ID, not by their contents, so they must be
+    /// created in the same order before the scene is loaded, so that they get the same IDs.
+    #[serde(default)]
+    pub renderable: Option<Renderable>,
+    /// The components of the node that could be saved; see `Component::save_json`.
+    #[serde(default)]
+    pub components: Vec<ComponentDescription>,
+}
+
+/// A description of a single component in a `NodeDescription`.
+#[derive(Serialize, Deserialize)]
+pub struct ComponentDescription {
+    /// The type name of the component, as given by `Component::type_name`. This is used to find the function that
+    /// loads the component in the `ComponentRegistry`.
+    pub type_name: String,
+    /// The state of the component, as given by `Component::save_json`.
+    pub data: serde_json::Value,
+}
+
+/// A reason that a scene could not be loaded.
+#[derive(Debug)]
+pub enum SceneLoadError {
+    /// The JSON was malformed, or didn't describe a scene.
+    Json(serde_json::Error),
+    /// A component's type name wasn't registered in the `ComponentRegistry`.
+    UnknownComponent(String),
+    /// A component's saved data couldn't be loaded.
+    InvalidComponent {
+        /// The type name of the component.
+        type_name: String,
+        /// The reason the data couldn't be loaded.
+        error: serde_json::Error,
+    },
+    /// Two nodes had the same ID.
+    DuplicateNode(NodeId),
+    /// A node's parent wasn't in the scene.
+    MissingParent {
+        /// The node whose parent was missing.
+        node: NodeId,
+        /// The ID of the missing parent.
+        parent: NodeId,
+    },
+    /// A node's parent was the node itself or one of its descendants.
+    ParentCycle {
+        /// The node whose parent would create a cycle.
+        node: NodeId,
+        /// The ID of its parent.
+        parent: NodeId,
+    },
+}
+
+impl std::fmt::Display for SceneLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SceneLoadError::Json(e) => write!(f, "invalid scene JSON: {}", e),
+            SceneLoadError::UnknownComponent(type_name) => write!(f, "component {} was not registered", type_name),
+            SceneLoadError::InvalidComponent { type_name, error } => write!(f, "could not load component {}: {}", type_name, error),
+            SceneLoadError::DuplicateNode(id) => write!(f, "more than one node has the ID {:?}", id),
+            SceneLoadError::MissingParent { node, parent } => write!(f, "the parent {:?} of node {:?} does not exist", parent, node),
+            SceneLoadError::ParentCycle { node, parent } => write!(f, "the parent {:?} of node {:?} is the node itself or one of its descendants", parent, node),
+        }
+    }
+}
+
+impl std::error::Error for SceneLoadError {}
+
+impl From<serde_json::Error> for SceneLoadError {
+    fn from(e: serde_json::Error) -> Self {
+        SceneLoadError::Json(e)
+    }
+}
+
+/// A function that attaches a component to a node, given the component's saved data.
+type ComponentLoader = Box<dyn Fn(Arc<RwLock<Node>>, serde_json::Value) -> Result<(), serde_json::Error> + Send + Sync>;
+
+/// Knows how to load each type of component from the data saved by `Component::save_json`.
+/// The default registry can load covalent's built-in components that have no external dependencies. Register your
+/// own components with `register`.
+pub struct ComponentRegistry {
+    loaders: HashMap<String, ComponentLoader>,
+}
+
+/// The saved state of a `TransformComponent`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TransformComponentData {
+    pub velocity: Vector3<f32>,
+    pub angular_velocity: Vector3<f32>,
+}
+
+/// The saved state of a `RotateComponent`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RotateComponentData {
+    pub axis: Vector3<f32>,
+    pub rate: f32,
+}
+
+/// The saved state of a `SceneDumpComponent`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SceneDumpComponentData {
+    pub key: crate::input::VirtualKeyCode,
+}
+
+impl ComponentRegistry {
+    /// Creates a registry that can't load any components.
+    pub fn empty() -> ComponentRegistry {
+        ComponentRegistry {
+            loaders: HashMap::new(),
+        }
+    }
+
+    /// Registers a function that loads components of type `T` from their saved data, attaching them to the given node.
+    /// `T` must use the default implementation of `Component::type_name`.
+    pub fn register<T: Component + 'static>(&mut self, load: impl Fn(Arc<RwLock<Node>>, serde_json::Value) -> Result<(), serde_json::Error> + Send + Sync + 'static) {
+        self.loaders.insert(std::any::type_name::<T>().to_string(), Box::new(load));
+    }
+
+    /// Loads the given component and attaches it to the given node.
+    fn load(&self, node: &Arc<RwLock<Node>>, component: ComponentDescription) -> Result<(), SceneLoadError> {
+        let loader = match self.loaders.get(&component.type_name) {
+            Some(loader) => loader,
+            None => return Err(SceneLoadError::UnknownComponent(component.type_name)),
+        };
+        let type_name = component.type_name;
+        loader(Arc::clone(node), component.data).map_err(|error| SceneLoadError::InvalidComponent { type_name, error })
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        let mut registry = ComponentRegistry::empty();
+        registry.register::<TickDebugComponent>(|node, _| {
+            TickDebugComponent::new(node);
+            Ok(())
+        });
+        registry.register::<TransformComponent>(|node, data| {
+            let data: TransformComponentData = serde_json::from_value(data)?;
+            TransformComponent::new(node, data.velocity, data.angular_velocity);
+            Ok(())
+        });
+        registry.register::<RotateComponent>(|node, data| {
+            let data: RotateComponentData = serde_json::from_value(data)?;
+            RotateComponent::new(node, data.axis, data.rate);
+            Ok(())
+        });
+        registry.register::<SceneDumpComponent>(|node, data| {
+            let data: SceneDumpComponentData = serde_json::from_value(data)?;
+            SceneDumpComponent::new(node, data.key);
+            Ok(())
+        });
+        registry.register::<crate::physics::ColliderComponent>(|node, data| {
+            crate::physics::ColliderComponent::new(node, serde_json::from_value(data)?);
+            Ok(())
+        });
+        registry
+    }
+}
+
+impl Scene {
+    /// Describes the nodes in this scene, along with their transforms, renderables and components.
+    /// Components that can't be saved (see `Component::save_json`) are left out.
+    pub fn describe(&self) -> SceneDescription {
+        let nodes = self.iter_3d().map(|node| {
+            let node = node.read().unwrap();
+            let components = node.components.iter().filter_map(|component| {
+                let component = component.read().unwrap();
+                let data = component.save_json();
+                if data.is_none() {
+                    log::debug!("Not saving component {}, since it cannot be saved", component.type_name());
+                }
+                data.map(|data| ComponentDescription {
+                    type_name: component.type_name().to_string(),
+                    data,
+                })
+            }).collect();
+            NodeDescription {
+                id: node.id(),
+                parent: node.parent().map(|parent| parent.read().unwrap().id()),
+                pos: node.get_pos(),
+                rot: node.get_rot(),
+                scl: node.get_scl(),
+                tint: node.tint,
+                outline: node.outline,
+                renderable: node.renderable.as_ref().map(|r| Renderable::clone(r)),
+                components,
+            }
+        }).collect();
+        SceneDescription { nodes }
+    }
+
+    /// Saves this scene as JSON. See `describe`.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.describe())
+    }
+
+    /// Creates a new scene from a description, loading components with the given registry.
+    pub fn from_description(description: SceneDescription, registry: &ComponentRegistry) -> Result<Arc<RwLock<Scene>>, SceneLoadError> {
+        let scene = Scene::new();
+        let mut parents = Vec::new();
+        for node_description in description.nodes {
+            let node = match scene.write().unwrap().new_node_with_id(node_description.id) {
+                Some(node) => node,
+                None => return Err(SceneLoadError::DuplicateNode(node_description.id)),
+            };
+            {
+                let mut node = node.write().unwrap();
+                node.set_pos(node_description.pos);
+                node.set_rot(node_description.rot);
+                node.set_scl(node_description.scl);
+                node.tint = node_description.tint;
+                node.outline = node_description.outline;
+                node.renderable = node_description.renderable.map(Arc::new);
+            }
+            for component in node_description.components {
+                registry.load(&node, component)?;
+            }
+            if let Some(parent) = node_description.parent {
+                parents.push((node, parent));
+            }
+        }
+
+        // Parents may be described after their children, so attach children once every node exists.
+        for (node, parent_id) in parents {
+            let parent = scene.read().unwrap().get_node(parent_id);
+            let node_id = node.read().unwrap().id();
+            match parent {
+                // Attaching a node to itself or a descendant would deadlock or loop forever.
+                Some(parent) if parent.read().unwrap().is_self_or_ancestor(&node) => return Err(SceneLoadError::ParentCycle {
+                    node: node_id,
+                    parent: parent_id,
+                }),
+                Some(parent) => parent.write().unwrap().add_child(node),
+                None => return Err(SceneLoadError::MissingParent {
+                    node: node_id,
+                    parent: parent_id,
+                }),
+            }
+        }
+        Ok(scene)
+    }
+
+    /// Loads a scene saved by `to_json`, loading components with the given registry.
+    pub fn from_json(json: &str, registry: &ComponentRegistry) -> Result<Arc<RwLock<Scene>>, SceneLoadError> {
+        Scene::from_description(serde_json::from_str(json)?, registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_round_trip() {
+        let scene = Scene::new();
+        let parent = scene.write().unwrap().new_node();
+        let child = scene.write().unwrap().new_node();
+        let parent_id = parent.read().unwrap().id();
+        let child_id = child.read().unwrap().id();
+        parent.write().unwrap().set_pos(cgmath::vec3(1.0, 2.0, 3.0));
+        parent.write().unwrap().add_child(Arc::clone(&child));
+        child.write().unwrap().tint = Colour::new(1.0, 0.0, 0.0);
+        child.write().unwrap().renderable = Some(Arc::new(Renderable::Mesh(4)));
+        TransformComponent::new(Arc::clone(&child), cgmath::vec3(0.0, 0.0, 1.0), cgmath::vec3(0.0, 0.0, 0.0));
+
+        let json = scene.read().unwrap().to_json().unwrap();
+        let loaded = Scene::from_json(&json, &ComponentRegistry::default()).unwrap();
+        let loaded = loaded.read().unwrap();
+        let child = loaded.get_node(child_id).unwrap();
+        let child = child.read().unwrap();
+        assert_eq!(Some(parent_id), child.parent().map(|parent| parent.read().unwrap().id()));
+        assert_eq!(0xFF0000FF, child.tint.packed());
+        assert!(matches!(child.renderable.as_deref(), Some(Renderable::Mesh(4))));
+        assert_eq!(1, child.components.len());
+        assert_eq!(cgmath::vec3(1.0, 2.0, 3.0), child.get_xform().w.truncate());
+    }
+
+    #[test]
+    fn parent_cycles_are_rejected() {
+        let scene = Scene::new();
+        let a = scene.write().unwrap().new_node();
+        let b = scene.write().unwrap().new_node();
+        let (a_id, b_id) = (a.read().unwrap().id(), b.read().unwrap().id());
+        let registry = ComponentRegistry::default();
+
+        let mut description = scene.read().unwrap().describe();
+        description.nodes[0].parent = Some(a_id);
+        assert!(matches!(
+            Scene::from_description(description, &registry),
+            Err(SceneLoadError::ParentCycle { node, parent }) if node == a_id && parent == a_id
+        ));
+
+        let mut description = scene.read().unwrap().describe();
+        description.nodes[0].parent = Some(b_id);
+        description.nodes[1].parent = Some(a_id);
+        assert!(matches!(
+            Scene::from_description(description, &registry),
+            Err(SceneLoadError::ParentCycle { node, parent }) if node == b_id && parent == a_id
+        ));
+    }
+}
@@ -1,28 +1,74 @@
 use covalent::graphics;
-use covalent::graphics::{PipelinePhase, RenderSettings, RenderTarget, RenderVertex, Renderable};
+use covalent::graphics::{Colour, FontId, FramebufferId, PipelinePhase, PixelFormat, RenderChannel, RenderSettings, RenderTarget, RenderVertex, Renderable, ShaderId, ShaderSource, Text, TextureId};
 use covalent::scene::Scene;
-use covalent::{Context, DisplayHints};
+use covalent::{Context, DisplayHints, WindowMode};
 use glium;
 use glium::glutin;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::sync::Arc;
-use glium::backend::glutin::glutin::event::{VirtualKeyCode, ElementState, DeviceEvent};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use covalent::cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+use glium::backend::glutin::glutin::event::{VirtualKeyCode, ElementState, DeviceEvent, MouseButton, MouseScrollDelta};
+use glium::backend::glutin::glutin::window::{CursorIcon, Fullscreen};
+use glium::backend::glutin::glutin::monitor::MonitorHandle;
 
-/// Max vertices to store in a single VBO.
-const MAX_VERTS: usize = 10_000;
-/// Max indices to store in a single IBO.
-const MAX_INDS: usize = 10_000;
+/// Settings for the OpenGL backend that aren't covered by `DisplayHints`, because other backends have no use for them.
+#[derive(Debug, Clone)]
+pub struct BackendGLConfig {
+    /// Max vertices to store in the VBO used to batch together small renderables, such as triangles.
+    /// Larger batches need fewer draw calls, at the cost of GPU memory. Must be at least 4.
+    pub max_verts: usize,
+    /// Max indices to store in the IBO used to batch together small renderables. Must be at least 6.
+    pub max_inds: usize,
+}
+
+impl Default for BackendGLConfig {
+    fn default() -> Self {
+        Self {
+            max_verts: 10_000,
+            max_inds: 10_000,
+        }
+    }
+}
 
 struct MeshGL {
     vbo: glium::VertexBuffer<Vertex>,
     ibo: glium::IndexBuffer<u32>,
+    /// The bounding box of the mesh's vertices, used for frustum culling. This is `None` if the mesh is empty.
+    bounds: Option<graphics::Aabb>,
+}
+
+/// A texture loaded by the user, along with how it should be sampled.
+struct TextureGL {
+    /// Shared with the framebuffer it belongs to, if it was created with `framebuffer_texture`.
+    texture: Rc<glium::texture::SrgbTexture2d>,
+    sampler: graphics::Sampler,
+}
+
+/// An off-screen framebuffer. Only a single colour channel is supported at the moment.
+struct FramebufferGL {
+    colour: Rc<glium::texture::SrgbTexture2d>,
+    /// The depth buffer, which also has a stencil buffer so that outlines can be drawn.
+    depth_stencil: Option<glium::framebuffer::DepthStencilRenderBuffer>,
+}
+
+impl FramebufferGL {
+    /// Creates a surface that draws to this framebuffer.
+    fn surface<'a>(&'a self, display: &glium::Display) -> glium::framebuffer::SimpleFrameBuffer<'a> {
+        match &self.depth_stencil {
+            Some(depth_stencil) => glium::framebuffer::SimpleFrameBuffer::with_depth_stencil_buffer(display, &*self.colour, depth_stencil),
+            None => glium::framebuffer::SimpleFrameBuffer::new(display, &*self.colour),
+        }
+        .unwrap()
+    }
 }
 
 /// BackendGL is a rendering backend for Covalent, using OpenGL.
 pub struct BackendGL {
     /// Hints that tell `winit` how to create the window.
     display_hints: DisplayHints,
+    config: BackendGLConfig,
 
     /// The backend owns the glium display.
     display: glium::Display,
@@ -34,27 +80,133 @@ pub struct BackendGL {
 
     /// This map stores the meshes currently on the GPU.
     meshes: RefCell<HashMap<i64, MeshGL>>,
+    /// The ID that will be given to the next mesh to be created.
+    next_mesh_id: Cell<i64>,
+
+    /// This map stores the textures currently on the GPU.
+    textures: RefCell<HashMap<TextureId, TextureGL>>,
+    /// The ID that will be given to the next texture to be loaded.
+    next_texture_id: Cell<TextureId>,
+    /// A single white pixel, sampled when drawing anything that isn't textured.
+    white_texture: glium::texture::SrgbTexture2d,
+
+    /// This map stores the off-screen framebuffers.
+    framebuffers: RefCell<HashMap<FramebufferId, FramebufferGL>>,
+    /// The ID that will be given to the next framebuffer to be created.
+    next_framebuffer_id: Cell<FramebufferId>,
+
+    /// This map stores the shader programs loaded by the user.
+    shaders: RefCell<HashMap<ShaderId, glium::Program>>,
+    /// The ID that will be given to the next shader program to be loaded.
+    next_shader_id: Cell<ShaderId>,
+    /// The ID of the shader program compiled from each source, so that each source is only compiled once.
+    shader_cache: RefCell<HashMap<ShaderSource, ShaderId>>,
+    /// The sources of the shader programs that were loaded from files, so that they can be recompiled.
+    shader_files: RefCell<HashMap<ShaderId, ShaderSource>>,
+    /// When the files of each shader program in `shader_files` were last modified, so we can tell when they change.
+    #[cfg(feature = "watch_shaders")]
+    shader_modified: RefCell<HashMap<ShaderId, Option<std::time::SystemTime>>>,
+
+    /// This map stores the fonts loaded by the user.
+    fonts: RefCell<HashMap<FontId, rusttype::Font<'static>>>,
+    /// The ID that will be given to the next font to be loaded.
+    next_font_id: Cell<FontId>,
+    /// Keeps track of which glyphs are stored in `glyph_texture`. Font IDs are used as the cache's font indices.
+    glyph_cache: RefCell<rusttype::gpu_cache::Cache<'static>>,
+    /// Rendered glyphs, stored as white pixels whose alpha is the glyph's coverage, so that text can be drawn with
+    /// the built-in program.
+    glyph_texture: glium::texture::SrgbTexture2d,
+
+    /// The mesh and texture IDs that renderables referred to but were never created. We've already logged a
+    /// warning about these, so we don't log it again every frame.
+    missing_ids: RefCell<HashSet<(&'static str, i64)>>,
 }
 
 impl BackendGL {
+    /// Creates a window with the given display hints, using the default configuration.
     pub fn new(display_hints: DisplayHints) -> BackendGL {
+        BackendGL::with_config(display_hints, BackendGLConfig::default())
+    }
+
+    /// Creates a window with the given display hints and configuration.
+    ///
+    /// # Panics
+    /// If the batch size in the configuration is too small to hold a single quad.
+    pub fn with_config(display_hints: DisplayHints, config: BackendGLConfig) -> BackendGL {
+        assert!(config.max_verts >= 4 && config.max_inds >= 6, "batches must have room for at least 4 vertices and 6 indices");
+        assert!(config.max_verts <= std::u32::MAX as usize, "batches cannot have more than u32::MAX vertices");
+
         // 1. The **winit::EventsLoop** for handling events.
         let event_loop = glium::glutin::event_loop::EventLoop::new();
         // 2. Parameters for building the Window.
-        let wb = glium::glutin::window::WindowBuilder::new()
+        let mut wb = glium::glutin::window::WindowBuilder::new()
             .with_inner_size(glium::glutin::dpi::LogicalSize::new(display_hints.width, display_hints.height))
-            .with_title(display_hints.title.clone());
+            .with_title(display_hints.title.clone())
+            .with_resizable(display_hints.resizable)
+            .with_window_icon(display_hints.icon.as_ref().and_then(|(rgba, width, height)| {
+                match glium::glutin::window::Icon::from_rgba(rgba.clone(), *width, *height) {
+                    Ok(icon) => Some(icon),
+                    Err(e) => {
+                        log::warn!("Could not use the window icon: {:?}", e);
+                        None
+                    }
+                }
+            }))
+            .with_fullscreen(covalent_window_mode_to_winit_fullscreen(
+                display_hints.mode,
+                event_loop.available_monitors(),
+                event_loop.primary_monitor(),
+                event_loop.primary_monitor(),
+            ));
+        if let Some((width, height)) = display_hints.min_size {
+            wb = wb.with_min_inner_size(glium::glutin::dpi::LogicalSize::new(width, height));
+        }
+        if let Some((width, height)) = display_hints.max_size {
+            wb = wb.with_max_inner_size(glium::glutin::dpi::LogicalSize::new(width, height));
+        }
         // 3. Parameters for building the OpenGL context.
-        let cb = glium::glutin::ContextBuilder::new();
+        //    A stencil buffer is required for drawing outlines.
+        let cb = glium::glutin::ContextBuilder::new()
+            .with_stencil_buffer(8)
+            .with_vsync(display_hints.vsync);
         // 4. Build the Display with the given window and OpenGL context parameters and register the
         //    window with the events_loop.
         let display = glium::Display::new(wb, cb, &event_loop).unwrap();
 
+        let white_texture = glium::texture::SrgbTexture2d::new(
+            &display,
+            glium::texture::RawImage2d::from_raw_rgba(vec![255u8; 4], (1, 1)),
+        )
+        .unwrap();
+        let glyph_texture = glium::texture::SrgbTexture2d::empty(&display, GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE).unwrap();
+
         BackendGL {
             display_hints,
+            config,
             display,
             event_loop: Some(event_loop),
             meshes: RefCell::from(HashMap::new()),
+            next_mesh_id: Cell::new(0),
+            textures: RefCell::from(HashMap::new()),
+            next_texture_id: Cell::new(0),
+            white_texture,
+            framebuffers: RefCell::from(HashMap::new()),
+            next_framebuffer_id: Cell::new(0),
+            shaders: RefCell::from(HashMap::new()),
+            next_shader_id: Cell::new(0),
+            shader_cache: RefCell::from(HashMap::new()),
+            shader_files: RefCell::from(HashMap::new()),
+            #[cfg(feature = "watch_shaders")]
+            shader_modified: RefCell::from(HashMap::new()),
+            fonts: RefCell::from(HashMap::new()),
+            next_font_id: Cell::new(0),
+            glyph_cache: RefCell::from(
+                rusttype::gpu_cache::Cache::builder()
+                    .dimensions(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE)
+                    .build(),
+            ),
+            glyph_texture,
+            missing_ids: RefCell::from(HashSet::new()),
         }
     }
 }
@@ -226,89 +378,96 @@ fn winit_keycode_to_covalent_keycode(k: VirtualKeyCode) -> Option<covalent::inpu
     })
 }
 
-impl graphics::Backend for BackendGL {
-    fn main_loop(mut self, ctx: Context) {
-        let vertex_shader_src = r#"
-            #version 140
-
-            uniform mat4 combined;
-
-            in vec3 position;
-            in uint col;
-            
-            out vec2 io_pos;
-            out vec4 io_col;
+fn gilrs_button_to_covalent_button(b: gilrs::Button) -> covalent::input::GamepadButton {
+    use covalent::input::GamepadButton;
+    match b {
+        gilrs::Button::South => GamepadButton::South,
+        gilrs::Button::East => GamepadButton::East,
+        gilrs::Button::North => GamepadButton::North,
+        gilrs::Button::West => GamepadButton::West,
+        gilrs::Button::C => GamepadButton::C,
+        gilrs::Button::Z => GamepadButton::Z,
+        gilrs::Button::LeftTrigger => GamepadButton::LeftTrigger,
+        gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        gilrs::Button::RightTrigger => GamepadButton::RightTrigger,
+        gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        gilrs::Button::Select => GamepadButton::Select,
+        gilrs::Button::Start => GamepadButton::Start,
+        gilrs::Button::Mode => GamepadButton::Mode,
+        gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+        gilrs::Button::RightThumb => GamepadButton::RightThumb,
+        gilrs::Button::DPadUp => GamepadButton::DPadUp,
+        gilrs::Button::DPadDown => GamepadButton::DPadDown,
+        gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+        gilrs::Button::DPadRight => GamepadButton::DPadRight,
+        gilrs::Button::Unknown => GamepadButton::Unknown,
+    }
+}
 
-            void main() {
-                gl_Position = combined * vec4(position, 1.0);
-                io_pos = position.xy;
-                io_col = vec4(
-                    ((col & uint(0xFF000000)) >> 24) / 255.0f,
-                    ((col & uint(0x00FF0000)) >> 16) / 255.0f,
-                    ((col & uint(0x0000FF00)) >> 8) / 255.0f,
-                    ((col & uint(0x000000FF))) / 255.0f
-                );
-            }
-        "#;
-        let fragment_shader_src = r#"
-            #version 140
+fn gilrs_axis_to_covalent_axis(a: gilrs::Axis) -> covalent::input::GamepadAxis {
+    use covalent::input::GamepadAxis;
+    match a {
+        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+        gilrs::Axis::LeftZ => GamepadAxis::LeftZ,
+        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+        gilrs::Axis::RightZ => GamepadAxis::RightZ,
+        gilrs::Axis::DPadX => GamepadAxis::DPadX,
+        gilrs::Axis::DPadY => GamepadAxis::DPadY,
+        gilrs::Axis::Unknown => GamepadAxis::Unknown,
+    }
+}
 
-            in vec2 io_pos;
-            in vec4 io_col;
+impl graphics::Backend for BackendGL {
+    fn main_loop(mut self, ctx: Context) {
+        let mut batch = self.create_batch();
 
-            out vec4 color;
+        // Tell covalent the *initial* screen size by emitting a window resize event.
+        // The display hints give the size in logical pixels, but covalent reports sizes in physical pixels.
+        let initial_size = self.display.gl_window().window().inner_size();
+        ctx.process_window_resize_event(covalent::events::WindowResizeEvent {
+            new_size: covalent::vec2(initial_size.width, initial_size.height)
+        });
 
-            void main() {
-                //color = vec4(io_pos.x*0.5+0.5, io_pos.y*0.5+0.5, 1.0, 1.0);
-                color = io_col;
+        // Gamepad support is optional: if gilrs can't be initialised, we can still run without it.
+        let mut gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => {
+                // Gamepads that were connected before we started don't emit connection events, so tell covalent about them now.
+                for (id, _) in gilrs.gamepads() {
+                    ctx.process_gamepad_connection_event(covalent::events::GamepadConnectionEvent {
+                        gamepad_id: id.into(),
+                        connected: true
+                    });
+                }
+                Some(gilrs)
+            },
+            Err(e) => {
+                log::error!("Could not initialise gamepad support: {:?}", e);
+                None
             }
-        "#;
-
-        let program = glium::Program::from_source(
-            &self.display,
-            vertex_shader_src,
-            fragment_shader_src,
-            None,
-        )
-        .unwrap();
-
-        let vbo = glium::VertexBuffer::dynamic(
-            &self.display,
-            &vec![
-                Vertex {
-                    position: [0.0, 0.0, 0.0],
-                    col: 0xFFFFFFFF
-                };
-                MAX_VERTS
-            ],
-        )
-        .unwrap();
-        let ibo = glium::index::IndexBuffer::dynamic(
-            &self.display,
-            glium::index::PrimitiveType::TrianglesList,
-            &vec![0u32; MAX_INDS],
-        )
-        .unwrap();
-        let mut batch = BatchGL {
-            vbo,
-            ibo,
-            program,
         };
 
-        // Tell covalent the *initial* screen size by emitting a window resize event.
-        ctx.process_window_resize_event(covalent::events::WindowResizeEvent {
-            new_size: covalent::vec2(self.display_hints.width, self.display_hints.height)
-        });
+        // If the frame rate is capped, we sleep until it's time to render the next frame instead of polling.
+        let frame_period = self.display_hints.max_fps
+            .filter(|fps| *fps > 0)
+            .map(|fps| std::time::Duration::from_secs_f64(1.0 / fps as f64));
+        let mut next_frame_time = std::time::Instant::now();
 
         self.event_loop
             .take()
             .unwrap()
             .run(move |ev, _, control_flow| {
-                *control_flow = glutin::event_loop::ControlFlow::Poll;
+                *control_flow = match frame_period {
+                    Some(_) => glutin::event_loop::ControlFlow::WaitUntil(next_frame_time),
+                    None => glutin::event_loop::ControlFlow::Poll,
+                };
 
                 match ev {
                     glutin::event::Event::WindowEvent { event, .. } => match event {
                         glutin::event::WindowEvent::CloseRequested => {
+                            // The event loop never returns, so give the game a chance to clean up now.
+                            ctx.process_shutdown();
                             *control_flow = glutin::event_loop::ControlFlow::Exit;
                             return;
                         },
@@ -324,15 +483,61 @@ impl graphics::Backend for BackendGL {
                                 virtual_keycode: match input.virtual_keycode {
                                     None => { None },
                                     Some(k) => { winit_keycode_to_covalent_keycode(k) },
-                                }
+                                },
+                                // Covalent works this out for us.
+                                is_repeat: false,
                             };
                             ctx.process_keyboard_event(event);
                         },
+                        glutin::event::WindowEvent::MouseInput { state, button, .. } => {
+                            ctx.process_mouse_button_event(covalent::events::MouseButtonEvent {
+                                button: match button {
+                                    MouseButton::Left => { covalent::input::MouseButton::Left },
+                                    MouseButton::Right => { covalent::input::MouseButton::Right },
+                                    MouseButton::Middle => { covalent::input::MouseButton::Middle },
+                                    MouseButton::Other(b) => { covalent::input::MouseButton::Other(b.into()) },
+                                },
+                                state: match state {
+                                    ElementState::Pressed => { covalent::input::ElementState::Pressed },
+                                    ElementState::Released => { covalent::input::ElementState::Released },
+                                },
+                            });
+                        },
+                        // glutin reports cursor positions in physical pixels already, which is what covalent expects.
+                        glutin::event::WindowEvent::CursorMoved { position, .. } => {
+                            ctx.process_cursor_moved_event(covalent::events::CursorMovedEvent {
+                                pos: covalent::vec2(position.x, position.y)
+                            });
+                        },
+                        glutin::event::WindowEvent::CursorEntered { .. } => {
+                            ctx.process_cursor_entered_event(covalent::events::CursorEnteredEvent {});
+                        },
+                        glutin::event::WindowEvent::CursorLeft { .. } => {
+                            ctx.process_cursor_left_event(covalent::events::CursorLeftEvent {});
+                        },
+                        glutin::event::WindowEvent::MouseWheel { delta, .. } => {
+                            ctx.process_mouse_scroll_event(match delta {
+                                MouseScrollDelta::LineDelta(x, y) => covalent::events::MouseScrollEvent {
+                                    delta: covalent::vec2(x as f64, y as f64),
+                                    unit: covalent::input::ScrollUnit::Lines,
+                                },
+                                MouseScrollDelta::PixelDelta(pos) => covalent::events::MouseScrollEvent {
+                                    delta: covalent::vec2(pos.x, pos.y),
+                                    unit: covalent::input::ScrollUnit::Pixels,
+                                },
+                            });
+                        },
                         glutin::event::WindowEvent::Resized(new_size) => {
                             ctx.process_window_resize_event(covalent::events::WindowResizeEvent {
                                 new_size: covalent::vec2(new_size.width, new_size.height)
                             });
                         },
+                        // Moving the window to a display with a different scale factor changes its size in physical pixels.
+                        glutin::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                            ctx.process_window_resize_event(covalent::events::WindowResizeEvent {
+                                new_size: covalent::vec2(new_inner_size.width, new_inner_size.height)
+                            });
+                        },
                         _ => (),
                     },
 
@@ -348,6 +553,73 @@ impl graphics::Backend for BackendGL {
                     // All events have been successfully polled.
                     // We can now begin rendering the screen.
                     glutin::event::Event::MainEventsCleared => {
+                        if let Some(frame_period) = frame_period {
+                            let now = std::time::Instant::now();
+                            if now < next_frame_time {
+                                // We were woken up early by an event. Keep waiting.
+                                return;
+                            }
+                            // If we've fallen behind, e.g. because the last frame was slow, don't try to catch up.
+                            next_frame_time = std::cmp::max(next_frame_time + frame_period, now);
+                            *control_flow = glutin::event_loop::ControlFlow::WaitUntil(next_frame_time);
+                        }
+
+                        // Gamepad events aren't delivered through the glutin event loop, so poll for them now.
+                        if let Some(gilrs) = &mut gilrs {
+                            while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+                                match event {
+                                    gilrs::EventType::Connected => {
+                                        ctx.process_gamepad_connection_event(covalent::events::GamepadConnectionEvent {
+                                            gamepad_id: id.into(),
+                                            connected: true
+                                        });
+                                    },
+                                    gilrs::EventType::Disconnected => {
+                                        ctx.process_gamepad_connection_event(covalent::events::GamepadConnectionEvent {
+                                            gamepad_id: id.into(),
+                                            connected: false
+                                        });
+                                    },
+                                    gilrs::EventType::ButtonPressed(button, _) => {
+                                        ctx.process_gamepad_event(covalent::events::GamepadEvent::Button {
+                                            gamepad_id: id.into(),
+                                            button: gilrs_button_to_covalent_button(button),
+                                            state: covalent::input::ElementState::Pressed,
+                                        });
+                                    },
+                                    gilrs::EventType::ButtonReleased(button, _) => {
+                                        ctx.process_gamepad_event(covalent::events::GamepadEvent::Button {
+                                            gamepad_id: id.into(),
+                                            button: gilrs_button_to_covalent_button(button),
+                                            state: covalent::input::ElementState::Released,
+                                        });
+                                    },
+                                    gilrs::EventType::AxisChanged(axis, value, _) => {
+                                        ctx.process_gamepad_event(covalent::events::GamepadEvent::Axis {
+                                            gamepad_id: id.into(),
+                                            axis: gilrs_axis_to_covalent_axis(axis),
+                                            value,
+                                        });
+                                    },
+                                    _ => (),
+                                }
+                            }
+                        }
+
+                        for command in ctx.drain_window_commands() {
+                            self.apply_window_command(command);
+                        }
+
+                        if ctx.take_shader_reload_request() {
+                            let ids: Vec<ShaderId> = self.shader_files.borrow().keys().copied().collect();
+                            self.recompile_shaders(ids);
+                        }
+                        #[cfg(feature = "watch_shaders")]
+                        {
+                            let modified = self.modified_shaders();
+                            self.recompile_shaders(modified);
+                        }
+
                         // For information about function invocation order,
                         // please see the documentation for `covalent::Context`.
                         ctx.begin_frame();
@@ -355,10 +627,16 @@ impl graphics::Backend for BackendGL {
                         let mut frame = self.display.draw();
 
                         let (scene, phases) = ctx.render_phases();
+                        // Every render phase draws the same nodes, so only walk the scene once per frame.
+                        let draw_list = DrawList::of_scene(&scene.read().unwrap(), ctx.drain_text());
 
+                        for (framebuffer, camera) in ctx.framebuffer_cameras() {
+                            self.render_to_framebuffer(framebuffer, camera, &draw_list, &mut batch);
+                        }
                         for (name, phase) in phases {
-                            self.execute_phase(name, &scene.read().unwrap(), phase, &mut batch, &mut frame);
+                            self.execute_phase(name, &draw_list, phase, &mut batch, &mut frame);
                         }
+                        self.render_profiler_graph(&draw_list.profiler_graph, &mut frame, &mut batch);
                         if let Err(e) = frame.finish() {
                             log::error!("Error caught when swapping buffers: {:?}", e);
                         }
@@ -370,12 +648,13 @@ impl graphics::Backend for BackendGL {
             });
     }
 
-    fn create_mesh(&self, verts: Vec<RenderVertex>, inds: Vec<u32>) -> Renderable {
+    fn create_mesh(&self, mut verts: Vec<RenderVertex>, inds: Vec<u32>) -> Renderable {
         log::trace!(
             "Creating mesh with {} verts, {} inds",
             verts.len(),
             inds.len()
         );
+        graphics::fill_flat_normals(&mut verts, &inds);
         let verts1 = verts.iter().map(conv).collect::<Vec<_>>();
         let mesh = MeshGL {
             vbo: glium::VertexBuffer::new(&self.display, &verts1).unwrap(),
@@ -385,202 +664,1571 @@ impl graphics::Backend for BackendGL {
                 &inds,
             )
             .unwrap(),
+            bounds: graphics::Aabb::from_points(verts.iter().map(|v| v.pos)),
         };
-        let idx = 1; // TODO create random index
+        let idx = self.next_mesh_id.get();
+        self.next_mesh_id.set(idx + 1);
         self.meshes.borrow_mut().insert(idx, mesh);
         Renderable::Mesh(idx)
     }
-}
 
-/// Convert a generic RenderVertex into an OpenGL-compatible vertex.
-fn conv(v: &RenderVertex) -> Vertex {
-    Vertex {
-        position: [v.pos.x, v.pos.y, v.pos.z],
-        col: v.col.packed(),
+    fn delete_mesh(&self, r: &Renderable) {
+        let idx = match r {
+            Renderable::Mesh(idx) => *idx,
+            Renderable::TexturedMesh { mesh_id, .. } => *mesh_id,
+            _ => return,
+        };
+        log::trace!("Deleting mesh {}", idx);
+        // Dropping the mesh frees its vertex and index buffers.
+        self.meshes.borrow_mut().remove(&idx);
     }
-}
-
-static mut I: i32 = 0;
 
-impl BackendGL {
-    fn execute_phase(
-        &self,
-        _name: &str,
-        scene: &Scene,
-        phase: &PipelinePhase,
-        batch: &mut BatchGL,
-        frame: &mut glium::Frame,
-    ) {
-        match phase {
-            PipelinePhase::Clear { target } => {
-                // We need to clear the given target.
-                let render_target = match target {
-                    RenderTarget::Window => frame,
-                };
+    fn load_texture(&self, pixels: Vec<u8>, width: u32, height: u32, format: PixelFormat) -> TextureId {
+        log::trace!("Loading {}x{} {:?} texture", width, height, format);
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "texture data must contain four bytes per pixel");
+        // glium's only four-byte client format is `U8U8U8U8`, which is always RGBA, so BGRA pixels are reordered first.
+        let rgba = format.to_rgba(pixels);
+        // OpenGL expects the bottom row first, so reverse the order of the rows.
+        let image = glium::texture::RawImage2d::from_raw_rgba_reversed(&rgba, (width, height));
+        let texture = glium::texture::SrgbTexture2d::new(&self.display, image).unwrap();
+        let id = self.next_texture_id.get();
+        self.next_texture_id.set(id + 1);
+        self.textures.borrow_mut().insert(id, TextureGL { texture: Rc::new(texture), sampler: graphics::Sampler::default() });
+        id
+    }
 
-                self.clear(render_target);
-            }
-            PipelinePhase::Render { settings, target } => {
-                // We need to render to the given target.
-                let render_target = match target {
-                    RenderTarget::Window => frame,
-                };
+    fn set_texture_sampler(&self, texture: TextureId, sampler: graphics::Sampler) {
+        match self.textures.borrow_mut().get_mut(&texture) {
+            Some(texture) => texture.sampler = sampler,
+            None => self.warn_missing("texture", texture),
+        }
+    }
 
-                self.render(settings, scene, render_target, batch);
+    fn create_framebuffer(&self, width: u32, height: u32, channels: &[RenderChannel]) -> FramebufferId {
+        log::trace!("Creating {}x{} framebuffer with channels {:?}", width, height, channels);
+        for channel in channels {
+            match channel {
+                RenderChannel::Colour(0) | RenderChannel::Depth => {}
+                _ => panic!("render channel {:?} is not supported by the GL backend yet", channel),
             }
         }
+        let colour = Rc::new(glium::texture::SrgbTexture2d::empty(&self.display, width, height).unwrap());
+        let depth_stencil = if channels.contains(&RenderChannel::Depth) {
+            Some(glium::framebuffer::DepthStencilRenderBuffer::new(
+                &self.display,
+                glium::texture::DepthStencilFormat::I24I8,
+                width,
+                height,
+            )
+            .unwrap())
+        } else {
+            None
+        };
+        let id = self.next_framebuffer_id.get();
+        self.next_framebuffer_id.set(id + 1);
+        self.framebuffers.borrow_mut().insert(id, FramebufferGL { colour, depth_stencil });
+        id
     }
 
-    fn clear(&self, render_target: &mut impl glium::Surface) {
-        render_target.clear_color_and_depth((0.5, 0.5, 0.5, 1.0), std::f32::MAX);
+    fn framebuffer_texture(&self, framebuffer: FramebufferId) -> TextureId {
+        let texture = match self.framebuffers.borrow().get(&framebuffer) {
+            Some(framebuffer) => Rc::clone(&framebuffer.colour),
+            None => panic!("tried to use framebuffer {} as a texture, but it was never created", framebuffer),
+        };
+        let id = self.next_texture_id.get();
+        self.next_texture_id.set(id + 1);
+        self.textures.borrow_mut().insert(id, TextureGL { texture, sampler: graphics::Sampler::default() });
+        id
     }
 
-    fn render(
-        &self,
-        settings: &RenderSettings,
-        scene: &Scene,
-        render_target: &mut impl glium::Surface,
-        batch: &mut BatchGL,
-    ) {
-        unsafe {
-            I += 1;
+    fn load_shader_source(&self, source: ShaderSource) -> ShaderId {
+        if let Some(id) = self.shader_cache.borrow().get(&source) {
+            return *id;
         }
+        let program = match self.compile_shader(&source) {
+            Ok(program) => program,
+            Err(e) => {
+                log::error!("Could not compile shader: {}", e);
+                panic!("could not compile shader: {}", e);
+            }
+        };
+        let id = self.next_shader_id.get();
+        self.next_shader_id.set(id + 1);
+        self.shaders.borrow_mut().insert(id, program);
+        self.shader_cache.borrow_mut().insert(source.clone(), id);
+        if source.paths().is_some() {
+            #[cfg(feature = "watch_shaders")]
+            self.shader_modified.borrow_mut().insert(id, last_modified(&source));
+            self.shader_files.borrow_mut().insert(id, source);
+        }
+        id
+    }
 
-        let mut it = scene
-            .iter_3d()
-            .filter_map(|node| {
-                node.read()
-                    .unwrap()
-                    .renderable
-                    .as_ref()
-                    .map(Arc::clone)
-            })
-            .peekable();
-
-        use covalent::cgmath::Matrix;
-        /*settings
-            .cam
-            .write()
-            .unwrap()
-            .as_perspective_camera()
-            .unwrap()
-            .set_pos(covalent::pt3(
-                1.1,
-                1.1,
-                0.3 + 0.3 * ((unsafe { I } as f32) * 0.01).sin(),
-            ));*/
-        let c = settings
-            .camera_matrices
-            .read()
-            .unwrap()
-            .combined
-            .transpose();
-        let combined = [
-            [c.x.x, c.y.x, c.z.x, c.w.x],
-            [c.x.y, c.y.y, c.z.y, c.w.y],
-            [c.x.z, c.y.z, c.z.z, c.w.z],
-            [c.x.w, c.y.w, c.z.w, c.w.w],
-        ];
-        let uniforms = glium::uniform! {
-            combined: combined
+    fn load_font(&self, ttf_bytes: Vec<u8>) -> FontId {
+        let font = match rusttype::Font::try_from_vec(ttf_bytes) {
+            Some(font) => font,
+            None => panic!("could not load font: the data is not a valid TrueType font"),
         };
+        let id = self.next_font_id.get();
+        self.next_font_id.set(id + 1);
+        self.fonts.borrow_mut().insert(id, font);
+        id
+    }
+}
 
-        let mut params: glium::DrawParameters = Default::default();
-        params.depth.test = glium::DepthTest::IfLess;
-        params.depth.write = true;
+fn covalent_sampler_to_glium_sampler(sampler: &graphics::Sampler) -> glium::uniforms::SamplerBehavior {
+    use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, SamplerWrapFunction};
+    let wrap = |wrap: graphics::SamplerWrap| match wrap {
+        graphics::SamplerWrap::Clamp => SamplerWrapFunction::Clamp,
+        graphics::SamplerWrap::Repeat => SamplerWrapFunction::Repeat,
+        graphics::SamplerWrap::MirroredRepeat => SamplerWrapFunction::Mirror,
+    };
+    // Minification also chooses between mipmaps. Textures without mipmaps, such as framebuffers, only have one level,
+    // so this has no effect on them.
+    let (minify_filter, magnify_filter) = match sampler.filter {
+        graphics::SamplerFilter::Nearest => (MinifySamplerFilter::NearestMipmapNearest, MagnifySamplerFilter::Nearest),
+        graphics::SamplerFilter::Linear => (MinifySamplerFilter::LinearMipmapLinear, MagnifySamplerFilter::Linear),
+    };
+    glium::uniforms::SamplerBehavior {
+        wrap_function: (wrap(sampler.wrap_u), wrap(sampler.wrap_v), SamplerWrapFunction::Clamp),
+        minify_filter,
+        magnify_filter,
+        max_anisotropy: sampler.anisotropy.max(1),
+        ..Default::default()
+    }
+}
 
-        let mut draw_calls = 0u32;
-        while let Some(_) = it.peek() {
-            draw_calls += 1;
-            let mut vbo = batch.vbo.map_write();
-            let mut ibo = batch.ibo.map_write();
-            let idx = self.render_lots(
-                &mut it,
-                &mut vbo,
-                &mut ibo,
-                render_target,
-                &batch.program,
-                &uniforms,
-                &params,
-            );
-            drop(vbo);
-            drop(ibo);
+fn covalent_blend_to_glium_blend(blend: graphics::BlendMode) -> glium::Blend {
+    use glium::{BlendingFunction, LinearBlendingFactor};
+    match blend {
+        graphics::BlendMode::Opaque => glium::Blend::default(),
+        graphics::BlendMode::AlphaBlend => glium::Blend::alpha_blending(),
+        graphics::BlendMode::Additive => glium::Blend {
+            color: BlendingFunction::Addition {
+                source: LinearBlendingFactor::SourceAlpha,
+                destination: LinearBlendingFactor::One,
+            },
+            alpha: BlendingFunction::Addition {
+                source: LinearBlendingFactor::Zero,
+                destination: LinearBlendingFactor::One,
+            },
+            constant_value: (0.0, 0.0, 0.0, 0.0),
+        },
+    }
+}
 
-            if idx > 0 {
-                render_target
-                    .draw(
-                        &batch.vbo,
-                        &batch.ibo.slice(0..idx).unwrap(),
-                        &batch.program,
-                        &uniforms,
-                        &params,
-                    )
-                    .unwrap();
-            }
-        }
-        //log::trace!("Executed {} draw calls this frame", draw_calls);
+fn covalent_polygon_mode_to_glium_polygon_mode(mode: graphics::PolygonMode) -> glium::PolygonMode {
+    match mode {
+        graphics::PolygonMode::Fill => glium::PolygonMode::Fill,
+        graphics::PolygonMode::Line => glium::PolygonMode::Line,
+        graphics::PolygonMode::Point => glium::PolygonMode::Point,
     }
+}
 
-    /// Render as many things from the given iterator as we can in the current batch, returning the (exclusive) max index we wrote to.
-    fn render_lots(
-        &self,
-        it: &mut std::iter::Peekable<impl Iterator<Item = Arc<Renderable>>>,
-        vbo: &mut glium::buffer::WriteMapping<[Vertex]>,
-        ibo: &mut glium::buffer::WriteMapping<[u32]>,
-        render_target: &mut impl glium::Surface,
-        program: &glium::Program,
-        uniforms: &impl glium::uniforms::Uniforms,
-        params: &glium::DrawParameters,
-    ) -> usize {
-        let mut current_vertex = 0;
-        let mut current_index = 0;
-        loop {
-            match it.peek() {
-                Some(r) => {
-                    match **r {
-                        Renderable::None => {
-                            it.next();
-                        }
-                        Renderable::Triangle(v0, v1, v2) => {
-                            if current_index + 3 >= MAX_INDS || current_vertex + 3 >= MAX_VERTS {
-                                break; // Do not consume the triangle, leave it to the next call to render_lots.
-                            }
-                            vbo.set(current_vertex + 0, conv(&v0));
-                            vbo.set(current_vertex + 1, conv(&v1));
-                            vbo.set(current_vertex + 2, conv(&v2));
-                            ibo.set(current_index + 0, (current_vertex + 0) as u32);
-                            ibo.set(current_index + 1, (current_vertex + 1) as u32);
-                            ibo.set(current_index + 2, (current_vertex + 2) as u32);
-                            current_vertex += 3;
-                            current_index += 3;
-                            it.next();
-                        }
-                        Renderable::Mesh(i) => {
-                            let mesh = &self.meshes.borrow()[&i];
-                            render_target
-                                .draw(&mesh.vbo, &mesh.ibo, program, uniforms, params)
-                                .unwrap();
-                            it.next();
-                        }
-                    }
-                }
-                None => break,
+fn covalent_cull_mode_to_glium_backface_culling_mode(mode: graphics::CullMode) -> glium::BackfaceCullingMode {
+    match mode {
+        graphics::CullMode::None => glium::BackfaceCullingMode::CullingDisabled,
+        graphics::CullMode::ClockwiseFront => glium::BackfaceCullingMode::CullCounterClockwise,
+        graphics::CullMode::CounterClockwiseFront => glium::BackfaceCullingMode::CullClockwise,
+    }
+}
+
+/// Works out which fullscreen mode winit should use for the given window mode. `current` is the monitor that a
+/// borderless window should cover. Exclusive fullscreen falls back to the primary monitor if the requested one
+/// doesn't exist, and to borderless if the monitor has no video modes.
+fn covalent_window_mode_to_winit_fullscreen(
+    mode: WindowMode,
+    mut monitors: impl Iterator<Item = MonitorHandle>,
+    primary: MonitorHandle,
+    current: MonitorHandle,
+) -> Option<Fullscreen> {
+    match mode {
+        WindowMode::Windowed => None,
+        WindowMode::Borderless => Some(Fullscreen::Borderless(current)),
+        WindowMode::Fullscreen { monitor_index } => {
+            let monitor = monitors.nth(monitor_index).unwrap_or_else(|| {
+                log::warn!("There is no monitor {}, so the primary monitor will be used for fullscreen", monitor_index);
+                primary
+            });
+            // Prefer the largest resolution, then the highest refresh rate.
+            let video_mode = monitor
+                .video_modes()
+                .max_by_key(|m| (m.size().width * m.size().height, m.refresh_rate()));
+            match video_mode {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => Some(Fullscreen::Borderless(monitor)),
             }
         }
-        current_index
     }
 }
 
+fn covalent_cursor_icon_to_winit_cursor_icon(icon: covalent::input::CursorIcon) -> CursorIcon {
+    match icon {
+        covalent::input::CursorIcon::Default => { CursorIcon::Default },
+        covalent::input::CursorIcon::Crosshair => { CursorIcon::Crosshair },
+        covalent::input::CursorIcon::Hand => { CursorIcon::Hand },
+        covalent::input::CursorIcon::Arrow => { CursorIcon::Arrow },
+        covalent::input::CursorIcon::Move => { CursorIcon::Move },
+        covalent::input::CursorIcon::Text => { CursorIcon::Text },
+        covalent::input::CursorIcon::Wait => { CursorIcon::Wait },
+        covalent::input::CursorIcon::Help => { CursorIcon::Help },
+        covalent::input::CursorIcon::Progress => { CursorIcon::Progress },
+        covalent::input::CursorIcon::NotAllowed => { CursorIcon::NotAllowed },
+        covalent::input::CursorIcon::ContextMenu => { CursorIcon::ContextMenu },
+        covalent::input::CursorIcon::Cell => { CursorIcon::Cell },
+        covalent::input::CursorIcon::VerticalText => { CursorIcon::VerticalText },
+        covalent::input::CursorIcon::Alias => { CursorIcon::Alias },
+        covalent::input::CursorIcon::Copy => { CursorIcon::Copy },
+        covalent::input::CursorIcon::NoDrop => { CursorIcon::NoDrop },
+        covalent::input::CursorIcon::Grab => { CursorIcon::Grab },
+        covalent::input::CursorIcon::Grabbing => { CursorIcon::Grabbing },
+        covalent::input::CursorIcon::AllScroll => { CursorIcon::AllScroll },
+        covalent::input::CursorIcon::ZoomIn => { CursorIcon::ZoomIn },
+        covalent::input::CursorIcon::ZoomOut => { CursorIcon::ZoomOut },
+        covalent::input::CursorIcon::EResize => { CursorIcon::EResize },
+        covalent::input::CursorIcon::NResize => { CursorIcon::NResize },
+        covalent::input::CursorIcon::NeResize => { CursorIcon::NeResize },
+        covalent::input::CursorIcon::NwResize => { CursorIcon::NwResize },
+        covalent::input::CursorIcon::SResize => { CursorIcon::SResize },
+        covalent::input::CursorIcon::SeResize => { CursorIcon::SeResize },
+        covalent::input::CursorIcon::SwResize => { CursorIcon::SwResize },
+        covalent::input::CursorIcon::WResize => { CursorIcon::WResize },
+        covalent::input::CursorIcon::EwResize => { CursorIcon::EwResize },
+        covalent::input::CursorIcon::NsResize => { CursorIcon::NsResize },
+        covalent::input::CursorIcon::NeswResize => { CursorIcon::NeswResize },
+        covalent::input::CursorIcon::NwseResize => { CursorIcon::NwseResize },
+        covalent::input::CursorIcon::ColResize => { CursorIcon::ColResize },
+        covalent::input::CursorIcon::RowResize => { CursorIcon::RowResize },
+    }
+}
+
+/// Convert a generic RenderVertex into an OpenGL-compatible vertex.
+fn conv(v: &RenderVertex) -> Vertex {
+    Vertex {
+        position: [v.pos.x, v.pos.y, v.pos.z],
+        col: v.col.packed(),
+        uv: [v.uv.x, v.uv.y],
+        normal: [v.normal.x, v.normal.y, v.normal.z],
+    }
+}
+
+/// The identity matrix, used as the `combined` uniform to draw directly in normalised device coordinates.
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// The uniforms passed to our shader programs.
+/// Programs simply ignore any of these uniforms that they don't use.
 #[derive(Copy, Clone)]
-#[repr(C)]
-struct Vertex {
-    position: [f32; 3],
-    col: u32,
+struct UniformsGL<'t> {
+    combined: [[f32; 4]; 4],
+    /// Transforms vertices from the local space of the mesh being drawn into world space.
+    model: [[f32; 4]; 4],
+    tint: [f32; 4],
+    outline_colour: [f32; 4],
+    /// If this is `Some`, the main program outputs this colour instead of the usual colours.
+    override_colour: Option<[f32; 4]>,
+    texture: &'t glium::texture::SrgbTexture2d,
+    /// How `texture` is sampled.
+    sampler: graphics::Sampler,
+    /// If this is `Some`, the main program lights the scene with a directional light shining in this direction.
+    light_dir: Option<[f32; 3]>,
+    ambient: f32,
+}
+
+impl<'t> glium::uniforms::Uniforms for UniformsGL<'t> {
+    fn visit_values<'a, F: FnMut(&str, glium::uniforms::UniformValue<'a>)>(&'a self, mut f: F) {
+        use glium::uniforms::UniformValue;
+        f("combined", UniformValue::Mat4(self.combined));
+        f("model", UniformValue::Mat4(self.model));
+        f("tint", UniformValue::Vec4(self.tint));
+        f("outline_colour", UniformValue::Vec4(self.outline_colour));
+        f("override_enabled", UniformValue::Bool(self.override_colour.is_some()));
+        f("override_colour", UniformValue::Vec4(self.override_colour.unwrap_or([0.0; 4])));
+        f("tex", UniformValue::SrgbTexture2d(self.texture, Some(covalent_sampler_to_glium_sampler(&self.sampler))));
+        f("lighting_enabled", UniformValue::Bool(self.light_dir.is_some()));
+        f("light_dir", UniformValue::Vec3(self.light_dir.unwrap_or([0.0; 3])));
+        f("ambient", UniformValue::Float(self.ambient));
+    }
+}
+
+fn colour_uniform(c: &Colour) -> [f32; 4] {
+    [c.r(), c.g(), c.b(), c.a()]
+}
+
+/// The colour that leaves anything it tints unchanged.
+fn white() -> Colour {
+    Colour::new(1.0, 1.0, 1.0)
+}
+
+/// A renderable to be drawn, along with the properties of the node it belongs to.
+#[derive(Clone)]
+struct DrawItem {
+    renderable: Arc<Renderable>,
+    tint: Colour,
+    /// The node's transformation matrix, from local space into world space.
+    xform: Matrix4<f32>,
+}
+
+impl DrawItem {
+    /// Creates a draw item for the given node, if it has a renderable.
+    fn of_node(node: &covalent::scene::Node) -> Option<DrawItem> {
+        node.renderable.as_ref().map(|r| DrawItem {
+            renderable: Arc::clone(r),
+            tint: node.tint,
+            xform: node.get_xform(),
+        })
+    }
+
+    /// Creates a draw item that isn't attached to any node, so is not transformed or tinted.
+    fn untransformed(renderable: Renderable) -> DrawItem {
+        DrawItem {
+            renderable: Arc::new(renderable),
+            tint: white(),
+            xform: Matrix4::identity(),
+        }
+    }
+}
+
+/// The renderables in the scene, collected once per frame so that every render phase can share them without
+/// locking each node again.
+struct DrawList {
+    /// The items of every node with a renderable, in the order the nodes were created.
+    items: Vec<DrawItem>,
+    /// The items of the nodes that should be outlined; see `Node::outline`.
+    outlined: Vec<DrawItem>,
+    /// The text queued to be drawn this frame.
+    text: Vec<Text>,
+    /// The visible lines of the scene's debug console, along with their opacity.
+    console_lines: Vec<(String, f32)>,
+    /// The frame profiler's bar chart, or nothing if the profiler is hidden; see `FrameProfiler::graph`.
+    profiler_graph: Vec<Renderable>,
+}
+
+impl DrawList {
+    fn of_scene(scene: &Scene, text: Vec<Text>) -> DrawList {
+        let mut items = Vec::new();
+        let mut outlined = Vec::new();
+        for node in scene.iter_3d() {
+            let node = node.read().unwrap();
+            if let Some(item) = DrawItem::of_node(&node) {
+                if node.outline {
+                    outlined.push(item.clone());
+                }
+                items.push(item);
+            }
+        }
+        let console_lines = scene.debug_console.read().unwrap()
+            .visible_lines()
+            .map(|(line, alpha)| (line.text.clone(), alpha))
+            .collect();
+        let profiler = scene.frame_profiler.read().unwrap();
+        let profiler_graph = if profiler.is_visible() { profiler.graph() } else { Vec::new() };
+        DrawList { items, outlined, text, console_lines, profiler_graph }
+    }
+}
+
+impl BackendGL {
+    /// Compiles the built-in shader programs and allocates the buffers used to batch renderables together.
+    fn create_batch(&self) -> BatchGL {
+        let vertex_shader_src = r#"
+            #version 140
+
+            uniform mat4 combined;
+            uniform mat4 model;
+            uniform vec4 tint;
+
+            in vec3 position;
+            in uint col;
+            in vec2 uv;
+            in vec3 normal;
+            
+            out vec2 io_pos;
+            out vec4 io_col;
+            out vec2 io_uv;
+            out vec3 io_normal;
+
+            void main() {
+                gl_Position = combined * model * vec4(position, 1.0);
+                io_pos = position.xy;
+                io_uv = uv;
+                io_normal = mat3(model) * normal;
+                io_col = vec4(
+                    ((col & uint(0xFF000000)) >> 24) / 255.0f,
+                    ((col & uint(0x00FF0000)) >> 16) / 255.0f,
+                    ((col & uint(0x0000FF00)) >> 8) / 255.0f,
+                    ((col & uint(0x000000FF))) / 255.0f
+                ) * tint;
+            }
+        "#;
+        let fragment_shader_src = r#"
+            #version 140
+
+            uniform sampler2D tex;
+            uniform bool override_enabled;
+            uniform vec4 override_colour;
+            uniform bool lighting_enabled;
+            uniform vec3 light_dir;
+            uniform float ambient;
+
+            in vec2 io_pos;
+            in vec4 io_col;
+            in vec2 io_uv;
+            in vec3 io_normal;
+
+            out vec4 color;
+
+            void main() {
+                //color = vec4(io_pos.x*0.5+0.5, io_pos.y*0.5+0.5, 1.0, 1.0);
+                if (override_enabled) {
+                    color = override_colour;
+                } else {
+                    color = io_col * texture(tex, io_uv);
+                    if (lighting_enabled && length(io_normal) > 0.0) {
+                        float brightness = max(dot(normalize(io_normal), -normalize(light_dir)), ambient);
+                        color.rgb *= brightness;
+                    }
+                }
+            }
+        "#;
+
+        // Packs the depth of each fragment into the four 8-bit channels of the colour, most significant first, so it
+        // can be read back from an ordinary texture; see `depth_at_centre`.
+        let depth_fragment_shader_src = r#"
+            #version 140
+
+            out vec4 color;
+
+            void main() {
+                vec4 packed = fract(gl_FragCoord.z * vec4(1.0, 255.0, 65025.0, 16581375.0));
+                color = packed - packed.yzww * vec4(1.0 / 255.0, 1.0 / 255.0, 1.0 / 255.0, 0.0);
+            }
+        "#;
+
+        let outline_fragment_shader_src = r#"
+            #version 140
+
+            uniform vec4 outline_colour;
+
+            out vec4 color;
+
+            void main() {
+                color = outline_colour;
+            }
+        "#;
+
+        let program = glium::Program::from_source(
+            &self.display,
+            vertex_shader_src,
+            fragment_shader_src,
+            None,
+        )
+        .unwrap();
+        let outline_program = glium::Program::from_source(
+            &self.display,
+            vertex_shader_src,
+            outline_fragment_shader_src,
+            None,
+        )
+        .unwrap();
+        let depth_program = glium::Program::from_source(
+            &self.display,
+            vertex_shader_src,
+            depth_fragment_shader_src,
+            None,
+        )
+        .unwrap();
+
+        let vbo = glium::VertexBuffer::dynamic(
+            &self.display,
+            &vec![
+                Vertex {
+                    position: [0.0, 0.0, 0.0],
+                    col: 0xFFFFFFFF,
+                    uv: [0.0, 0.0],
+                    normal: [0.0, 0.0, 0.0]
+                };
+                self.config.max_verts
+            ],
+        )
+        .unwrap();
+        let ibo = glium::index::IndexBuffer::dynamic(
+            &self.display,
+            glium::index::PrimitiveType::TrianglesList,
+            &vec![0u32; self.config.max_inds],
+        )
+        .unwrap();
+        BatchGL {
+            vbo,
+            ibo,
+            program,
+            outline_program,
+            depth_program,
+            probe_colour: glium::texture::Texture2d::empty(&self.display, 1, 1).unwrap(),
+            probe_depth: glium::framebuffer::DepthRenderBuffer::new(
+                &self.display,
+                glium::texture::DepthFormat::I24,
+                1,
+                1,
+            )
+            .unwrap(),
+        }
+    }
+
+    fn apply_window_command(&self, command: graphics::WindowCommand) {
+        let gl_window = self.display.gl_window();
+        let window = gl_window.window();
+        match command {
+            graphics::WindowCommand::SetCursorIcon(icon) => {
+                // winit falls back to the default arrow on platforms that don't have the given icon.
+                window.set_cursor_icon(covalent_cursor_icon_to_winit_cursor_icon(icon));
+            }
+            graphics::WindowCommand::SetCursorGrab(grab) => {
+                if let Err(e) = window.set_cursor_grab(grab) {
+                    log::warn!("Could not set cursor grab to {}: {:?}", grab, e);
+                }
+            }
+            graphics::WindowCommand::SetCursorVisible(visible) => {
+                window.set_cursor_visible(visible);
+            }
+            graphics::WindowCommand::SetWindowMode(mode) => {
+                window.set_fullscreen(covalent_window_mode_to_winit_fullscreen(
+                    mode,
+                    window.available_monitors(),
+                    window.primary_monitor(),
+                    window.current_monitor(),
+                ));
+            }
+        }
+    }
+
+    fn execute_phase(
+        &self,
+        _name: &str,
+        draw_list: &DrawList,
+        phase: &PipelinePhase,
+        batch: &mut BatchGL,
+        frame: &mut glium::Frame,
+    ) {
+        let target = match phase {
+            PipelinePhase::Clear { target, .. }
+            | PipelinePhase::ClearGradient { target, .. }
+            | PipelinePhase::Render { target, .. }
+            | PipelinePhase::PostProcess { target, .. }
+            | PipelinePhase::Text { target, .. } => target,
+        };
+        match target {
+            RenderTarget::Window => self.execute_phase_on(draw_list, phase, batch, frame),
+            RenderTarget::Framebuffer { id } => {
+                let framebuffers = self.framebuffers.borrow();
+                match framebuffers.get(id) {
+                    Some(framebuffer) => {
+                        let mut surface = framebuffer.surface(&self.display);
+                        self.execute_phase_on(draw_list, phase, batch, &mut surface);
+                    }
+                    None => self.warn_missing("framebuffer", *id),
+                }
+            }
+        }
+    }
+
+    /// Clears the given framebuffer to transparent black, then draws the scene into it from the point of view of the
+    /// given camera; see `Scene::render_to_framebuffer`.
+    fn render_to_framebuffer(
+        &self,
+        framebuffer: FramebufferId,
+        camera: Arc<RwLock<dyn graphics::Camera + Send + Sync>>,
+        draw_list: &DrawList,
+        batch: &mut BatchGL,
+    ) {
+        let framebuffers = self.framebuffers.borrow();
+        let framebuffer = match framebuffers.get(&framebuffer) {
+            Some(framebuffer) => framebuffer,
+            None => return self.warn_missing("framebuffer", framebuffer),
+        };
+        let mut surface = framebuffer.surface(&self.display);
+        self.clear(&mut surface, Some(&Colour::from_rgba_u8(0, 0, 0, 0)), Some(f32::MAX), Some(0));
+        let settings = RenderSettings { camera: Some(camera), ..Default::default() };
+        self.render(&settings, draw_list, &mut surface, batch);
+    }
+
+    /// Executes the given phase, drawing to the given surface, which has been resolved from the phase's target.
+    fn execute_phase_on(
+        &self,
+        draw_list: &DrawList,
+        phase: &PipelinePhase,
+        batch: &mut BatchGL,
+        render_target: &mut impl glium::Surface,
+    ) {
+        match phase {
+            PipelinePhase::Clear { colour, depth, stencil, .. } => {
+                self.clear(render_target, colour.as_ref(), *depth, *stencil);
+            }
+            PipelinePhase::ClearGradient { top, bottom, .. } => {
+                self.clear_gradient(render_target, top, bottom, batch);
+            }
+            PipelinePhase::Render { settings, .. } => {
+                self.render(settings, draw_list, render_target, batch);
+            }
+            PipelinePhase::PostProcess { input, target, shader } => {
+                if *target == (RenderTarget::Framebuffer { id: *input }) {
+                    log::error!("post-processing phase cannot read from framebuffer {} while drawing to it", input);
+                    return;
+                }
+                self.post_process(*input, *shader, render_target, batch);
+            }
+            PipelinePhase::Text { console_font, .. } => {
+                self.render_text(draw_list, *console_font, render_target, batch);
+            }
+        }
+    }
+
+    /// Draws a triangle covering the whole render target with the given shader, sampling the colour channel of the
+    /// given framebuffer.
+    fn post_process(&self, input: FramebufferId, shader: ShaderId, render_target: &mut impl glium::Surface, batch: &mut BatchGL) {
+        let framebuffers = self.framebuffers.borrow();
+        let shaders = self.shaders.borrow();
+        let input = match framebuffers.get(&input) {
+            Some(input) => input,
+            None => return self.warn_missing("framebuffer", input),
+        };
+        let program = match shaders.get(&shader) {
+            Some(program) => program,
+            None => return self.warn_missing("shader", shader),
+        };
+
+        // A single triangle that covers the whole of normalised device coordinates, with (0, 0) to (1, 1) in
+        // texture coordinates mapped onto the render target.
+        let vertex = |x: f32, y: f32| RenderVertex {
+            pos: covalent::vec3(x, y, 0.0),
+            col: white(),
+            uv: covalent::vec2((x + 1.0) * 0.5, (y + 1.0) * 0.5),
+            normal: covalent::vec3(0.0, 0.0, 0.0),
+        };
+        let renderables = vec![
+            DrawItem::untransformed(Renderable::Triangle(vertex(-1.0, -1.0), vertex(3.0, -1.0), vertex(-1.0, 3.0))),
+        ];
+
+        let uniforms = UniformsGL {
+            combined: IDENTITY,
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &input.colour,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        let params: glium::DrawParameters = Default::default();
+        self.draw_renderables(renderables.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, program, &uniforms, &params);
+    }
+
+    fn clear(&self, render_target: &mut impl glium::Surface, colour: Option<&Colour>, depth: Option<f32>, stencil: Option<i32>) {
+        let colour = colour.map(|c| (c.r(), c.g(), c.b(), c.a()));
+        render_target.clear(None, colour, false, depth, stencil);
+    }
+
+    /// Clears the depth buffer, then draws a quad covering the whole render target, coloured with a vertical
+    /// gradient. The colours of the vertices are interpolated over the height of the target.
+    fn clear_gradient(&self, render_target: &mut impl glium::Surface, top: &Colour, bottom: &Colour, batch: &mut BatchGL) {
+        render_target.clear_depth(std::f32::MAX);
+
+        let vertex = |x: f32, y: f32, col: Colour| RenderVertex {
+            pos: covalent::vec3(x, y, 0.0),
+            col,
+            uv: covalent::vec2(0.0, 0.0),
+            normal: covalent::vec3(0.0, 0.0, 0.0),
+        };
+        let renderables = vec![
+            DrawItem::untransformed(Renderable::Triangle(vertex(-1.0, -1.0, *bottom), vertex(1.0, -1.0, *bottom), vertex(1.0, 1.0, *top))),
+            DrawItem::untransformed(Renderable::Triangle(vertex(-1.0, -1.0, *bottom), vertex(1.0, 1.0, *top), vertex(-1.0, 1.0, *top))),
+        ];
+
+        let uniforms = UniformsGL {
+            combined: IDENTITY,
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &self.white_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        // The default parameters neither test against nor write to the depth buffer, so the scene is drawn over the gradient.
+        let params: glium::DrawParameters = Default::default();
+        self.draw_renderables(renderables.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.program, &uniforms, &params);
+    }
+
+    fn render(
+        &self,
+        settings: &RenderSettings,
+        draw_list: &DrawList,
+        render_target: &mut impl glium::Surface,
+        batch: &mut BatchGL,
+    ) {
+        let combined_matrix = settings.combined_matrix();
+        let frustum = if settings.frustum_cull {
+            Some(graphics::Frustum::from_matrix(combined_matrix))
+        } else {
+            None
+        };
+        let visible = |item: &&DrawItem| frustum.as_ref().map_or(true, |frustum| self.is_visible(item, frustum));
+        let mut items = draw_list.items.iter().filter(visible).cloned().collect::<Vec<_>>();
+        if settings.transparent {
+            items = self.sort_back_to_front(items, combined_matrix);
+        }
+
+        use covalent::cgmath::Matrix;
+        let c = combined_matrix.transpose();
+        let combined = [
+            [c.x.x, c.y.x, c.z.x, c.w.x],
+            [c.x.y, c.y.y, c.z.y, c.w.y],
+            [c.x.z, c.y.z, c.z.z, c.w.z],
+            [c.x.w, c.y.w, c.z.w, c.w.w],
+        ];
+        let uniforms = UniformsGL {
+            combined,
+            model: IDENTITY,
+            tint: colour_uniform(&settings.tint),
+            outline_colour: colour_uniform(&settings.outline_colour),
+            override_colour: settings.override_colour.as_ref().map(colour_uniform),
+            texture: &self.white_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: settings.light_dir.map(|d| [d.x, d.y, d.z]),
+            ambient: settings.ambient,
+        };
+
+        let (width, height) = render_target.get_dimensions();
+        let (offset, size) = settings.viewport(covalent::vec2(width, height));
+        let viewport = glium::Rect {
+            left: offset.x,
+            bottom: offset.y,
+            width: size.x,
+            height: size.y,
+        };
+        if settings.target_aspect.is_some() {
+            for bar in letterbox_bars(width, height, &viewport) {
+                render_target.clear(Some(&bar), Some((0.0, 0.0, 0.0, 1.0)), false, None, None);
+            }
+        }
+        if let Some(c) = &settings.clear_colour {
+            render_target.clear(Some(&viewport), Some((c.r(), c.g(), c.b(), c.a())), false, Some(std::f32::MAX), None);
+        }
+
+        // Find the point under the crosshair now, since drawing the scene consumes the items.
+        let readout = settings.crosshair.and_then(|crosshair| crosshair.readout_font).and_then(|_| {
+            let depth = self.depth_at_centre(&items, combined_matrix, viewport, batch)?;
+            let matrices = graphics::CameraMatrices { combined: combined_matrix, inverse: combined_matrix.invert()? };
+            // The probe uses the default depth range of 0 to 1, so convert back into normalised device coordinates.
+            Some(matrices.screen_to_point(covalent::vec2(0.0, 0.0), depth * 2.0 - 1.0))
+        });
+
+        let mut params: glium::DrawParameters = Default::default();
+        params.depth.test = glium::DepthTest::IfLess;
+        // Blended renderables don't hide what is drawn after them; see `BlendMode`.
+        params.depth.write = settings.effective_blend() == graphics::BlendMode::Opaque;
+        params.depth.range = settings.depth_range;
+        params.viewport = Some(viewport);
+        params.blend = covalent_blend_to_glium_blend(settings.effective_blend());
+        params.polygon_mode = covalent_polygon_mode_to_glium_polygon_mode(*settings.polygon_mode.read().unwrap());
+        params.backface_culling = covalent_cull_mode_to_glium_backface_culling_mode(settings.cull_mode);
+
+        let shaders = self.shaders.borrow();
+        let program = match settings.shader {
+            Some(shader) => match shaders.get(&shader) {
+                Some(program) => program,
+                None => {
+                    self.warn_missing("shader", shader);
+                    &batch.program
+                }
+            },
+            None => &batch.program,
+        };
+        self.draw_renderables(items.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, program, &uniforms, &params);
+
+        let outlined = draw_list.outlined.iter().filter(visible).cloned().collect::<Vec<_>>();
+        if !outlined.is_empty() {
+            self.render_outlines(settings, &outlined, render_target, batch, &uniforms, viewport);
+        }
+
+        if let Some(crosshair) = &settings.crosshair {
+            self.render_crosshair(crosshair, readout, render_target, batch, viewport);
+        }
+    }
+
+    /// Computes the bounding box of the given item in world space, if its bounds are known.
+    fn world_bounds(&self, item: &DrawItem) -> Option<graphics::Aabb> {
+        let bounds = match &*item.renderable {
+            Renderable::Mesh(mesh_id) | Renderable::TexturedMesh { mesh_id, .. } => {
+                self.meshes.borrow().get(mesh_id).and_then(|mesh| mesh.bounds)
+            }
+            r => r.bounds(),
+        };
+        bounds.map(|bounds| bounds.transformed(&item.xform))
+    }
+
+    /// Checks whether any part of the given item might be inside the given frustum.
+    /// Items whose bounds are unknown, such as those referring to deleted meshes, are assumed to be visible.
+    fn is_visible(&self, item: &DrawItem, frustum: &graphics::Frustum) -> bool {
+        self.world_bounds(item).map_or(true, |bounds| frustum.intersects(&bounds))
+    }
+
+    /// Sorts the given items so that those furthest from the camera are drawn first, so translucent items blend with
+    /// whatever is behind them. Items are compared by the distance from the camera with the given combined matrix to
+    /// the centre of their bounds (or the origin of their node, if their bounds are unknown). Orthographic cameras are
+    /// infinitely far away, so items are compared by how far they are along the camera's direction instead. Items at
+    /// the same distance stay in scene order.
+    fn sort_back_to_front(&self, items: Vec<DrawItem>, combined: Matrix4<f32>) -> Vec<DrawItem> {
+        // The camera is the point that the projection sends to infinity, i.e. to a clip space position with a `w` of
+        // zero. For orthographic cameras this is a direction rather than a point; it points forwards.
+        let eye = combined.invert().map_or(Vector4::new(0.0, 0.0, 0.0, 0.0), |inverse| inverse * Vector4::new(0.0, 0.0, 1.0, 0.0));
+        let perspective = eye.w.abs() > 1e-6 * eye.truncate().magnitude();
+        let mut keyed = items.into_iter().map(|item| {
+            let centre = match self.world_bounds(&item) {
+                Some(bounds) => (bounds.min + bounds.max) * 0.5,
+                None => item.xform.w.truncate(),
+            };
+            let distance = if perspective {
+                (centre - eye.truncate() / eye.w).magnitude2()
+            } else {
+                centre.dot(eye.truncate())
+            };
+            (distance, item)
+        }).collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Draws the text queued this frame in screen space, followed by the debug console if a font is given for it.
+    /// Glyphs are rendered into the glyph cache as needed, then drawn as textured quads.
+    fn render_text(&self, draw_list: &DrawList, console_font: Option<FontId>, render_target: &mut impl glium::Surface, batch: &mut BatchGL) {
+        let mut texts = draw_list.text.clone();
+        if let Some(font) = console_font {
+            for (i, (line, alpha)) in draw_list.console_lines.iter().enumerate() {
+                texts.push(Text {
+                    font,
+                    string: line.clone(),
+                    pos: covalent::vec2(CONSOLE_MARGIN, CONSOLE_MARGIN + i as f32 * CONSOLE_TEXT_SIZE),
+                    size: CONSOLE_TEXT_SIZE,
+                    colour: white().with_alpha(*alpha),
+                });
+            }
+        }
+        self.draw_text(&texts, render_target, batch);
+    }
+
+    /// Draws the given text in screen space, on top of everything else.
+    fn draw_text(&self, texts: &[Text], render_target: &mut impl glium::Surface, batch: &mut BatchGL) {
+        if texts.is_empty() {
+            return;
+        }
+
+        let fonts = self.fonts.borrow();
+        let mut cache = self.glyph_cache.borrow_mut();
+        let mut glyphs = Vec::new();
+        for text in texts {
+            let font = match fonts.get(&text.font) {
+                Some(font) => font,
+                None => {
+                    self.warn_missing("font", text.font);
+                    continue;
+                }
+            };
+            let scale = rusttype::Scale::uniform(text.size);
+            // `pos` is the top left corner of the text, but glyphs are laid out relative to the baseline.
+            let baseline = text.pos.y + font.v_metrics(scale).ascent;
+            for glyph in font.layout(&text.string, scale, rusttype::point(text.pos.x, baseline)) {
+                cache.queue_glyph(text.font as usize, glyph.clone());
+                glyphs.push((text.font as usize, glyph, text.colour));
+            }
+        }
+
+        let glyph_texture = &self.glyph_texture;
+        let cached = cache.cache_queued(|rect, coverage| {
+            let rgba = coverage.iter().flat_map(|&alpha| vec![255, 255, 255, alpha]).collect::<Vec<u8>>();
+            glyph_texture.write(
+                glium::Rect {
+                    left: rect.min.x,
+                    bottom: rect.min.y,
+                    width: rect.width(),
+                    height: rect.height(),
+                },
+                glium::texture::RawImage2d::from_raw_rgba(rgba, (rect.width(), rect.height())),
+            );
+        });
+        if let Err(e) = cached {
+            log::warn!("Could not fit this frame's text into the glyph cache: {:?}", e);
+            return;
+        }
+
+        // Convert pixels from the top left corner of the render target into normalised device coordinates.
+        let (width, height) = render_target.get_dimensions();
+        let to_ndc = |p: rusttype::Point<i32>| (p.x as f32 / width as f32 * 2.0 - 1.0, 1.0 - p.y as f32 / height as f32 * 2.0);
+        let renderables = glyphs.iter().filter_map(|(font, glyph, colour)| {
+            // Glyphs such as spaces have nothing to draw.
+            let (uv, screen) = cache.rect_for(*font, glyph).ok().flatten()?;
+            let (left, top) = to_ndc(screen.min);
+            let (right, bottom) = to_ndc(screen.max);
+            let vertex = |x: f32, y: f32, u: f32, v: f32| RenderVertex {
+                pos: covalent::vec3(x, y, 0.0),
+                col: *colour,
+                uv: covalent::vec2(u, v),
+                normal: covalent::vec3(0.0, 0.0, 0.0),
+            };
+            Some(DrawItem::untransformed(Renderable::Quad(
+                vertex(left, bottom, uv.min.x, uv.max.y),
+                vertex(right, bottom, uv.max.x, uv.max.y),
+                vertex(right, top, uv.max.x, uv.min.y),
+                vertex(left, top, uv.min.x, uv.min.y),
+            )))
+        }).collect::<Vec<_>>();
+
+        let uniforms = UniformsGL {
+            combined: IDENTITY,
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &self.glyph_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        // The default parameters disable depth testing, so text is always drawn on top.
+        let mut params: glium::DrawParameters = Default::default();
+        params.blend = glium::Blend::alpha_blending();
+        self.draw_renderables(renderables.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.program, &uniforms, &params);
+    }
+
+    /// Draws the frame profiler's bar chart in the bottom left corner of the render target, on top of everything else.
+    fn render_profiler_graph(&self, graph: &[Renderable], render_target: &mut impl glium::Surface, batch: &mut BatchGL) {
+        if graph.is_empty() {
+            return;
+        }
+
+        // The chart fills the unit square, so scale it to its size in pixels, then convert into normalised device
+        // coordinates, which range from -1 to 1 across the render target.
+        let (width, height) = render_target.get_dimensions();
+        let to_ndc = |pixels: f32, size: u32| pixels * 2.0 / size as f32;
+        let xform = Matrix4::from_translation(Vector3::new(
+            to_ndc(PROFILER_GRAPH_MARGIN, width) - 1.0,
+            to_ndc(PROFILER_GRAPH_MARGIN, height) - 1.0,
+            0.0,
+        )) * Matrix4::from_nonuniform_scale(to_ndc(PROFILER_GRAPH_WIDTH, width), to_ndc(PROFILER_GRAPH_HEIGHT, height), 1.0);
+        let renderables = graph.iter().map(|r| DrawItem {
+            renderable: Arc::new(r.clone()),
+            tint: white(),
+            xform,
+        }).collect::<Vec<_>>();
+
+        let uniforms = UniformsGL {
+            combined: IDENTITY,
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &self.white_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        // The default parameters disable depth testing, so the chart is always drawn on top.
+        let params: glium::DrawParameters = Default::default();
+        self.draw_renderables(renderables.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.program, &uniforms, &params);
+    }
+
+    /// Draws a crosshair in screen space at the centre of the viewport, on top of everything else.
+    /// If the crosshair has a readout font and a point is given, the point is written beside the crosshair.
+    fn render_crosshair(
+        &self,
+        crosshair: &graphics::Crosshair,
+        readout: Option<Point3<f32>>,
+        render_target: &mut impl glium::Surface,
+        batch: &mut BatchGL,
+        viewport: glium::Rect,
+    ) {
+        // Convert pixel sizes into normalised device coordinates, which range from -1 to 1 across the viewport.
+        let (width, height) = (viewport.width, viewport.height);
+        let size_x = crosshair.size * 2.0 / width as f32;
+        let size_y = crosshair.size * 2.0 / height as f32;
+        let thickness_x = crosshair.thickness / width as f32;
+        let thickness_y = crosshair.thickness / height as f32;
+
+        let vertex = |x: f32, y: f32| RenderVertex {
+            pos: covalent::vec3(x, y, 0.0),
+            col: crosshair.colour,
+            uv: covalent::vec2(0.0, 0.0),
+            normal: covalent::vec3(0.0, 0.0, 0.0),
+        };
+        let quad = |x0: f32, y0: f32, x1: f32, y1: f32| vec![
+            DrawItem::untransformed(Renderable::Triangle(vertex(x0, y0), vertex(x1, y0), vertex(x1, y1))),
+            DrawItem::untransformed(Renderable::Triangle(vertex(x0, y0), vertex(x1, y1), vertex(x0, y1))),
+        ];
+        let mut renderables = quad(-size_x, -thickness_y, size_x, thickness_y);
+        renderables.append(&mut quad(-thickness_x, -size_y, thickness_x, size_y));
+
+        let uniforms = UniformsGL {
+            combined: IDENTITY,
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &self.white_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        // The default parameters disable depth testing, so the crosshair is always drawn on top.
+        let mut params: glium::DrawParameters = Default::default();
+        params.viewport = Some(viewport);
+        self.draw_renderables(renderables.into_iter(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.program, &uniforms, &params);
+
+        if let (Some(font), Some(point)) = (crosshair.readout_font, readout) {
+            // Text is positioned from the top left corner of the render target, but the viewport from the bottom left.
+            let (_, target_height) = render_target.get_dimensions();
+            let centre_x = viewport.left as f32 + width as f32 * 0.5;
+            let centre_y = target_height as f32 - (viewport.bottom as f32 + height as f32 * 0.5);
+            let text = Text {
+                font,
+                string: format!("{:.2}, {:.2}, {:.2}", point.x, point.y, point.z),
+                pos: covalent::vec2(centre_x + crosshair.size + CROSSHAIR_READOUT_MARGIN, centre_y + crosshair.size + CROSSHAIR_READOUT_MARGIN),
+                size: CROSSHAIR_READOUT_SIZE,
+                colour: crosshair.colour,
+            };
+            self.draw_text(&[text], render_target, batch);
+        }
+    }
+
+    /// Finds the depth of the nearest of the given items at the centre of the viewport, from 0 on the near plane to
+    /// 1 on the far plane, or `None` if nothing covers the centre.
+    ///
+    /// The items are drawn again into a single pixel with `depth_program`, which is then read back from the GPU.
+    /// Reading waits for every earlier draw call to finish, so this is slow.
+    fn depth_at_centre(&self, items: &[DrawItem], combined: Matrix4<f32>, viewport: glium::Rect, batch: &mut BatchGL) -> Option<f32> {
+        // Scale the view so that the pixel at the centre of the viewport covers the whole of normalised device
+        // coordinates. Only x and y are scaled, so depths are unchanged.
+        let zoom = Matrix4::from_nonuniform_scale(viewport.width as f32, viewport.height as f32, 1.0) * combined;
+        let uniforms = UniformsGL {
+            combined: zoom.into(),
+            model: IDENTITY,
+            tint: colour_uniform(&white()),
+            outline_colour: colour_uniform(&white()),
+            override_colour: None,
+            texture: &self.white_texture,
+            sampler: graphics::Sampler::default(),
+            light_dir: None,
+            ambient: 1.0,
+        };
+        let mut params: glium::DrawParameters = Default::default();
+        params.depth.test = glium::DepthTest::IfLess;
+        params.depth.write = true;
+
+        use glium::Surface;
+        let mut surface = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&self.display, &batch.probe_colour, &batch.probe_depth).unwrap();
+        // White unpacks to just over 1, so it can't be mistaken for a real depth.
+        surface.clear_color_and_depth((1.0, 1.0, 1.0, 1.0), 1.0);
+        self.draw_renderables(items.iter().cloned(), &mut surface, &mut batch.vbo, &mut batch.ibo, &batch.depth_program, &uniforms, &params);
+        drop(surface);
+
+        let pixels: Vec<Vec<(u8, u8, u8, u8)>> = batch.probe_colour.read();
+        let (r, g, b, a) = pixels[0][0];
+        let depth = r as f32 / 255.0 + g as f32 / 65025.0 + b as f32 / 16581375.0 + a as f32 / 4228250625.0;
+        if depth < 1.0 {
+            Some(depth)
+        } else {
+            None
+        }
+    }
+
+    /// Draws the given renderables, batching together as many as possible into each draw call.
+    ///
+    /// Sprites sample from their own texture, so each run of consecutive sprites with the same texture is drawn in
+    /// separate draw calls from the renderables around it, as quads facing the camera of the `combined` uniform.
+    fn draw_renderables(
+        &self,
+        it: impl Iterator<Item = DrawItem>,
+        render_target: &mut impl glium::Surface,
+        batch_vbo: &mut glium::VertexBuffer<Vertex>,
+        batch_ibo: &mut glium::IndexBuffer<u32>,
+        program: &glium::Program,
+        uniforms: &UniformsGL,
+        params: &glium::DrawParameters,
+    ) {
+        let sprite_texture = |item: &DrawItem| match &*item.renderable {
+            Renderable::Sprite { texture_id, .. } => Some(*texture_id),
+            _ => None,
+        };
+        let axes = graphics::camera_axes(Matrix4::from(uniforms.combined));
+        let textures = self.textures.borrow();
+
+        let mut it = it.peekable();
+        while let Some(first) = it.peek() {
+            let texture_id = sprite_texture(first);
+            let mut run = Vec::new();
+            while let Some(item) = it.peek() {
+                if sprite_texture(item) != texture_id {
+                    break;
+                }
+                run.push(it.next().unwrap());
+            }
+            match texture_id {
+                None => self.draw_batches(run.into_iter(), render_target, batch_vbo, batch_ibo, program, uniforms, params),
+                Some(texture_id) => {
+                    let uniforms = self.textured(&textures, texture_id, uniforms);
+                    let quads = run.iter().map(|item| sprite_quad(item, axes));
+                    self.draw_batches(quads, render_target, batch_vbo, batch_ibo, program, &uniforms, params);
+                }
+            }
+        }
+    }
+
+    /// Draws the given renderables, which must not contain sprites, batching together as many as possible into each
+    /// draw call.
+    fn draw_batches(
+        &self,
+        it: impl Iterator<Item = DrawItem>,
+        render_target: &mut impl glium::Surface,
+        batch_vbo: &mut glium::VertexBuffer<Vertex>,
+        batch_ibo: &mut glium::IndexBuffer<u32>,
+        program: &glium::Program,
+        uniforms: &UniformsGL,
+        params: &glium::DrawParameters,
+    ) {
+        let mut it = it.peekable();
+        let mut draw_calls = 0u32;
+        let mut batched_verts = 0usize;
+        while let Some(_) = it.peek() {
+            draw_calls += 1;
+            let mut vbo = batch_vbo.map_write();
+            let mut ibo = batch_ibo.map_write();
+            let (idx, verts) = self.render_lots(
+                &mut it,
+                &mut vbo,
+                &mut ibo,
+                render_target,
+                program,
+                uniforms,
+                params,
+            );
+            drop(vbo);
+            drop(ibo);
+            batched_verts += verts;
+
+            if idx > 0 {
+                render_target
+                    .draw(
+                        &*batch_vbo,
+                        &batch_ibo.slice(0..idx).unwrap(),
+                        program,
+                        uniforms,
+                        params,
+                    )
+                    .unwrap();
+            }
+        }
+        log::trace!("Executed {} draw calls, with {} batched vertices", draw_calls, batched_verts);
+    }
+
+    /// Draws an outline around the silhouette of the given renderables.
+    ///
+    /// This uses the stencil buffer. First, every pixel covered by an outlined renderable is marked in the stencil
+    /// buffer. Then, the renderables are drawn again as thick wireframes in the outline colour, but only where the
+    /// stencil buffer is not marked, so just the part of each line that sticks out past the silhouette is visible.
+    fn render_outlines(
+        &self,
+        settings: &RenderSettings,
+        outlined: &[DrawItem],
+        render_target: &mut impl glium::Surface,
+        batch: &mut BatchGL,
+        uniforms: &UniformsGL,
+        viewport: glium::Rect,
+    ) {
+        use glium::draw_parameters::{StencilOperation, StencilTest};
+
+        render_target.clear_stencil(0);
+
+        let mut params: glium::DrawParameters = Default::default();
+        params.viewport = Some(viewport);
+        params.color_mask = (false, false, false, false);
+        params.stencil = outline_stencil(StencilTest::AlwaysPass, StencilOperation::Replace);
+        self.draw_renderables(outlined.iter().cloned(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.program, uniforms, &params);
+
+        let mut params: glium::DrawParameters = Default::default();
+        params.viewport = Some(viewport);
+        params.polygon_mode = glium::PolygonMode::Line;
+        params.line_width = Some(settings.outline_width * 2.0);
+        params.stencil = outline_stencil(StencilTest::IfNotEqual { mask: 0xFF }, StencilOperation::Keep);
+        self.draw_renderables(outlined.iter().cloned(), render_target, &mut batch.vbo, &mut batch.ibo, &batch.outline_program, uniforms, &params);
+    }
+
+    /// Render as many things from the given iterator as we can in the current batch, returning the (exclusive) max index
+    /// we wrote to and the amount of vertices we wrote.
+    fn render_lots(
+        &self,
+        it: &mut std::iter::Peekable<impl Iterator<Item = DrawItem>>,
+        vbo: &mut glium::buffer::WriteMapping<[Vertex]>,
+        ibo: &mut glium::buffer::WriteMapping<[u32]>,
+        render_target: &mut impl glium::Surface,
+        program: &glium::Program,
+        uniforms: &UniformsGL,
+        params: &glium::DrawParameters,
+    ) -> (usize, usize) {
+        let mut writer = BatchWriter::new(vbo, ibo, &self.config);
+        loop {
+            match it.peek() {
+                Some(DrawItem { renderable, tint, xform }) => {
+                    // Triangles and quads from many nodes share a draw call, so the node's tint and transform are applied to the vertices here.
+                    // Polygons without normals are given the normal of their face, just like meshes.
+                    let apply_node = |v: &RenderVertex, face: Vector3<f32>| {
+                        let normal = if v.normal == covalent::vec3(0.0, 0.0, 0.0) { face } else { v.normal };
+                        conv(&RenderVertex {
+                            pos: (*xform * v.pos.extend(1.0)).truncate(),
+                            col: Colour::multiply(v.col, *tint),
+                            normal: (*xform * normal.extend(0.0)).truncate(),
+                            ..*v
+                        })
+                    };
+                    match &**renderable {
+                        Renderable::None => {
+                            it.next();
+                        }
+                        Renderable::Triangle(v0, v1, v2) => {
+                            if !writer.has_room(3, 3) {
+                                break; // Do not consume the triangle, leave it to the next call to render_lots.
+                            }
+                            let face = graphics::face_normal(v0.pos, v1.pos, v2.pos);
+                            writer.push(&[apply_node(v0, face), apply_node(v1, face), apply_node(v2, face)], &[0, 1, 2]);
+                            it.next();
+                        }
+                        Renderable::Quad(v0, v1, v2, v3) => {
+                            if !writer.has_room(4, 6) {
+                                break;
+                            }
+                            let face = graphics::face_normal(v0.pos, v1.pos, v2.pos);
+                            writer.push(
+                                &[apply_node(v0, face), apply_node(v1, face), apply_node(v2, face), apply_node(v3, face)],
+                                &[0, 1, 2, 0, 2, 3],
+                            );
+                            it.next();
+                        }
+                        Renderable::Mesh(_) | Renderable::TexturedMesh { .. } if writer.vertices > 0 => {
+                            // Meshes are drawn straight away, so draw the polygons before them first to keep them in order.
+                            break;
+                        }
+                        Renderable::Mesh(i) => {
+                            self.draw_mesh(*i, None, tint, xform, render_target, program, uniforms, params);
+                            it.next();
+                        }
+                        Renderable::TexturedMesh { mesh_id, texture_id } => {
+                            self.draw_mesh(*mesh_id, Some(*texture_id), tint, xform, render_target, program, uniforms, params);
+                            it.next();
+                        }
+                        Renderable::Sprite { .. } => unreachable!("sprites are converted into quads by draw_renderables"),
+                    }
+                }
+                None => break,
+            }
+        }
+        (writer.indices, writer.vertices)
+    }
+
+    /// Draws a single mesh with the given node tint and transform, sampling from the given texture if there is one.
+    fn draw_mesh(
+        &self,
+        mesh_id: i64,
+        texture_id: Option<TextureId>,
+        tint: &Colour,
+        xform: &Matrix4<f32>,
+        render_target: &mut impl glium::Surface,
+        program: &glium::Program,
+        uniforms: &UniformsGL,
+        params: &glium::DrawParameters,
+    ) {
+        let meshes = self.meshes.borrow();
+        let textures = self.textures.borrow();
+        let mesh = match meshes.get(&mesh_id) {
+            Some(mesh) => mesh,
+            None => {
+                // Meshes with smaller IDs than the next one have been deleted, which is allowed. Other IDs are stale
+                // or corrupt, e.g. from an old save file.
+                if mesh_id < 0 || mesh_id >= self.next_mesh_id.get() {
+                    self.warn_missing("mesh", mesh_id);
+                }
+                return;
+            }
+        };
+        // Meshes are drawn one at a time, so the node's tint and transform can be passed as uniforms instead.
+        let t = uniforms.tint;
+        let textured = match texture_id {
+            Some(texture_id) => self.textured(&textures, texture_id, uniforms),
+            None => UniformsGL { ..*uniforms },
+        };
+        let uniforms = UniformsGL {
+            model: (*xform).into(),
+            tint: [t[0] * tint.r(), t[1] * tint.g(), t[2] * tint.b(), t[3] * tint.a()],
+            ..textured
+        };
+        render_target
+            .draw(&mesh.vbo, &mesh.ibo, program, &uniforms, params)
+            .unwrap();
+    }
+}
+
+impl BackendGL {
+    /// Creates a copy of the given uniforms that samples from the given texture, or leaves the texture unchanged
+    /// (after logging a warning) if it doesn't exist.
+    fn textured<'a>(&self, textures: &'a HashMap<TextureId, TextureGL>, texture_id: TextureId, uniforms: &UniformsGL<'a>) -> UniformsGL<'a> {
+        match textures.get(&texture_id) {
+            Some(texture) => UniformsGL {
+                texture: &texture.texture,
+                sampler: texture.sampler,
+                ..*uniforms
+            },
+            None => {
+                self.warn_missing("texture", texture_id);
+                UniformsGL { ..*uniforms }
+            }
+        }
+    }
+
+    /// Reads and compiles a shader program, returning a description of the error if either step fails.
+    fn compile_shader(&self, source: &ShaderSource) -> Result<glium::Program, String> {
+        let (vertex_src, fragment_src) = source.read().map_err(|e| e.to_string())?;
+        glium::Program::from_source(&self.display, &vertex_src, &fragment_src, None).map_err(|e| e.to_string())
+    }
+
+    /// Recompiles the given shader programs from their files. If a program fails to compile, the error is logged
+    /// and the previous version of the program is kept, so a mistake in a shader doesn't stop the game.
+    fn recompile_shaders(&self, ids: Vec<ShaderId>) {
+        let files = self.shader_files.borrow();
+        for id in ids {
+            let source = match files.get(&id) {
+                Some(source) => source,
+                None => continue,
+            };
+            match self.compile_shader(source) {
+                Ok(program) => {
+                    log::info!("Reloaded shader {}", id);
+                    self.shaders.borrow_mut().insert(id, program);
+                },
+                Err(e) => log::error!("Could not reload shader {}, keeping the previous version: {}", id, e),
+            }
+        }
+    }
+
+    /// Finds the shader programs whose files have been modified since we last checked.
+    #[cfg(feature = "watch_shaders")]
+    fn modified_shaders(&self) -> Vec<ShaderId> {
+        let files = self.shader_files.borrow();
+        let mut modified = self.shader_modified.borrow_mut();
+        files.iter().filter_map(|(id, source)| {
+            let time = last_modified(source);
+            if modified.insert(*id, time) != Some(time) {
+                Some(*id)
+            } else {
+                None
+            }
+        }).collect()
+    }
+
+    /// Logs a warning that a renderable or pipeline phase referred to a mesh, texture or framebuffer that doesn't exist,
+    /// unless we've warned about it already.
+    fn warn_missing(&self, kind: &'static str, id: i64) {
+        if self.missing_ids.borrow_mut().insert((kind, id)) {
+            log::warn!("tried to use {} {}, which was never created", kind, id);
+        }
+    }
+}
+
+/// Retrieves the most recent time that either of a shader program's files was modified, or `None` if a file can't
+/// be read, e.g. because an editor is part way through saving it.
+#[cfg(feature = "watch_shaders")]
+fn last_modified(source: &ShaderSource) -> Option<std::time::SystemTime> {
+    let (vertex, fragment) = source.paths()?;
+    let vertex = std::fs::metadata(vertex).and_then(|m| m.modified()).ok()?;
+    let fragment = std::fs::metadata(fragment).and_then(|m| m.modified()).ok()?;
+    Some(vertex.max(fragment))
+}
+
+/// Converts a draw item containing a sprite into a quad in world space that faces the camera, given the camera's right
+/// and up directions. The quad keeps the item's tint, but not the rest of its transform.
+fn sprite_quad(item: &DrawItem, (right, up): (Vector3<f32>, Vector3<f32>)) -> DrawItem {
+    let (size, anchor) = match &*item.renderable {
+        Renderable::Sprite { size, anchor, .. } => (*size, *anchor),
+        _ => unreachable!("only sprites can be converted into quads"),
+    };
+    let pos = item.xform.w.truncate();
+    let (min, max) = anchor.corners(size);
+    let vertex = |x: f32, y: f32, u: f32, v: f32| RenderVertex {
+        pos: pos + right * x + up * y,
+        col: white(),
+        uv: covalent::vec2(u, v),
+        normal: covalent::vec3(0.0, 0.0, 0.0),
+    };
+    DrawItem {
+        renderable: Arc::new(Renderable::Quad(
+            vertex(min.x, min.y, 0.0, 0.0),
+            vertex(max.x, min.y, 1.0, 0.0),
+            vertex(max.x, max.y, 1.0, 1.0),
+            vertex(min.x, max.y, 0.0, 1.0),
+        )),
+        tint: item.tint,
+        xform: Matrix4::identity(),
+    }
+}
+
+/// Computes the regions of a render target of the given size that lie outside the given viewport.
+/// The viewport is assumed to be centred, as produced by `RenderSettings::viewport`.
+fn letterbox_bars(width: u32, height: u32, viewport: &glium::Rect) -> Vec<glium::Rect> {
+    let mut bars = Vec::new();
+    if viewport.left > 0 {
+        bars.push(glium::Rect { left: 0, bottom: 0, width: viewport.left, height });
+        let right = viewport.left + viewport.width;
+        bars.push(glium::Rect { left: right, bottom: 0, width: width - right, height });
+    }
+    if viewport.bottom > 0 {
+        bars.push(glium::Rect { left: 0, bottom: 0, width, height: viewport.bottom });
+        let top = viewport.bottom + viewport.height;
+        bars.push(glium::Rect { left: 0, bottom: top, width, height: height - top });
+    }
+    bars
+}
+
+/// Creates stencil parameters for drawing outlines, which compare against the reference value 1 for both
+/// clockwise and anticlockwise faces.
+fn outline_stencil(
+    test: glium::draw_parameters::StencilTest,
+    operation: glium::draw_parameters::StencilOperation,
+) -> glium::draw_parameters::Stencil {
+    use glium::draw_parameters::StencilOperation;
+    glium::draw_parameters::Stencil {
+        test_clockwise: test,
+        reference_value_clockwise: 1,
+        write_mask_clockwise: 0xFF,
+        fail_operation_clockwise: StencilOperation::Keep,
+        pass_depth_fail_operation_clockwise: operation,
+        depth_pass_operation_clockwise: operation,
+        test_counter_clockwise: test,
+        reference_value_counter_clockwise: 1,
+        write_mask_counter_clockwise: 0xFF,
+        fail_operation_counter_clockwise: StencilOperation::Keep,
+        pass_depth_fail_operation_counter_clockwise: operation,
+        depth_pass_operation_counter_clockwise: operation,
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    col: u32,
+    uv: [f32; 2],
+    normal: [f32; 3],
+}
+glium::implement_vertex!(Vertex, position, col, uv, normal);
+
+/// The width and height in pixels of the texture that caches rendered glyphs.
+const GLYPH_CACHE_SIZE: u32 = 1024;
+/// The height in pixels of each line of the debug console.
+const CONSOLE_TEXT_SIZE: f32 = 16.0;
+/// The distance in pixels between the debug console and the top left corner of the screen.
+const CONSOLE_MARGIN: f32 = 8.0;
+/// The width in pixels of the frame profiler's bar chart.
+const PROFILER_GRAPH_WIDTH: f32 = 300.0;
+/// The height in pixels of the frame profiler's bar chart.
+const PROFILER_GRAPH_HEIGHT: f32 = 100.0;
+/// The distance in pixels between the frame profiler's bar chart and the bottom left corner of the screen.
+const PROFILER_GRAPH_MARGIN: f32 = 8.0;
+/// The height in pixels of the world position written beside a crosshair; see `Crosshair::readout_font`.
+const CROSSHAIR_READOUT_SIZE: f32 = 16.0;
+/// The distance in pixels between the end of a crosshair's arms and its readout.
+const CROSSHAIR_READOUT_MARGIN: f32 = 4.0;
+
+/// How many of the most recently written vertices `BatchWriter` checks for duplicates of each new vertex.
+/// This is enough to catch the shared corners of adjacent triangles and quads, without searching the whole batch.
+const DEDUP_WINDOW: usize = 8;
+
+/// Writes polygons into a mapped vertex and index buffer, reusing recently written vertices that are identical
+/// instead of writing them again.
+///
+/// The vertex and index buffers are mapped separately, so their mappings have their own lifetimes `'v` and `'i`.
+struct BatchWriter<'a, 'v, 'i> {
+    vbo: &'a mut glium::buffer::WriteMapping<'v, [Vertex]>,
+    ibo: &'a mut glium::buffer::WriteMapping<'i, [u32]>,
+    /// The capacity of the vertex buffer.
+    max_verts: usize,
+    /// The capacity of the index buffer.
+    max_inds: usize,
+    /// The amount of vertices written so far.
+    vertices: usize,
+    /// The amount of indices written so far.
+    indices: usize,
+    /// The most recently written vertices along with their indices, oldest first.
+    recent: std::collections::VecDeque<(Vertex, u32)>,
+}
+
+impl<'a, 'v, 'i> BatchWriter<'a, 'v, 'i> {
+    fn new(
+        vbo: &'a mut glium::buffer::WriteMapping<'v, [Vertex]>,
+        ibo: &'a mut glium::buffer::WriteMapping<'i, [u32]>,
+        config: &BackendGLConfig,
+    ) -> Self {
+        BatchWriter {
+            vbo,
+            ibo,
+            max_verts: config.max_verts,
+            max_inds: config.max_inds,
+            vertices: 0,
+            indices: 0,
+            recent: std::collections::VecDeque::with_capacity(DEDUP_WINDOW),
+        }
+    }
+
+    /// Returns true if a polygon with the given amount of vertices and indices is guaranteed to fit in the batch.
+    fn has_room(&self, verts: usize, inds: usize) -> bool {
+        self.indices + inds <= self.max_inds && self.vertices + verts <= self.max_verts
+    }
+
+    /// Writes a polygon of at most four vertices into the batch. `inds` are indices into `verts`.
+    fn push(&mut self, verts: &[Vertex], inds: &[usize]) {
+        let mut mapped = [0u32; 4];
+        for (i, v) in verts.iter().enumerate() {
+            mapped[i] = match self.recent.iter().rev().find(|(recent, _)| recent == v) {
+                Some((_, index)) => *index,
+                None => {
+                    let index = self.vertices as u32;
+                    self.vbo.set(self.vertices, *v);
+                    self.vertices += 1;
+                    if self.recent.len() == DEDUP_WINDOW {
+                        self.recent.pop_front();
+                    }
+                    self.recent.push_back((*v, index));
+                    index
+                }
+            };
+        }
+        for i in inds {
+            self.ibo.set(self.indices, mapped[*i]);
+            self.indices += 1;
+        }
+    }
 }
-glium::implement_vertex!(Vertex, position, col);
 
 struct BatchGL {
     vbo: glium::VertexBuffer<Vertex>,
     ibo: glium::IndexBuffer<u32>,
     program: glium::Program,
+    /// Draws geometry in a single colour, given by the `outline_colour` uniform.
+    outline_program: glium::Program,
+    /// Draws the depth of geometry packed into its colour; see `depth_at_centre`.
+    depth_program: glium::Program,
+    /// A single pixel that `depth_program` draws into, so that depths can be read back from the GPU.
+    probe_colour: glium::texture::Texture2d,
+    /// The depth buffer used while drawing into `probe_colour`.
+    probe_depth: glium::framebuffer::DepthRenderBuffer,
 }
@@ -0,0 +1,281 @@
+use covalent::graphics;
+use covalent::graphics::{FontId, FramebufferId, PixelFormat, RenderChannel, RenderTarget, RenderVertex, Renderable, Sampler, ShaderId, ShaderSource, TextureId};
+use covalent::{Context, HeadlessDuration};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// A mesh that has been "uploaded" to the headless backend.
+pub struct MeshHeadless {
+    pub verts: Vec<RenderVertex>,
+    pub inds: Vec<u32>,
+}
+
+/// A framebuffer that has been created by the headless backend. Nothing is ever rendered to it.
+pub struct FramebufferHeadless {
+    pub width: u32,
+    pub height: u32,
+    pub channels: Vec<RenderChannel>,
+}
+
+/// A texture that has been "uploaded" to the headless backend.
+pub struct TextureHeadless {
+    /// The texture's pixels, converted to `PixelFormat::Rgba8` if needed.
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// The sampler given to `set_texture_sampler`, or the default one.
+    pub sampler: Sampler,
+}
+
+/// BackendHeadless is a graphics backend for covalent that never opens a window or touches the GPU.
+/// It steps the scene exactly like a real backend would, so it is useful for testing game logic (components,
+/// events, scene manipulation) in unit tests and on CI machines.
+///
+/// Frames are run back to back, as fast as possible. Nothing is rendered, and window commands are discarded.
+/// Once the last frame has run, a `ShutdownEvent` is fired, just as if the window had been closed.
+/// Meshes and textures are simply stored in memory, so tests can check what would have been uploaded.
+///
+/// To step a scene at a steady rate instead, e.g. on a dedicated server, use `covalent::run_headless`.
+///
+/// # Examples
+/// ```
+/// let scene = covalent::scene::Scene::new();
+/// let backend = covalent_headless::BackendHeadless::new(covalent::HeadlessDuration::Steps(10));
+/// covalent::execute(scene, covalent::graphics::Pipeline::new(), backend);
+/// ```
+pub struct BackendHeadless {
+    /// How many frames to run before `main_loop` returns.
+    duration: HeadlessDuration,
+
+    /// This map stores the meshes that have been created.
+    meshes: RefCell<HashMap<i64, MeshHeadless>>,
+    /// The ID that will be given to the next mesh to be created.
+    next_mesh_id: Cell<i64>,
+
+    /// This map stores the textures that have been loaded.
+    textures: RefCell<HashMap<TextureId, TextureHeadless>>,
+    /// The ID that will be given to the next texture to be loaded.
+    next_texture_id: Cell<TextureId>,
+
+    /// The sources of the shaders that have been loaded. Files are never read.
+    shaders: RefCell<Vec<ShaderSource>>,
+    /// The ID of the shader loaded from each source, so that loading it again returns the same ID.
+    shader_cache: RefCell<HashMap<ShaderSource, ShaderId>>,
+
+    /// The contents of the fonts that have been loaded. They are never parsed.
+    fonts: RefCell<Vec<Vec<u8>>>,
+
+    /// This map stores the framebuffers that have been created.
+    framebuffers: RefCell<HashMap<FramebufferId, FramebufferHeadless>>,
+    /// The ID that will be given to the next framebuffer to be created.
+    next_framebuffer_id: Cell<FramebufferId>,
+}
+
+impl BackendHeadless {
+    /// Creates a headless backend whose main loop runs for the given duration.
+    pub fn new(duration: HeadlessDuration) -> BackendHeadless {
+        BackendHeadless {
+            duration,
+            meshes: RefCell::from(HashMap::new()),
+            next_mesh_id: Cell::new(0),
+            textures: RefCell::from(HashMap::new()),
+            next_texture_id: Cell::new(0),
+            shaders: RefCell::from(Vec::new()),
+            shader_cache: RefCell::from(HashMap::new()),
+            fonts: RefCell::from(Vec::new()),
+            framebuffers: RefCell::from(HashMap::new()),
+            next_framebuffer_id: Cell::new(0),
+        }
+    }
+
+    /// Calls the given function with the mesh with the given ID, if it exists.
+    pub fn with_mesh<T>(&self, id: i64, f: impl FnOnce(&MeshHeadless) -> T) -> Option<T> {
+        self.meshes.borrow().get(&id).map(f)
+    }
+
+    /// Retrieves the amount of meshes that have been created and not deleted.
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.borrow().len()
+    }
+
+    /// Calls the given function with the texture with the given ID, if it exists.
+    pub fn with_texture<T>(&self, id: TextureId, f: impl FnOnce(&TextureHeadless) -> T) -> Option<T> {
+        self.textures.borrow().get(&id).map(f)
+    }
+
+    /// Retrieves the amount of distinct shaders that have been loaded.
+    pub fn shader_count(&self) -> usize {
+        self.shaders.borrow().len()
+    }
+
+    /// Calls the given function with the framebuffer with the given ID, if it exists.
+    pub fn with_framebuffer<T>(&self, id: FramebufferId, f: impl FnOnce(&FramebufferHeadless) -> T) -> Option<T> {
+        self.framebuffers.borrow().get(&id).map(f)
+    }
+}
+
+impl graphics::Backend for BackendHeadless {
+    fn main_loop(self, ctx: Context) {
+        let mut frames = 0u64;
+        loop {
+            let finished = match &self.duration {
+                HeadlessDuration::Steps(n) => frames >= *n,
+                HeadlessDuration::Until(flag) => flag.load(Ordering::SeqCst),
+            };
+            if finished {
+                break;
+            }
+
+            // There is no window to apply these to, but they must still be drained so the queue doesn't grow forever.
+            for command in ctx.drain_window_commands() {
+                log::trace!("Ignoring window command {:?}", command);
+            }
+            if ctx.take_shader_reload_request() {
+                log::trace!("Ignoring shader reload request");
+            }
+
+            ctx.begin_frame();
+            // Likewise, text is never drawn, but must be drained.
+            ctx.drain_text();
+            ctx.end_frame();
+            frames += 1;
+        }
+        ctx.process_shutdown();
+    }
+
+    fn create_mesh(&self, mut verts: Vec<RenderVertex>, inds: Vec<u32>) -> Renderable {
+        log::trace!(
+            "Creating mesh with {} verts, {} inds",
+            verts.len(),
+            inds.len()
+        );
+        graphics::fill_flat_normals(&mut verts, &inds);
+        let idx = self.next_mesh_id.get();
+        self.next_mesh_id.set(idx + 1);
+        self.meshes.borrow_mut().insert(idx, MeshHeadless { verts, inds });
+        Renderable::Mesh(idx)
+    }
+
+    fn delete_mesh(&self, r: &Renderable) {
+        let idx = match r {
+            Renderable::Mesh(idx) => *idx,
+            Renderable::TexturedMesh { mesh_id, .. } => *mesh_id,
+            _ => return,
+        };
+        self.meshes.borrow_mut().remove(&idx);
+    }
+
+    fn load_texture(&self, pixels: Vec<u8>, width: u32, height: u32, format: PixelFormat) -> TextureId {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "texture data must contain four bytes per pixel");
+        let rgba = format.to_rgba(pixels);
+        let id = self.next_texture_id.get();
+        self.next_texture_id.set(id + 1);
+        self.textures.borrow_mut().insert(id, TextureHeadless { rgba, width, height, sampler: Sampler::default() });
+        id
+    }
+
+    fn set_texture_sampler(&self, texture: TextureId, sampler: Sampler) {
+        match self.textures.borrow_mut().get_mut(&texture) {
+            Some(texture) => texture.sampler = sampler,
+            None => log::warn!("tried to set the sampler of texture {}, which was never created", texture),
+        }
+    }
+
+    fn create_framebuffer(&self, width: u32, height: u32, channels: &[RenderChannel]) -> FramebufferId {
+        let id = self.next_framebuffer_id.get();
+        for channel in channels {
+            assert!(RenderTarget::Framebuffer { id }.is_render_channel_supported(*channel), "render channel {:?} is not supported by framebuffers", channel);
+        }
+        self.next_framebuffer_id.set(id + 1);
+        self.framebuffers.borrow_mut().insert(id, FramebufferHeadless { width, height, channels: channels.to_vec() });
+        id
+    }
+
+    /// Nothing is ever rendered, so the texture is transparent black.
+    fn framebuffer_texture(&self, framebuffer: FramebufferId) -> TextureId {
+        let (width, height) = match self.framebuffers.borrow().get(&framebuffer) {
+            Some(framebuffer) => (framebuffer.width, framebuffer.height),
+            None => panic!("tried to use framebuffer {} as a texture, but it was never created", framebuffer),
+        };
+        self.load_texture(vec![0; (width * height * 4) as usize], width, height, PixelFormat::Rgba8)
+    }
+
+    /// Shaders are never read or compiled, so this never panics.
+    fn load_shader_source(&self, source: ShaderSource) -> ShaderId {
+        let mut shaders = self.shaders.borrow_mut();
+        *self.shader_cache.borrow_mut().entry(source.clone()).or_insert_with(|| {
+            shaders.push(source);
+            (shaders.len() - 1) as ShaderId
+        })
+    }
+
+    fn load_font(&self, ttf_bytes: Vec<u8>) -> FontId {
+        let mut fonts = self.fonts.borrow_mut();
+        fonts.push(ttf_bytes);
+        (fonts.len() - 1) as FontId
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use covalent::graphics::{Backend, Colour};
+    use covalent::{vec2, vec3};
+
+    #[test]
+    fn tick_listeners_run_each_frame() {
+        let scene = covalent::scene::Scene::new();
+        let node = scene.write().unwrap().new_node();
+        covalent::scene::TransformComponent::new(std::sync::Arc::clone(&node), vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0));
+
+        let backend = BackendHeadless::new(HeadlessDuration::Steps(10));
+        covalent::execute(scene, covalent::graphics::Pipeline::new(), backend);
+        assert!(node.read().unwrap().get_pos().x > 0.0);
+    }
+
+    #[test]
+    fn meshes_get_unique_ids() {
+        let backend = BackendHeadless::new(HeadlessDuration::Steps(0));
+        let vert = RenderVertex { pos: vec3(0.0, 0.0, 0.0), col: Colour::new(1.0, 1.0, 1.0), uv: vec2(0.0, 0.0), normal: vec3(0.0, 0.0, 0.0) };
+        let a = backend.create_mesh(vec![vert; 3], vec![0, 1, 2]);
+        let b = backend.create_mesh(vec![vert; 4], vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(2, backend.mesh_count());
+        if let (Renderable::Mesh(a), Renderable::Mesh(b)) = (&a, &b) {
+            assert_ne!(a, b);
+            assert_eq!(Some(4), backend.with_mesh(*b, |mesh| mesh.verts.len()));
+        } else {
+            panic!("create_mesh should return a mesh");
+        }
+
+        backend.delete_mesh(&a);
+        assert_eq!(1, backend.mesh_count());
+    }
+
+    #[test]
+    fn precompiled_shaders_are_reused() {
+        let backend = BackendHeadless::new(HeadlessDuration::Steps(0));
+        let sources = vec![ShaderSource::inline("a", "b"), ShaderSource::inline("c", "d")];
+        let ids = backend.precompile_shaders(&sources);
+        assert_eq!(2, backend.shader_count());
+        assert_eq!(ids[1], backend.load_shader("c", "d"));
+        assert_eq!(2, backend.shader_count());
+    }
+
+    #[test]
+    fn red_textures_are_red_in_either_format() {
+        let backend = BackendHeadless::new(HeadlessDuration::Steps(0));
+        let rgba = backend.load_texture(vec![255, 0, 0, 255], 1, 1, PixelFormat::Rgba8);
+        let bgra = backend.load_texture(vec![0, 0, 255, 255], 1, 1, PixelFormat::Bgra8);
+        for id in [rgba, bgra] {
+            assert_eq!(Some(vec![255, 0, 0, 255]), backend.with_texture(id, |texture| texture.rgba.clone()));
+        }
+    }
+
+    #[test]
+    fn framebuffer_textures_have_the_framebuffers_size() {
+        let backend = BackendHeadless::new(HeadlessDuration::Steps(0));
+        let framebuffer = backend.create_framebuffer(4, 2, &[RenderChannel::Colour(0), RenderChannel::Depth]);
+        let texture = backend.framebuffer_texture(framebuffer);
+        assert_eq!(Some((4, 2)), backend.with_texture(texture, |texture| (texture.width, texture.height)));
+    }
+}
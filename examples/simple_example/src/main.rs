@@ -23,61 +23,78 @@ fn setup_logger() -> Result<(), fern::InitError> {
 }
 
 fn create_scene(gbackend: &impl covalent::graphics::Backend, camera_matrices: Arc<RwLock<covalent::graphics::CameraMatrices>>) -> Arc<RwLock<covalent::scene::Scene>> {
-    use covalent::graphics::{RenderVertex, Colour};
+    use covalent::graphics::Colour;
     use covalent::vec3;
 
-    let s = covalent::scene::Scene::new();
-    let mut verts = Vec::new();
-    let mut inds = Vec::new();
-    for i in (-10..10).map(|x| x as f32) {
-        for j in (-10..10).map(|x| x as f32) {
-            for k in (-10..10).map(|x| x as f32) {
-                let v = verts.len() as u32;
-                verts.push(RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) });
-                verts.push(RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) });
-                verts.push(RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) });
-                verts.push(RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) });
-                inds.push(v);
-                inds.push(v+1);
-                inds.push(v+2);
-                inds.push(v);
-                inds.push(v+2);
-                inds.push(v+3);
-            }
-        }
-    }
-    s.write().unwrap().new_node().write().unwrap().renderable = Some(Arc::new(gbackend.create_mesh(verts, inds)));
-    let node = s.write().unwrap().new_node();
-    covalent::scene::TickDebugComponent::new(Arc::clone(&node));
-    covalent::scene::TickDebugComponent::new(Arc::clone(&node));
+    // A stack of planes, shaded red and green across each plane and blue up the stack.
+    let (verts, inds) = covalent::graphics::shapes::plane_with(2.0, 2.0, 20, |pos| Colour::new(pos.x, pos.y, 1.0));
+    // Each plane is textured with a black and white checkerboard.
+    let checkerboard = (0..8).flat_map(|y| (0..8).map(move |x| if (x + y) % 2 == 0 { 255u8 } else { 40u8 }))
+        .flat_map(|v| vec![v, v, v, 255u8])
+        .collect::<Vec<_>>();
+    let checkerboard = gbackend.load_texture(checkerboard, 8, 8, covalent::graphics::PixelFormat::Rgba8);
 
     let cam = covalent::graphics::PerspectiveCamera::new(
         covalent::pt3(1.1, 1.1, 0.0),
         covalent::vec3(-1.0, -1.0, -3.0),
         covalent::vec3(0.0, 0.0, 1.0),
     );
-    covalent::scene::CameraMotionComponent::new(Arc::clone(&node), cam, camera_matrices);
 
-    s
+    // A selected cube spinning above the grid, highlighted with an outline.
+    let (cube_verts, cube_inds) = covalent::graphics::shapes::cube_with(0.2, |pos| Colour::new(0.5 + pos.z * 2.5, 0.2, 0.2));
+
+    let grid = gbackend.create_mesh(verts, inds).with_texture(checkerboard);
+
+    covalent::scene::Scene::build(|builder| {
+        for k in (-10..10).map(|x| x as f32) {
+            builder.node()
+                .renderable(grid.clone())
+                .pos(vec3(0.0, 0.0, 0.02*k))
+                .tint(Colour::new(1.0, 1.0, 0.1*k))
+                .finish();
+        }
+        builder.node()
+            .renderable(gbackend.create_mesh(cube_verts, cube_inds))
+            .pos(vec3(0.0, 0.0, 0.5))
+            .outline(true)
+            .component(|node| { covalent::scene::RotateComponent::new(node, vec3(0.0, 0.0, 1.0), 1.0); })
+            .finish();
+        // A checkerboard sprite standing on the grid, which turns to face the camera as it moves.
+        builder.node()
+            .renderable(covalent::graphics::Renderable::Sprite {
+                texture_id: checkerboard,
+                size: covalent::vec2(0.3, 0.3),
+                anchor: covalent::graphics::SpriteAnchor::BottomCentre,
+            })
+            .pos(vec3(0.6, -0.6, 0.2))
+            .finish();
+        builder.node()
+            .component(covalent::scene::TickDebugComponent::new)
+            .component(covalent::scene::TickDebugComponent::new)
+            // WASD/QE to move, the mouse to look around, and O to toggle between perspective and orthographic views.
+            .component(|node| covalent::scene::CameraMotionComponent::new(node, cam, camera_matrices))
+            .finish();
+    })
 }
 
+/// Builds a grid like the stack of planes in `create_scene` out of individual quads, one node each, instead of meshes.
+/// Each quad is batched as four vertices, rather than the six needed for two separate triangles.
+/// Like `shapes::plane`, each quad is wound anticlockwise when viewed from above, so it faces upwards under
+/// `CullMode::CounterClockwiseFront`.
 pub fn create_scene_unoptimised() -> Arc<RwLock<covalent::scene::Scene>> {
     use covalent::graphics::{Renderable, RenderVertex, Colour};
-    use covalent::vec3;
+    use covalent::{vec2, vec3};
 
     let s = covalent::scene::Scene::new();
+    let mut nodes = s.write().unwrap().new_nodes(20 * 20 * 20).into_iter();
     for i in (-10..10).map(|x| x as f32) {
         for j in (-10..10).map(|x| x as f32) {
             for k in (-10..10).map(|x| x as f32) {
-                s.write().unwrap().new_node().write().unwrap().renderable = Some(Arc::new(Renderable::Triangle(
-                    RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) },
-                    RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) },
-                    RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) }
-                )));
-                s.write().unwrap().new_node().write().unwrap().renderable = Some(Arc::new(Renderable::Triangle(
-                    RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) },
-                    RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) },
-                    RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k) }
+                nodes.next().unwrap().write().unwrap().renderable = Some(Arc::new(Renderable::Quad(
+                    RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k), uv: vec2(0.0, 0.0), normal: vec3(0.0, 0.0, 0.0) },
+                    RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.01, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k), uv: vec2(0.0, 0.0), normal: vec3(0.0, 0.0, 0.0) },
+                    RenderVertex{ pos: vec3(0.1*i+0.09, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k), uv: vec2(0.0, 0.0), normal: vec3(0.0, 0.0, 0.0) },
+                    RenderVertex{ pos: vec3(0.1*i+0.01, 0.1*j+0.09, 0.02*k+0.0), col: Colour::new(0.1*i, 0.1*j, 0.1*k), uv: vec2(0.0, 0.0), normal: vec3(0.0, 0.0, 0.0) }
                 )));
             }
         }
@@ -85,7 +102,89 @@ pub fn create_scene_unoptimised() -> Arc<RwLock<covalent::scene::Scene>> {
     s
 }
 
+covalent::lock_data! {
+    WireframeData
+    polygon_mode: write covalent::graphics::PolygonMode
+}
+
+/// Switches between filled and wireframe rendering whenever F is pressed, to show the triangles that make up the scene.
+fn toggle_wireframe_on_key(scene: &Arc<RwLock<covalent::scene::Scene>>, polygon_mode: Arc<RwLock<covalent::graphics::PolygonMode>>) {
+    use covalent::graphics::PolygonMode;
+    use covalent::input::{ElementState, VirtualKeyCode};
+
+    let data = Arc::new(RwLock::new(WireframeData {
+        polygon_mode: Arc::downgrade(&polygon_mode),
+    }));
+    WireframeData::listen(&data, &scene.read().unwrap().events.key, |event, polygon_mode| {
+        if event.virtual_keycode == Some(VirtualKeyCode::F) && event.state == ElementState::Pressed && !event.is_repeat {
+            *polygon_mode = match *polygon_mode {
+                PolygonMode::Fill => PolygonMode::Line,
+                _ => PolygonMode::Fill,
+            };
+        }
+    });
+}
+
+covalent::lock_data! {
+    FullscreenData
+    scene: read covalent::scene::Scene
+}
+
+/// Switches between windowed and borderless fullscreen modes whenever Alt+Enter is pressed.
+fn toggle_fullscreen_on_alt_enter(scene: &Arc<RwLock<covalent::scene::Scene>>) {
+    use covalent::input::{ElementState, VirtualKeyCode};
+    use covalent::WindowMode;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let data = Arc::new(RwLock::new(FullscreenData {
+        scene: Arc::downgrade(scene),
+    }));
+    let fullscreen = AtomicBool::new(false);
+    FullscreenData::listen(&data, &scene.read().unwrap().events.key, move |event, scene| {
+        let alt = scene.input().is_key_pressed(VirtualKeyCode::LAlt) || scene.input().is_key_pressed(VirtualKeyCode::RAlt);
+        if alt && event.virtual_keycode == Some(VirtualKeyCode::Return) && event.state == ElementState::Pressed && !event.is_repeat {
+            let was_fullscreen = fullscreen.fetch_xor(true, Ordering::SeqCst);
+            scene.set_window_mode(if was_fullscreen { WindowMode::Windowed } else { WindowMode::Borderless });
+        }
+    });
+}
+
+const GRAYSCALE_VERTEX_SRC: &str = r#"
+    #version 140
+
+    in vec3 position;
+    in vec2 uv;
+
+    out vec2 io_uv;
+
+    void main() {
+        io_uv = uv;
+        gl_Position = vec4(position, 1.0);
+    }
+"#;
+
+const GRAYSCALE_FRAGMENT_SRC: &str = r#"
+    #version 140
+
+    in vec2 io_uv;
+
+    out vec4 color;
+
+    uniform sampler2D tex;
+
+    void main() {
+        vec4 c = texture(tex, io_uv);
+        if (io_uv.x < 0.5) {
+            float luma = dot(c.rgb, vec3(0.299, 0.587, 0.114));
+            c = vec4(luma, luma, luma, c.a);
+        }
+        color = c;
+    }
+"#;
+
 fn main() {
+    use covalent::graphics::{Backend, RenderChannel};
+
     match setup_logger() {
         Err(e) => {
             eprintln!("Could not instantiate fern logger: {:?}", e);
@@ -97,32 +196,60 @@ fn main() {
 
     let mut hints = covalent::DisplayHints::new();
     hints.title = String::from("Covalent | Simple Example");
+    hints.width = 768;
+    hints.height = 768;
+    let (width, height) = (hints.width, hints.height);
 
     let backend = covalent_gl::BackendGL::new(hints);
 
+    // The scene is rendered into an off-screen framebuffer, then copied onto the window by a post-processing phase.
+    let scene_fb = backend.create_framebuffer(width, height, &[RenderChannel::Colour(0), RenderChannel::Depth]);
+    let scene_target = covalent::graphics::RenderTarget::Framebuffer { id: scene_fb };
+
     let mut pipeline = covalent::graphics::Pipeline::new();
 
     pipeline.add_phase(
         0,
         "Clear".to_string(),
-        covalent::graphics::PipelinePhase::Clear {
-            target: covalent::graphics::RenderTarget::Window,
+        covalent::graphics::PipelinePhase::ClearGradient {
+            target: scene_target,
+            top: covalent::graphics::Colour::new(0.4, 0.6, 0.9),
+            bottom: covalent::graphics::Colour::new(0.85, 0.9, 1.0),
         },
     );
 
-    let render_settings = covalent::graphics::RenderSettings::default();
+    let mut render_settings = covalent::graphics::RenderSettings::default();
+    // Light the scene from above, and slightly to one side so that the faces of the cube are distinguishable.
+    render_settings.light_dir = Some(covalent::vec3(0.3, 0.5, -1.0));
     let render_camera_matrices = Arc::clone(&render_settings.camera_matrices);
+    let polygon_mode = Arc::clone(&render_settings.polygon_mode);
     pipeline.add_phase(
         100,
         "Render".to_string(),
         covalent::graphics::PipelinePhase::Render {
             settings: render_settings,
+            target: scene_target,
+        },
+    );
+
+    // Turn the left half of the screen grayscale.
+    let grayscale = backend.load_shader(GRAYSCALE_VERTEX_SRC, GRAYSCALE_FRAGMENT_SRC);
+    pipeline.add_phase(
+        200,
+        "Grayscale".to_string(),
+        covalent::graphics::PipelinePhase::PostProcess {
+            input: scene_fb,
             target: covalent::graphics::RenderTarget::Window,
+            shader: grayscale,
         },
     );
 
     let scene = create_scene(&backend, render_camera_matrices);
     //let scene = create_scene_unoptimised();
+    toggle_wireframe_on_key(&scene, polygon_mode);
+    toggle_fullscreen_on_alt_enter(&scene);
+    // F3 shows a histogram of frame times, to spot stutters.
+    scene.read().unwrap().frame_profiler.write().unwrap().set_toggle_key(Some(covalent::input::VirtualKeyCode::F3));
 
     covalent::execute(scene, pipeline, backend);
 }